@@ -0,0 +1,319 @@
+use crate::{
+    config::Config,
+    data::{AmendCommits, Modification, Session},
+    drive, message,
+    repo::{Git, Repository},
+};
+use console::style;
+use eyre::{bail, eyre, Result};
+use std::collections::HashMap;
+
+pub fn run(amend: AmendCommits, config: &Config, session: &Session) -> Result<Modification> {
+    let repo = Repository::discover()?;
+
+    let ids = if amend.navigators.is_empty() {
+        drive::session_navigators(session.scope)?
+    } else {
+        amend.navigators
+    };
+    if ids.is_empty() {
+        bail!(
+            "There is no drive session to take the navigators from, use {} to name them.",
+            style("--with").yellow()
+        );
+    }
+    let label = drive::email_label(Some(&repo))?;
+    let navigators = ids
+        .iter()
+        .map(|id| drive::match_navigator(id, config, label.as_deref()))
+        .collect::<Result<Vec<_>>>()?;
+    let trailers = drive::trailers(session, Some(&repo))?;
+
+    let range = amend.range.as_deref().unwrap_or("HEAD");
+    let targets = resolve(range)?;
+    if targets.is_empty() {
+        bail!("There are no commits in `{}`.", range);
+    }
+    if !amend.force {
+        refuse_pushed_commits(&repo, &targets)?;
+    }
+
+    let sign = repo.config_value("commit.gpgsign")?.as_deref() == Some("true");
+    let mut rewritten = HashMap::<String, String>::new();
+    let mut amended = 0_usize;
+
+    for line in commits_to_rewrite(&targets)?.lines() {
+        let mut shas = line.split_whitespace();
+        let sha = match shas.next() {
+            Some(sha) => sha,
+            None => continue,
+        };
+        let parents = shas
+            .map(|p| rewritten.get(p).map_or(p, String::as_str).to_string())
+            .collect::<Vec<_>>();
+        let parents_changed = line
+            .split_whitespace()
+            .skip(1)
+            .ne(parents.iter().map(String::as_str));
+
+        let commit = Commit::read(sha)?;
+        let message = if targets.iter().any(|t| t == sha) {
+            let author = commit.author();
+            let lines = drive::trailer_lines(&trailers, &navigators, Some(author.as_str()))?;
+            message::add_trailers(&commit.message, &lines, None)
+        } else {
+            commit.message.clone()
+        };
+
+        let message_changed = message != commit.message;
+        if !message_changed && !parents_changed {
+            continue;
+        }
+
+        if commit.signed && !sign {
+            bail!(
+                concat!(
+                    "The commit {} is signed and would lose its signature when it is rewritten.\n",
+                    "Set {} to sign the rewritten commits."
+                ),
+                style(&sha[..sha.len().min(7)]).cyan(),
+                style("commit.gpgsign").yellow()
+            );
+        }
+
+        let new_sha = commit.write(&parents, &message, sign)?;
+        rewritten.insert(sha.to_string(), new_sha);
+        if message_changed {
+            amended += 1;
+        }
+    }
+
+    let head = Git::new(["rev-parse", "--verify", "HEAD"]).run()?;
+    let head = head.trim();
+    match rewritten.get(head) {
+        Some(new_head) => {
+            Git::new([
+                "update-ref",
+                "-m",
+                concat!(env!("CARGO_PKG_NAME"), ": amend"),
+                "HEAD",
+                new_head.as_str(),
+                head,
+            ])
+            .run()?;
+            println!(
+                "Added the trailers to {} commit(s), HEAD is now at {}.",
+                amended,
+                style(&new_head[..new_head.len().min(7)]).cyan()
+            );
+        }
+        None => println!("All commits already have the trailers."),
+    }
+
+    Ok(Modification::Unchanged)
+}
+
+/// A single commit means only that commit, anything else is given to `git rev-list`.
+fn resolve(range: &str) -> Result<Vec<String>> {
+    let shas = if range.contains("..") || range.starts_with('^') || range.ends_with('!') {
+        Git::new(["rev-list", range, "--"]).run()?
+    } else {
+        Git::new(["rev-parse", "--verify", "--end-of-options"])
+            .arg(format!("{}^{{commit}}", range))
+            .run()?
+    };
+    Ok(shas.lines().map(String::from).collect())
+}
+
+fn refuse_pushed_commits(repo: &Repository, targets: &[String]) -> Result<()> {
+    let upstream = match repo.upstream()? {
+        Some(upstream) => upstream,
+        None => return Ok(()),
+    };
+    let upstream = upstream.as_str();
+
+    let unpushed = Git::new(["rev-list"])
+        .args(targets)
+        .args(["--not", upstream, "--"])
+        .run()?;
+    let pushed = targets
+        .iter()
+        .filter(|t| !unpushed.lines().any(|u| u == t.as_str()))
+        .map(|t| &t[..t.len().min(7)])
+        .collect::<Vec<_>>();
+
+    if pushed.is_empty() {
+        return Ok(());
+    }
+
+    Err(eyre!(
+        "Refusing to rewrite {} commit(s) that are already on {}: {}\nUse {} to rewrite them anyway.",
+        pushed.len(),
+        style(upstream).cyan(),
+        pushed.join(", "),
+        style("--force").yellow()
+    ))
+}
+
+/// All commits from the oldest target up to `HEAD`, oldest first, each followed by its parents.
+fn commits_to_rewrite(targets: &[String]) -> Result<String> {
+    let parents = Git::new(["rev-list", "--no-walk", "--parents"])
+        .args(targets)
+        .arg("--")
+        .run()?;
+    let mut boundary = parents
+        .lines()
+        .flat_map(|line| line.split_whitespace().skip(1))
+        .filter(|parent| !targets.iter().any(|t| t == parent))
+        .collect::<Vec<_>>();
+    boundary.sort_unstable();
+    boundary.dedup();
+
+    let commits = Git::new(["rev-list", "--reverse", "--topo-order", "--parents", "HEAD"])
+        .arg("--not")
+        .args(boundary)
+        .arg("--")
+        .run()?;
+
+    for target in targets {
+        if !commits.lines().any(|l| l.starts_with(target.as_str())) {
+            bail!(
+                "The commit {} is not part of the current branch.",
+                style(&target[..target.len().min(7)]).cyan()
+            );
+        }
+    }
+
+    Ok(commits)
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Commit {
+    tree: String,
+    author_name: String,
+    author_email: String,
+    author_date: String,
+    /// The `encoding` header, if the message is not in UTF-8.
+    encoding: Option<String>,
+    /// Whether the commit has a `gpgsig` header.
+    signed: bool,
+    message: String,
+}
+
+impl Commit {
+    fn read(sha: &str) -> Result<Self> {
+        let raw = Git::new(["cat-file", "commit", sha]).run()?;
+        Self::parse(&raw).ok_or_else(|| eyre!("Could not read the commit {}", sha))
+    }
+
+    fn parse(raw: &str) -> Option<Self> {
+        let (headers, message) = raw.split_once("\n\n").unwrap_or((raw, ""));
+        let mut tree = None;
+        let mut author = None;
+        let mut encoding = None;
+        let mut signed = false;
+        for header in headers.lines() {
+            match header.split_once(' ') {
+                Some(("tree", value)) => tree = Some(value),
+                Some(("author", value)) => author = Some(value),
+                Some(("encoding", value)) => encoding = Some(value.to_string()),
+                Some(("gpgsig" | "gpgsig-sha256", _)) => signed = true,
+                _ => {}
+            }
+        }
+
+        // author Name <email> 1700000000 +0100
+        let (ident, date) = author?.split_once("> ")?;
+        let (name, email) = ident.rsplit_once(" <")?;
+
+        Some(Self {
+            tree: tree?.to_string(),
+            author_name: name.to_string(),
+            author_email: email.to_string(),
+            author_date: date.to_string(),
+            encoding,
+            signed,
+            message: message.to_string(),
+        })
+    }
+
+    fn author(&self) -> String {
+        format!("{} <{}>", self.author_name, self.author_email)
+    }
+
+    /// Writes a copy of this commit with the new parents and message,
+    /// keeping the author and the encoding.
+    fn write(&self, parents: &[String], message: &str, sign: bool) -> Result<String> {
+        let encoding = self
+            .encoding
+            .iter()
+            .flat_map(|e| [String::from("-c"), format!("i18n.commitEncoding={}", e)]);
+        let mut git = Git::new(encoding).args(["commit-tree", self.tree.as_str()]);
+        for parent in parents {
+            git = git.args(["-p", parent.as_str()]);
+        }
+        if sign {
+            git = git.arg("-S");
+        }
+        let sha = git
+            .env("GIT_AUTHOR_NAME", &self.author_name)
+            .env("GIT_AUTHOR_EMAIL", &self.author_email)
+            .env("GIT_AUTHOR_DATE", &self.author_date)
+            .input(message)
+            .run()?;
+        Ok(sha.trim().to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_commit() {
+        let raw = concat!(
+            "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+            "parent 8d6b1b5bd6e0e1c3ba6a3c0ab5d4d9c8fb2fc9a3\n",
+            "author Bernd Bernd <foo@bar.org> 1700000000 +0100\n",
+            "committer Ralle <qux@bar.org> 1700000001 +0100\n",
+            "\n",
+            "Subject\n\nBody\n",
+        );
+
+        let commit = Commit::parse(raw).unwrap();
+        assert_eq!(
+            commit,
+            Commit {
+                tree: String::from("4b825dc642cb6eb9a060e54bf8d69288fbee4904"),
+                author_name: String::from("Bernd Bernd"),
+                author_email: String::from("foo@bar.org"),
+                author_date: String::from("1700000000 +0100"),
+                encoding: None,
+                signed: false,
+                message: String::from("Subject\n\nBody\n"),
+            }
+        );
+        assert_eq!(commit.author(), "Bernd Bernd <foo@bar.org>");
+    }
+
+    #[test]
+    fn parse_signed_commit_with_encoding() {
+        let raw = concat!(
+            "tree 4b825dc642cb6eb9a060e54bf8d69288fbee4904\n",
+            "author Bernd Bernd <foo@bar.org> 1700000000 +0100\n",
+            "committer Ralle <qux@bar.org> 1700000001 +0100\n",
+            "encoding ISO-8859-1\n",
+            "gpgsig -----BEGIN PGP SIGNATURE-----\n",
+            " \n",
+            " iQEzBAABCAAdFiEE\n",
+            " -----END PGP SIGNATURE-----\n",
+            "\n",
+            "Subject\n",
+        );
+
+        let commit = Commit::parse(raw).unwrap();
+        assert_eq!(commit.encoding.as_deref(), Some("ISO-8859-1"));
+        assert!(commit.signed);
+        assert_eq!(commit.message, "Subject\n");
+    }
+}
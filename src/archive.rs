@@ -0,0 +1,106 @@
+use crate::{
+    config::Config,
+    data::{Id, Kind, Modification},
+};
+use console::style;
+use eyre::{bail, Result};
+
+/// Hides the navigators from listing and driving, but keeps them for past commits.
+pub fn archive(config: &mut Config, ids: &[Id]) -> Result<Modification> {
+    set_archived(config, ids, true)
+}
+
+/// Makes archived navigators available for driving again.
+pub fn unarchive(config: &mut Config, ids: &[Id]) -> Result<Modification> {
+    set_archived(config, ids, false)
+}
+
+fn set_archived(config: &mut Config, ids: &[Id], archived: bool) -> Result<Modification> {
+    let verb = if archived { "archived" } else { "unarchived" };
+
+    let mut changed = Modification::Unchanged;
+    for id in ids {
+        if let Some(file) = config.included_from(Kind::Navigator, id) {
+            bail!(
+                "The navigator {} is defined in the included file {} and cannot be {} here.",
+                style(&**id).cyan(),
+                file.display(),
+                verb
+            );
+        }
+        let nav = match config.navigators.iter_mut().find(|n| id.same_as_nav(n)) {
+            Some(nav) => nav,
+            None => bail!("Alias {} does not exist.", style(&**id).cyan()),
+        };
+
+        if nav.archived == archived {
+            println!(
+                "The navigator {} is already {}.",
+                style(&*nav.alias).cyan(),
+                if archived { "archived" } else { "active" }
+            );
+            continue;
+        }
+        nav.archived = archived;
+        println!(
+            "The navigator {} is now {}.",
+            style(&*nav.alias).cyan(),
+            verb
+        );
+        changed = Modification::Changed;
+    }
+
+    Ok(changed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{
+            tests::{nav1, nav2},
+            Navigator,
+        },
+        ui::util::disable_colors,
+    };
+
+    #[test]
+    fn test_archive_and_unarchive() {
+        let mut config = Config::from_iter([nav1(), nav2()]);
+
+        let modified = archive(&mut config, &[Id::from("nav1")]).unwrap();
+        assert_eq!(modified, Modification::Changed);
+        assert!(config.navigators[0].archived);
+        assert!(!config.navigators[1].archived);
+
+        let modified = archive(&mut config, &[Id::from("nav1")]).unwrap();
+        assert_eq!(modified, Modification::Unchanged);
+
+        let modified = unarchive(&mut config, &[Id::from("nav1")]).unwrap();
+        assert_eq!(modified, Modification::Changed);
+        assert_eq!(config, Config::from_iter([nav1(), nav2()]));
+    }
+
+    #[test]
+    fn test_archive_by_secondary_alias() {
+        let nav = Navigator {
+            aliases: vec![Id::from("bernie")],
+            ..nav1()
+        };
+        let mut config = Config::from_iter([nav]);
+
+        archive(&mut config, &[Id::from("bernie")]).unwrap();
+        assert!(config.navigators[0].archived);
+    }
+
+    #[test]
+    fn test_archive_non_existing_alias() {
+        let _guard = disable_colors();
+
+        let mut config = Config::from_iter([nav1()]);
+        let err = archive(&mut config, &[Id::from("foo")]).unwrap_err();
+
+        assert_eq!(err.to_string(), "Alias foo does not exist.");
+        assert_eq!(config, Config::from_iter([nav1()]));
+    }
+}
@@ -1,17 +1,26 @@
 use crate::{
-    data::{Id, PartialNav, ShowNav},
+    data::{
+        parse_duration, AmendCommits, ChangeSeat, CheckConfig, Expiry, Hook, Id, InstallHook,
+        KeyFormat, PartialNav, RenameAlias, RunHook, SaveGuests, Scope, Session, ShowNav, Trailer,
+        VerifyCommits,
+    },
     Result,
 };
-use clap::{builder::ValueParser, error::ErrorKind, Arg, ArgAction, ArgMatches, Command};
-use std::{convert::Into, ffi::OsString, io::Write};
+use clap::{
+    builder::ValueParser, error::ErrorKind, parser::ValueSource, Arg, ArgAction, ArgMatches,
+    Command,
+};
+use std::{convert::Into, ffi::OsString, io::Write, path::PathBuf, str::FromStr, time::Duration};
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Action {
     DriveFromSelection,
     DriveWith(Id),
     DriveWithAll(Vec<Id>),
+    DriveWithSaving(SaveGuests),
     DriveAlone,
     ListNavigators,
+    ListAllNavigators,
     ListDrivers,
     ShowCurrentNavigator(ShowNav),
     NewNavigator(PartialNav),
@@ -19,17 +28,38 @@ pub enum Action {
     DeleteNavigatorFromSelection,
     DeleteNavigator(Id),
     DeleteAllNavigators(Vec<Id>),
+    RenameNavigator(RenameAlias),
+    PromoteNavigator(ChangeSeat),
+    ArchiveNavigators(Vec<Id>),
+    UnarchiveNavigators(Vec<Id>),
     DriveAsFromSelection,
     DriveAs(Id),
+    DriveAuto,
     NewDriver(PartialNav),
     EditDriver(PartialNav),
     DeleteDriverFromSelection,
     DeleteDriver(Id),
     DeleteAllDrivers(Vec<Id>),
+    RenameDriver(RenameAlias),
+    DemoteDriver(ChangeSeat),
+    Amend(AmendCommits),
+    Verify(VerifyCommits),
+    InstallHook(InstallHook),
+    RunHook(RunHook),
+    EditConfig,
+    CheckConfig(CheckConfig),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Args {
+    pub action: Action,
+    pub session: Session,
+    /// Never prompt, fail if input is missing instead.
+    pub no_input: bool,
 }
 
-pub fn action() -> Action {
-    Action::parse()
+pub fn args() -> Args {
+    Args::parse()
 }
 
 pub fn print_help_stderr() -> std::io::Result<()> {
@@ -43,7 +73,7 @@ pub fn print_help_stderr() -> std::io::Result<()> {
     out.flush()
 }
 
-impl Action {
+impl Args {
     fn parse() -> Self {
         Self::parse_from(std::env::args_os())
     }
@@ -54,7 +84,7 @@ impl Action {
         I::Item: Into<OsString> + Clone,
     {
         match Self::try_parse_from(args) {
-            Ok(action) => action,
+            Ok(args) => args,
             Err((mut app, e)) => {
                 let e = e.format(&mut app);
                 drop(app);
@@ -77,17 +107,110 @@ impl Action {
         let args =
             std::iter::once(OsString::from("test-prog")).chain(args.into_iter().map(Into::into));
 
-        let mut app = Self::app();
+        let mut app = Action::app();
         let matches = match app.try_get_matches_from_mut(args) {
             Ok(matches) => matches,
             Err(e) => return Err((app, e)),
         };
-        match Self::action_from_matches(matches) {
-            Ok(action) => Ok(action),
+        if let Err(e) = Self::check_session_position(&matches) {
+            return Err((app, e));
+        }
+        let session = Self::session_from_matches(&matches);
+        let no_input = matches.get_flag("no-input");
+        match Action::action_from_matches(matches) {
+            Ok(action) => Ok(Self {
+                action,
+                session,
+                no_input,
+            }),
             Err(e) => Err((app, e)),
         }
     }
 
+    /// The session options of `git drive` itself are not options of its subcommands.
+    fn check_session_position(matches: &ArgMatches) -> Result<(), clap::Error> {
+        let subcommand = match matches.subcommand_name() {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+        let given = ["worktree", "shared", "global", "trailer", "for", "until"]
+            .into_iter()
+            .find(|id| matches.value_source(id) == Some(ValueSource::CommandLine));
+        match given {
+            Some(id) => Err(clap::Error::raw(
+                ErrorKind::ArgumentConflict,
+                format!(
+                    "The argument '--{}' cannot be used before the subcommand '{}'",
+                    id, subcommand
+                ),
+            )),
+            None => Ok(()),
+        }
+    }
+
+    /// The session options of the subcommand, or of `git drive` itself.
+    /// Subcommands that do not take an option leave it unset.
+    fn session_from_matches(matches: &ArgMatches) -> Session {
+        let matches = matches.subcommand().map_or(matches, |(_, matches)| matches);
+        let flag = |id| matches.try_get_one::<bool>(id).ok().flatten() == Some(&true);
+
+        let scope = if flag("worktree") {
+            Some(Scope::Worktree)
+        } else if flag("shared") {
+            Some(Scope::Shared)
+        } else if flag("global") {
+            Some(Scope::Global)
+        } else {
+            None
+        };
+
+        let trailers = matches
+            .try_get_many::<Trailer>("trailer")
+            .ok()
+            .flatten()
+            .map(|trailers| trailers.cloned().collect())
+            .unwrap_or_default();
+
+        let expires = matches
+            .try_get_one::<Duration>("for")
+            .ok()
+            .flatten()
+            .map(|duration| Expiry::After(*duration))
+            .or_else(|| {
+                matches
+                    .try_get_one::<String>("until")
+                    .ok()
+                    .flatten()
+                    .map(|time| Expiry::At(time.clone()))
+            });
+
+        Session {
+            scope,
+            trailers,
+            expires,
+        }
+    }
+}
+
+impl Action {
+    #[cfg(test)]
+    fn parse_from<I>(args: I) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString> + Clone,
+    {
+        Args::parse_from(args).action
+    }
+
+    #[cfg(test)]
+    fn try_parse_from<I>(args: I) -> Result<Self, (Command, clap::Error)>
+    where
+        I: IntoIterator,
+        I::Item: Into<OsString> + Clone,
+    {
+        Args::try_parse_from(args).map(|args| args.action)
+    }
+
     fn app() -> Command {
         Command::new(env!("CARGO_PKG_NAME"))
             .version(env!("CARGO_PKG_VERSION"))
@@ -96,14 +219,53 @@ impl Action {
             .infer_long_args(true)
             .infer_subcommands(true)
             .subcommand_required(false)
+            .args(Self::scope_args())
+            .arg(Self::trailer_arg())
+            .args(Self::expiry_args())
+            .arg(
+                Arg::new("no-input")
+                    .long("no-input")
+                    .action(ArgAction::SetTrue)
+                    .global(true)
+                    .help("Never prompt, fail if input is missing instead"),
+            )
             .subcommand(
                 Command::new("with")
                     .arg(Self::ids_arg().required(true).num_args(1..))
+                    .args(Self::scope_args())
+                    .arg(Self::trailer_arg())
+                    .args(Self::expiry_args())
+                    .arg(
+                        Arg::new("save")
+                            .long("save")
+                            .action(ArgAction::SetTrue)
+                            .help("Add the guests given as `\"Name <email>\"` to the navigators"),
+                    )
+                    .arg(
+                        Arg::new("as")
+                            .long("as")
+                            .value_name("AS")
+                            .value_parser(ValueParser::string())
+                            .action(ArgAction::Set)
+                            .requires("save")
+                            .help("The identifier to use for the saved guest"),
+                    )
                     .about("Start driving with the specified navigator(s)"),
             )
-            .subcommand(Command::new("alone").about("Start driving alone"))
+            .subcommand(
+                Command::new("alone")
+                    .args(Self::scope_args())
+                    .about("Start driving alone"),
+            )
             .subcommand(
                 Command::new("list")
+                    .arg(
+                        Arg::new("all")
+                            .short('a')
+                            .long("all")
+                            .action(ArgAction::SetTrue)
+                            .help("Also list the archived navigators"),
+                    )
                     .subcommand(Command::new("me").about("It's me"))
                     .about("List known navigators"),
             )
@@ -128,6 +290,7 @@ impl Action {
                             .action(ArgAction::SetTrue)
                             .help("If set, fail the process if there are no current navigators"),
                     )
+                    .args(Self::scope_args())
                     .about("Show current navigators"),
             )
             .subcommand(
@@ -145,11 +308,96 @@ impl Action {
                     .arg(Self::ids_arg())
                     .about("Deletes navigator(s), either prompted for, or specified"),
             )
+            .subcommand(
+                Command::new("rename")
+                    .args(Self::rename_args())
+                    .about("Rename a navigator, either prompted for, or specified"),
+            )
+            .subcommand(
+                Command::new("promote")
+                    .arg(Self::seat_arg().help("The navigator to turn into a driver"))
+                    .arg(Self::key_arg())
+                    .arg(Self::key_format_arg())
+                    .arg(Self::keep_arg())
+                    .about("Turn a navigator into a driver, either prompted for, or specified"),
+            )
+            .subcommand(
+                Command::new("archive")
+                    .arg(Self::ids_arg().required(true))
+                    .about("Archive navigator(s), hiding them from listing and driving"),
+            )
+            .subcommand(
+                Command::new("unarchive")
+                    .arg(Self::ids_arg().required(true))
+                    .about("Make archived navigator(s) available for driving again"),
+            )
             .subcommand(
                 Command::new("as")
                     .arg(Self::ids_arg().num_args(..=1))
+                    .args(Self::scope_args())
                     .about("Change driver seat"),
             )
+            .subcommand(
+                Command::new("amend")
+                    .args(Self::amend_args())
+                    .about("Add the trailers of the navigators to existing commits"),
+            )
+            .subcommand(
+                Command::new("verify")
+                    .args(Self::verify_args())
+                    .about("Check that commits made while driving have the navigators' trailers"),
+            )
+            .subcommand(
+                Command::new("hook")
+                    .subcommand(
+                        Command::new("install")
+                            .arg(Self::hook_arg())
+                            .arg(Self::fix_arg())
+                            .arg(
+                                Arg::new("force")
+                                    .short('f')
+                                    .long("force")
+                                    .action(ArgAction::SetTrue)
+                                    .help("Replace an existing hook"),
+                            )
+                            .about("Install a git hook that enforces the trailers while driving"),
+                    )
+                    .subcommand(
+                        Command::new("run")
+                            .arg(Self::hook_arg())
+                            .arg(Self::fix_arg())
+                            .arg(
+                                Arg::new("file")
+                                    .value_name("FILE")
+                                    .value_parser(ValueParser::path_buf())
+                                    .action(ArgAction::Set)
+                                    .required(true)
+                                    .help("The file with the commit message"),
+                            )
+                            .hide(true)
+                            .about("Run a git hook, as called by git"),
+                    )
+                    .subcommand_required(true)
+                    .arg_required_else_help(true)
+                    .about("Manage the git hooks"),
+            )
+            .subcommand(
+                Command::new("config")
+                    .subcommand(Command::new("edit").about("Edit the roster in $VISUAL or $EDITOR"))
+                    .subcommand(
+                        Command::new("check")
+                            .arg(
+                                Arg::new("fix")
+                                    .long("fix")
+                                    .action(ArgAction::SetTrue)
+                                    .help("Fix the problems that have an unambiguous fix"),
+                            )
+                            .about("Report all problems in the roster file"),
+                    )
+                    .subcommand_required(true)
+                    .arg_required_else_help(true)
+                    .about("Manage the roster of navigators and drivers"),
+            )
             .subcommand(
                 Command::new("me")
                     .subcommand(Command::new("list").about("List known drivers"))
@@ -157,12 +405,14 @@ impl Action {
                         Command::new("new")
                             .args(Self::partial_nav_args())
                             .arg(Self::key_arg())
+                            .arg(Self::key_format_arg())
                             .about("Add a new driver, either prompted for, or specified"),
                     )
                     .subcommand(
                         Command::new("edit")
                             .args(Self::partial_nav_args())
                             .arg(Self::key_arg())
+                            .arg(Self::key_format_arg())
                             .about("Edit driver(s), either prompted for, or specified"),
                     )
                     .subcommand(
@@ -170,12 +420,159 @@ impl Action {
                             .arg(Self::ids_arg().help("The drivers"))
                             .about("Deletes driver(s), either prompted for, or specified"),
                     )
+                    .subcommand(
+                        Command::new("rename")
+                            .args(Self::rename_args())
+                            .about("Rename a driver, either prompted for, or specified"),
+                    )
+                    .subcommand(
+                        Command::new("demote")
+                            .arg(Self::seat_arg().help("The driver to turn into a navigator"))
+                            .arg(Self::keep_arg())
+                            .about("Turn a driver into a navigator, prompted for or specified"),
+                    )
+                    .subcommand(
+                        Command::new("auto")
+                            .about("Change driver seat to the one selected by `drive.driverRule`"),
+                    )
                     .subcommand_required(true)
                     .arg_required_else_help(true)
                     .about("Operate on the driver instead of the navigator"),
             )
     }
 
+    fn scope_args() -> [Arg; 3] {
+        [
+            Arg::new("worktree")
+                .long("worktree")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["shared", "global"])
+                .help("Drive only in the current working tree"),
+            Arg::new("shared")
+                .long("shared")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["worktree", "global"])
+                .help("Drive in all working trees of the repository"),
+            Arg::new("global")
+                .long("global")
+                .action(ArgAction::SetTrue)
+                .conflicts_with_all(["worktree", "shared"])
+                .help("Drive in all repositories"),
+        ]
+    }
+
+    fn trailer_arg() -> Arg {
+        Arg::new("trailer")
+            .long("trailer")
+            .value_name("KEY[=driver]")
+            .value_parser(Trailer::from_str)
+            .action(ArgAction::Append)
+            .help("Add this trailer instead of the configured ones, can be repeated")
+    }
+
+    fn expiry_args() -> [Arg; 2] {
+        [
+            Arg::new("for")
+                .long("for")
+                .value_name("DURATION")
+                .value_parser(parse_duration)
+                .action(ArgAction::Set)
+                .conflicts_with("until")
+                .help("End the drive after this long, e.g. `2h` or `1h30m`"),
+            Arg::new("until")
+                .long("until")
+                .value_name("TIME")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .conflicts_with("for")
+                .help("End the drive at this time, e.g. `18:00`"),
+        ]
+    }
+
+    fn amend_args() -> [Arg; 3] {
+        [
+            Arg::new("range")
+                .value_name("RANGE")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .help("The commit or range of commits to amend [default: HEAD]"),
+            Self::with_arg().help("Add these navigators instead of the current ones"),
+            Arg::new("force")
+                .short('f')
+                .long("force")
+                .action(ArgAction::SetTrue)
+                .help("Also rewrite commits that are already on the upstream branch"),
+        ]
+    }
+
+    fn verify_args() -> [Arg; 2] {
+        [
+            Arg::new("range")
+                .value_name("RANGE")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .required(true)
+                .help("The range of commits to check, e.g. `origin/main..`"),
+            Self::with_arg().help(
+                "Expect these navigators on all commits instead of using the session history",
+            ),
+        ]
+    }
+
+    fn with_arg() -> Arg {
+        Arg::new("with")
+            .long("with")
+            .value_name("NAVIGATOR")
+            .value_delimiter(',')
+            .value_parser(ValueParser::string())
+            .action(ArgAction::Append)
+    }
+
+    fn hook_arg() -> Arg {
+        Arg::new("hook")
+            .value_name("HOOK")
+            .value_parser(Hook::from_str)
+            .action(ArgAction::Set)
+            .required(true)
+            .help("The git hook, e.g. `commit-msg`")
+    }
+
+    fn fix_arg() -> Arg {
+        Arg::new("fix")
+            .long("fix")
+            .action(ArgAction::SetTrue)
+            .help("Add missing trailers instead of rejecting the commit")
+    }
+
+    fn rename_args() -> [Arg; 2] {
+        [
+            Arg::new("old")
+                .value_name("OLD")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .help("The current alias"),
+            Arg::new("new")
+                .value_name("NEW")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .help("The new alias"),
+        ]
+    }
+
+    fn seat_arg() -> Arg {
+        Arg::new("alias")
+            .value_name("ALIAS")
+            .value_parser(ValueParser::string())
+            .action(ArgAction::Set)
+    }
+
+    fn keep_arg() -> Arg {
+        Arg::new("keep")
+            .long("keep")
+            .action(ArgAction::SetTrue)
+            .help("Keep the original entry and only copy it")
+    }
+
     fn ids_arg() -> Arg {
         Arg::new("ids")
             .value_name("IDS")
@@ -194,7 +591,16 @@ impl Action {
             .help("The signing key to use")
     }
 
-    fn partial_nav_args() -> [Arg; 4] {
+    fn key_format_arg() -> Arg {
+        Arg::new("key-format")
+            .long("key-format")
+            .value_name("FORMAT")
+            .value_parser(KeyFormat::from_str)
+            .action(ArgAction::Set)
+            .help("The format of the signing key, one of `openpgp`, `x509`, or `ssh`")
+    }
+
+    fn partial_nav_args() -> [Arg; 6] {
         [
             Arg::new("as")
                 .long("as")
@@ -215,6 +621,18 @@ impl Action {
                 .value_parser(ValueParser::string())
                 .action(ArgAction::Set)
                 .help("The author's email"),
+            Arg::new("emails")
+                .long("emails")
+                .value_name("LABEL=EMAIL,...")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .help("The author's other emails, chosen with `alias:label`"),
+            Arg::new("aliases")
+                .long("aliases")
+                .value_name("ALIAS,...")
+                .value_parser(ValueParser::string())
+                .action(ArgAction::Set)
+                .help("Secondary identifiers that are accepted for the author's entry"),
             Arg::new("alias")
                 .value_name("ALIAS")
                 .value_parser(ValueParser::string())
@@ -230,6 +648,13 @@ impl Action {
             Some((name, matches)) => (name, matches),
         };
         match name.as_str() {
+            "with" if matches.get_flag("save") => Ok(Self::DriveWithSaving(SaveGuests {
+                ids: matches
+                    .remove_many::<String>("ids")
+                    .map(|ids| ids.map(Id).collect())
+                    .unwrap_or_default(),
+                alias: matches.remove_one::<String>("as").map(Id),
+            })),
             "with" => Ok(fold_map(
                 matches.remove_many::<String>("ids"),
                 Self::DriveAlone,
@@ -237,10 +662,11 @@ impl Action {
                 Self::DriveWithAll,
             )),
             "alone" => Ok(Self::DriveAlone),
-            "list" => Ok(matches
-                .subcommand()
-                .map(|_| Self::ListDrivers)
-                .unwrap_or(Self::ListNavigators)),
+            "list" => Ok(match matches.subcommand() {
+                Some(_) => Self::ListDrivers,
+                None if matches.get_flag("all") => Self::ListAllNavigators,
+                None => Self::ListNavigators,
+            }),
             "show" => Ok(Self::ShowCurrentNavigator(ShowNav {
                 color: matches.remove_one::<String>("color").expect("has default"),
                 fail_if_empty: matches.get_flag("fail-if-empty"),
@@ -253,12 +679,77 @@ impl Action {
                 Self::DeleteNavigator,
                 Self::DeleteAllNavigators,
             )),
+            "rename" => Ok(Self::RenameNavigator(Self::rename_alias(matches))),
+            "promote" => Ok(Self::PromoteNavigator(Self::change_seat(matches))),
+            "archive" => Ok(Self::ArchiveNavigators(Self::ids(matches))),
+            "unarchive" => Ok(Self::UnarchiveNavigators(Self::ids(matches))),
             "as" => Ok(fold_map(
                 matches.remove_many::<String>("ids"),
                 Self::DriveAsFromSelection,
                 Self::DriveAs,
                 |_| panic!("Cannot drive as multiple drivers"),
             )),
+            "amend" => Ok(Self::Amend(AmendCommits {
+                range: matches.remove_one::<String>("range"),
+                navigators: matches
+                    .remove_many::<String>("with")
+                    .map(|ids| ids.map(Id).collect())
+                    .unwrap_or_default(),
+                force: matches.get_flag("force"),
+            })),
+            "verify" => Ok(Self::Verify(VerifyCommits {
+                range: matches
+                    .remove_one::<String>("range")
+                    .expect("required argument"),
+                navigators: matches
+                    .remove_many::<String>("with")
+                    .map(|ids| ids.map(Id).collect())
+                    .unwrap_or_default(),
+            })),
+            "hook" => matches
+                .remove_subcommand()
+                .ok_or_else(|| {
+                    clap::Error::raw(
+                        ErrorKind::MissingSubcommand,
+                        "A subcommand is required but one was not provided.",
+                    )
+                })
+                .and_then(|(name, mut matches)| {
+                    let hook = matches
+                        .remove_one::<Hook>("hook")
+                        .expect("required argument");
+                    let fix = matches.get_flag("fix");
+                    Ok(match name.as_str() {
+                        "install" => Self::InstallHook(InstallHook {
+                            hook,
+                            fix,
+                            force: matches.get_flag("force"),
+                        }),
+                        "run" => Self::RunHook(RunHook {
+                            hook,
+                            fix,
+                            message_file: matches
+                                .remove_one::<PathBuf>("file")
+                                .expect("required argument"),
+                        }),
+                        otherwise => return Err(Self::unknown_command(otherwise)),
+                    })
+                }),
+            "config" => matches
+                .remove_subcommand()
+                .ok_or_else(|| {
+                    clap::Error::raw(
+                        ErrorKind::MissingSubcommand,
+                        "A subcommand is required but one was not provided.",
+                    )
+                })
+                .and_then(|(name, matches)| match name.as_str() {
+                    "edit" => Ok(Self::EditConfig),
+                    "check" => Ok(Self::CheckConfig(CheckConfig {
+                        fix: matches.get_flag("fix"),
+                    })),
+                    otherwise => Err(Self::unknown_command(otherwise)),
+                }),
             "me" => matches
                 .remove_subcommand()
                 .ok_or_else(|| {
@@ -278,6 +769,9 @@ impl Action {
                             Self::DeleteDriver,
                             Self::DeleteAllDrivers,
                         ),
+                        "rename" => Self::RenameDriver(Self::rename_alias(matches)),
+                        "demote" => Self::DemoteDriver(Self::change_seat(matches)),
+                        "auto" => Self::DriveAuto,
                         othwerise => return Err(Self::unknown_command(othwerise)),
                     })
                 }),
@@ -294,7 +788,39 @@ impl Action {
             )
             .with_name(matches.remove_one::<String>("name"))
             .with_email(matches.remove_one::<String>("email"))
+            .with_emails(matches.remove_one::<String>("emails"))
+            .with_aliases(matches.remove_one::<String>("aliases"))
             .with_key(key.then(|| matches.remove_one::<String>("key")).flatten())
+            .with_key_format(
+                key.then(|| matches.remove_one::<KeyFormat>("key-format"))
+                    .flatten(),
+            )
+    }
+
+    fn ids(mut matches: ArgMatches) -> Vec<Id> {
+        matches
+            .remove_many::<String>("ids")
+            .map(|ids| ids.map(Id).collect())
+            .unwrap_or_default()
+    }
+
+    fn rename_alias(mut matches: ArgMatches) -> RenameAlias {
+        RenameAlias {
+            old: matches.remove_one::<String>("old").map(Id),
+            new: matches.remove_one::<String>("new").map(Id),
+        }
+    }
+
+    fn change_seat(mut matches: ArgMatches) -> ChangeSeat {
+        ChangeSeat {
+            id: matches.remove_one::<String>("alias").map(Id),
+            key: matches.try_remove_one::<String>("key").ok().flatten(),
+            key_format: matches
+                .try_remove_one::<KeyFormat>("key-format")
+                .ok()
+                .flatten(),
+            keep: matches.get_flag("keep"),
+        }
     }
 
     fn unknown_command(name: &str) -> clap::Error {
@@ -343,6 +869,47 @@ mod tests {
         );
     }
 
+    #[test]
+    fn with_guest() {
+        let action = Action::parse_from(["with", "Jane Doe <jane@example.com>", "bob"]);
+        assert_eq!(
+            action,
+            Action::DriveWithAll(vec![
+                Id::from("Jane Doe <jane@example.com>"),
+                Id::from("bob")
+            ])
+        );
+    }
+
+    #[test]
+    fn with_saving_guests() {
+        let action = Action::parse_from(["with", "Jane Doe <jane@example.com>", "--save"]);
+        assert_eq!(
+            action,
+            Action::DriveWithSaving(SaveGuests {
+                ids: vec![Id::from("Jane Doe <jane@example.com>")],
+                alias: None,
+            })
+        );
+
+        let action =
+            Action::parse_from(["with", "Jane <jane@example.com>", "--save", "--as", "jd"]);
+        assert_eq!(
+            action,
+            Action::DriveWithSaving(SaveGuests {
+                ids: vec![Id::from("Jane <jane@example.com>")],
+                alias: Some(Id::from("jd")),
+            })
+        );
+    }
+
+    #[test]
+    fn with_as_requires_save() {
+        let (_, err) =
+            Action::try_parse_from(["with", "Jane <jane@example.com>", "--as", "jd"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+    }
+
     #[test]
     fn with_requires_at_least_one_id() {
         let (_, err) = Action::try_parse_from(["with"]).unwrap_err();
@@ -355,12 +922,221 @@ mod tests {
         assert_eq!(action, Action::DriveAlone);
     }
 
+    #[test]
+    fn session_scope_defaults_to_none() {
+        let args = Args::parse_from(["with", "foo"]);
+        assert_eq!(args.session, Session::default());
+    }
+
+    #[test]
+    fn session_scope_worktree() {
+        let args = Args::parse_from(["with", "foo", "--worktree"]);
+        assert_eq!(args.action, Action::DriveWith(Id::from("foo")));
+        assert_eq!(args.session.scope, Some(Scope::Worktree));
+    }
+
+    #[test]
+    fn session_scope_shared() {
+        let args = Args::parse_from(["alone", "--shared"]);
+        assert_eq!(args.action, Action::DriveAlone);
+        assert_eq!(args.session.scope, Some(Scope::Shared));
+    }
+
+    #[test]
+    fn session_scope_global() {
+        let args = Args::parse_from(["show", "--global"]);
+        assert_eq!(args.session.scope, Some(Scope::Global));
+    }
+
+    #[test]
+    fn session_scopes_conflict() {
+        let (_, err) = Args::try_parse_from(["show", "--worktree", "--shared"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn no_input() {
+        let args = Args::parse_from(["new", "--no-input", "--as", "foo"]);
+        assert!(args.no_input);
+
+        let args = Args::parse_from(["new"]);
+        assert!(!args.no_input);
+    }
+
+    #[test]
+    fn session_trailers() {
+        let args = Args::parse_from([
+            "with",
+            "foo",
+            "--trailer",
+            "Co-authored-by",
+            "--trailer",
+            "Signed-off-by=driver",
+        ]);
+        assert_eq!(
+            args.session.trailers,
+            [
+                "Co-authored-by".parse().unwrap(),
+                "Signed-off-by=driver".parse::<Trailer>().unwrap()
+            ]
+        );
+    }
+
+    #[test]
+    fn invalid_session_trailer() {
+        let (_, err) =
+            Args::try_parse_from(["with", "foo", "--trailer", "Signed off"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn session_args_of_drive_itself() {
+        let args = Args::parse_from(["--global", "--for", "2h"]);
+        assert_eq!(args.action, Action::DriveFromSelection);
+        assert_eq!(args.session.scope, Some(Scope::Global));
+        assert_eq!(
+            args.session.expires,
+            Some(Expiry::After(Duration::from_secs(7_200)))
+        );
+    }
+
+    #[test]
+    fn session_args_only_where_they_apply() {
+        let (_, err) = Args::try_parse_from(["list", "--global"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnknownArgument);
+
+        let (_, err) = Args::try_parse_from(["alone", "--for", "2h"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::UnknownArgument);
+
+        let (_, err) = Args::try_parse_from(["--for", "2h", "list"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn session_expires_after_duration() {
+        let args = Args::parse_from(["with", "foo", "--for", "2h"]);
+        assert_eq!(
+            args.session.expires,
+            Some(Expiry::After(Duration::from_secs(7_200)))
+        );
+    }
+
+    #[test]
+    fn session_expires_at_time() {
+        let args = Args::parse_from(["with", "foo", "--until", "18:00"]);
+        assert_eq!(
+            args.session.expires,
+            Some(Expiry::At(String::from("18:00")))
+        );
+    }
+
+    #[test]
+    fn session_expiry_conflicts() {
+        let (_, err) =
+            Args::try_parse_from(["with", "foo", "--for", "2h", "--until", "18:00"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ArgumentConflict);
+    }
+
+    #[test]
+    fn amend_head() {
+        let action = Action::parse_from(["amend"]);
+        assert_eq!(
+            action,
+            Action::Amend(AmendCommits {
+                range: None,
+                navigators: Vec::new(),
+                force: false,
+            })
+        );
+    }
+
+    #[test]
+    fn amend_range_with_navigators() {
+        let action = Action::parse_from(["amend", "HEAD~3..", "--with", "foo,bar", "-f"]);
+        assert_eq!(
+            action,
+            Action::Amend(AmendCommits {
+                range: Some(String::from("HEAD~3..")),
+                navigators: vec![Id::from("foo"), Id::from("bar")],
+                force: true,
+            })
+        );
+    }
+
+    #[test]
+    fn verify_range() {
+        let action = Action::parse_from(["verify", "origin/main..", "--with", "foo"]);
+        assert_eq!(
+            action,
+            Action::Verify(VerifyCommits {
+                range: String::from("origin/main.."),
+                navigators: vec![Id::from("foo")],
+            })
+        );
+    }
+
+    #[test]
+    fn verify_requires_a_range() {
+        let (_, err) = Action::try_parse_from(["verify"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+    }
+
+    #[test]
+    fn install_hook() {
+        let action = Action::parse_from(["hook", "install", "commit-msg", "--fix"]);
+        assert_eq!(
+            action,
+            Action::InstallHook(InstallHook {
+                hook: Hook::CommitMsg,
+                fix: true,
+                force: false,
+            })
+        );
+    }
+
+    #[test]
+    fn run_hook() {
+        let action = Action::parse_from(["hook", "run", "commit-msg", ".git/COMMIT_EDITMSG"]);
+        assert_eq!(
+            action,
+            Action::RunHook(RunHook {
+                hook: Hook::CommitMsg,
+                fix: false,
+                message_file: PathBuf::from(".git/COMMIT_EDITMSG"),
+            })
+        );
+    }
+
+    #[test]
+    fn install_unknown_hook() {
+        let (_, err) = Action::try_parse_from(["hook", "install", "pre-push"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn edit_config() {
+        let action = Action::parse_from(["config", "edit"]);
+        assert_eq!(action, Action::EditConfig);
+    }
+
+    #[test]
+    fn check_config() {
+        let action = Action::parse_from(["config", "check", "--fix"]);
+        assert_eq!(action, Action::CheckConfig(CheckConfig { fix: true }));
+    }
+
     #[test]
     fn list_navigators() {
         let action = Action::parse_from(["list"]);
         assert_eq!(action, Action::ListNavigators);
     }
 
+    #[test]
+    fn list_all_navigators() {
+        let action = Action::parse_from(["list", "--all"]);
+        assert_eq!(action, Action::ListAllNavigators);
+    }
+
     #[test]
     fn list_drivers() {
         let action = Action::parse_from(["list", "me"]);
@@ -507,6 +1283,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn edit_navigator_emails() {
+        let action = Action::parse_from(["edit", "--emails", "oss=foo@bar.org"]);
+        assert_eq!(
+            action,
+            Action::EditNavigator(
+                PartialNav::default().with_emails(String::from("oss=foo@bar.org"))
+            )
+        );
+    }
+
+    #[test]
+    fn edit_navigator_aliases() {
+        let action = Action::parse_from(["edit", "bernd", "--aliases", "bernie,bd"]);
+        assert_eq!(
+            action,
+            Action::EditNavigator(
+                PartialNav::default()
+                    .with_id(String::from("bernd"))
+                    .with_aliases(String::from("bernie,bd"))
+            )
+        );
+    }
+
     #[test]
     fn edit_navigator_all_with_as() {
         let action =
@@ -536,6 +1336,27 @@ mod tests {
         );
     }
 
+    #[test]
+    fn archive_navigators() {
+        let action = Action::parse_from(["archive", "foo", "bar"]);
+        assert_eq!(
+            action,
+            Action::ArchiveNavigators(vec![Id::from("foo"), Id::from("bar")])
+        );
+    }
+
+    #[test]
+    fn unarchive_navigator() {
+        let action = Action::parse_from(["unarchive", "foo"]);
+        assert_eq!(action, Action::UnarchiveNavigators(vec![Id::from("foo")]));
+    }
+
+    #[test]
+    fn archive_requires_navigators() {
+        let (_, err) = Action::try_parse_from(["archive"]).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::MissingRequiredArgument);
+    }
+
     #[test]
     fn delete_initiates_navigator_selection() {
         let action = Action::parse_from(["delete"]);
@@ -557,6 +1378,35 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rename_navigator() {
+        let action = Action::parse_from(["rename", "foo", "bar"]);
+        assert_eq!(
+            action,
+            Action::RenameNavigator(RenameAlias {
+                old: Some(Id::from("foo")),
+                new: Some(Id::from("bar")),
+            })
+        );
+
+        let action = Action::parse_from(["rename"]);
+        assert_eq!(action, Action::RenameNavigator(RenameAlias::default()));
+    }
+
+    #[test]
+    fn promote_navigator() {
+        let action = Action::parse_from(["promote", "foo", "--key", "bar", "--keep"]);
+        assert_eq!(
+            action,
+            Action::PromoteNavigator(ChangeSeat {
+                id: Some(Id::from("foo")),
+                key: Some(String::from("bar")),
+                key_format: None,
+                keep: true,
+            })
+        );
+    }
+
     #[test]
     fn as_initiates_selection() {
         let action = Action::parse_from(["as"]);
@@ -638,6 +1488,19 @@ mod tests {
         );
     }
 
+    #[test]
+    fn new_driver_key_format() {
+        let action = Action::parse_from(["me", "new", "--key", "foo.pub", "--key-format", "ssh"]);
+        assert_eq!(
+            action,
+            Action::NewDriver(
+                PartialNav::default()
+                    .with_key(String::from("foo.pub"))
+                    .with_key_format(KeyFormat::Ssh)
+            )
+        );
+    }
+
     #[test]
     fn new_driver_all_with_as() {
         let action = Action::parse_from([
@@ -754,6 +1617,30 @@ mod tests {
         );
     }
 
+    #[test]
+    fn rename_driver() {
+        let action = Action::parse_from(["me", "rename", "foo"]);
+        assert_eq!(
+            action,
+            Action::RenameDriver(RenameAlias {
+                old: Some(Id::from("foo")),
+                new: None,
+            })
+        );
+    }
+
+    #[test]
+    fn demote_driver() {
+        let action = Action::parse_from(["me", "demote"]);
+        assert_eq!(action, Action::DemoteDriver(ChangeSeat::default()));
+    }
+
+    #[test]
+    fn drive_auto() {
+        let action = Action::parse_from(["me", "auto"]);
+        assert_eq!(action, Action::DriveAuto);
+    }
+
     #[test]
     fn infer_commands() {
         fn test(short: &str, full: &str) {
@@ -0,0 +1,242 @@
+use crate::{
+    config::Config,
+    data::{DriverRule, Modification, RuleTarget, Scope, Session},
+    drive,
+    repo::{self, Repository},
+};
+use console::style;
+use directories::BaseDirs;
+use eyre::{bail, eyre, Result};
+use std::path::Path;
+
+const RULES: &str = "drive.driverRule";
+
+/// Takes the driver seat that the rules select for the current repository.
+pub fn run(config: &Config, session: &Session) -> Result<Modification> {
+    let repo = Repository::discover()?;
+    let rule = match matching_rule(&repo)? {
+        Some(rule) => rule,
+        None => bail!(
+            "None of the rules in {} matches this repository.",
+            style(RULES).yellow()
+        ),
+    };
+    take_seat_of(&rule, &repo, config, session)?;
+
+    Ok(Modification::Unchanged)
+}
+
+/// Takes the driver seat that the rules select before starting a drive,
+/// unless the repository already uses the email of that driver.
+pub fn apply(config: &Config, session: &Session) -> Result<()> {
+    let repo = match Repository::discover() {
+        Ok(repo) => repo,
+        Err(_) => return Ok(()),
+    };
+    let rule = match matching_rule(&repo)? {
+        Some(rule) => rule,
+        None => return Ok(()),
+    };
+    let email = repo.config_value("user.email")?;
+    let selected = config.drivers.iter().find(|d| rule.driver.same_as_drv(d));
+    if selected.map(|d| d.navigator.email.as_str()) == email.as_deref() {
+        return Ok(());
+    }
+
+    take_seat_of(&rule, &repo, config, session)
+}
+
+fn take_seat_of(
+    rule: &DriverRule,
+    repo: &Repository,
+    config: &Config,
+    session: &Session,
+) -> Result<()> {
+    let driver = match config.drivers.iter().find(|d| rule.driver.same_as_drv(d)) {
+        Some(driver) => driver,
+        None => bail!(
+            "The driver {} of the rule `{}` does not exist.",
+            style(&*rule.driver).cyan(),
+            rule
+        ),
+    };
+
+    if let Some(email) = repo.config_value("user.email")? {
+        if email != driver.navigator.email {
+            eprintln!(
+                "The `user.email` of this repository is {}, but the rule `{}` selects {} <{}>.",
+                email, rule, driver.navigator.name, driver.navigator.email
+            );
+        }
+    }
+
+    // the rules are about the repository, a global session does not change that
+    let scope = match session.scope {
+        Some(Scope::Worktree) => Scope::Worktree,
+        Some(Scope::Shared | Scope::Global) | None => Scope::Shared,
+    };
+    drive::take_seat(scope, driver)?;
+
+    println!("Driving as {} now.", style(&*rule.driver).cyan());
+    Ok(())
+}
+
+/// The rules in `drive.driverRule` that select the driver by this alias,
+/// from the config of the current repository, if there is one.
+pub fn rules_selecting(alias: &str) -> Result<Vec<DriverRule>> {
+    let repo = Repository::discover().ok();
+    Ok(repo::config_values(repo.as_ref(), RULES)?
+        .iter()
+        .filter_map(|rule| rule.parse::<DriverRule>().ok())
+        .filter(|rule| &*rule.driver == alias)
+        .collect())
+}
+
+/// The first rule in `drive.driverRule` that matches a remote or the working tree.
+fn matching_rule(repo: &Repository) -> Result<Option<DriverRule>> {
+    let rules = repo
+        .config_values(RULES)?
+        .iter()
+        .map(|rule| rule.parse::<DriverRule>().map_err(|e| eyre!(e)))
+        .collect::<Result<Vec<_>>>()?;
+    if rules.is_empty() {
+        return Ok(None);
+    }
+
+    let remotes = repo.remote_urls()?;
+    Ok(rules
+        .into_iter()
+        .find(|rule| matches(rule, &remotes, repo.work_tree())))
+}
+
+fn matches(rule: &DriverRule, remotes: &[String], work_tree: Option<&Path>) -> bool {
+    match rule.target {
+        RuleTarget::Remote => {
+            let pattern = normalize_remote(&rule.pattern);
+            remotes
+                .iter()
+                .any(|url| glob_matches(pattern.as_bytes(), normalize_remote(url).as_bytes()))
+        }
+        RuleTarget::Path => {
+            let pattern = expand_home(&rule.pattern);
+            work_tree.and_then(Path::to_str).map_or(false, |path| {
+                glob_matches(pattern.as_bytes(), path.as_bytes())
+            })
+        }
+    }
+}
+
+/// Turns the different forms of remote URLs into `host/path`,
+/// e.g. `git@github.com:acme/repo.git` and `https://github.com/acme/repo` into
+/// `github.com/acme/repo`.
+fn normalize_remote(url: &str) -> String {
+    let url = url.trim();
+    let (host, path) = match url.split_once("://") {
+        Some((_, rest)) => {
+            let (host, path) = rest.split_once('/').unwrap_or((rest, ""));
+            (
+                host.rsplit_once(':').map_or(host, |(host, _port)| host),
+                path,
+            )
+        }
+        None => url.split_once(':').unwrap_or(("", url)),
+    };
+    let host = host.rsplit_once('@').map_or(host, |(_user, host)| host);
+    let path = path.trim_end_matches('/');
+    let path = path.strip_suffix(".git").unwrap_or(path);
+
+    if host.is_empty() {
+        String::from(path)
+    } else {
+        format!("{}/{}", host, path)
+    }
+}
+
+fn expand_home(pattern: &str) -> String {
+    match (pattern.strip_prefix("~/"), BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => format!("{}/{}", dirs.home_dir().display(), rest),
+        _ => String::from(pattern),
+    }
+}
+
+/// Matches the whole text, where `*` matches within a path segment and `**` across segments.
+pub fn glob_matches(pattern: &[u8], text: &[u8]) -> bool {
+    match pattern {
+        [] => text.is_empty(),
+        [b'*', b'*', rest @ ..] => (0..=text.len()).any(|i| glob_matches(rest, &text[i..])),
+        [b'*', rest @ ..] => {
+            let segment = text.iter().position(|&c| c == b'/').unwrap_or(text.len());
+            (0..=segment).any(|i| glob_matches(rest, &text[i..]))
+        }
+        [c, rest @ ..] => text.first() == Some(c) && glob_matches(rest, &text[1..]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(rule: &str) -> DriverRule {
+        rule.parse().unwrap()
+    }
+
+    #[test]
+    fn normalize_remote_urls() {
+        for url in [
+            "git@github.com:acme/repo.git",
+            "https://github.com/acme/repo",
+            "https://user@github.com/acme/repo.git/",
+            "ssh://git@github.com:22/acme/repo.git",
+            "github.com:acme/repo",
+        ] {
+            assert_eq!(normalize_remote(url), "github.com/acme/repo", "{}", url);
+        }
+        assert_eq!(normalize_remote("/srv/git/repo.git"), "/srv/git/repo");
+    }
+
+    #[test]
+    fn glob_patterns() {
+        let glob = |pattern: &str, text: &str| glob_matches(pattern.as_bytes(), text.as_bytes());
+
+        assert!(glob("github.com/acme/*", "github.com/acme/repo"));
+        assert!(!glob("github.com/acme/*", "github.com/acme/group/repo"));
+        assert!(glob("github.com/acme/**", "github.com/acme/group/repo"));
+        assert!(glob("*/acme/*", "gitlab.com/acme/repo"));
+        assert!(!glob("github.com/acme/*", "github.com/acmecorp/repo"));
+        assert!(glob("/home/*/work/**", "/home/ralle/work/acme/repo"));
+        assert!(!glob("/home/*/work/**", "/home/ralle/private/repo"));
+    }
+
+    #[test]
+    fn match_rules_against_remotes_and_paths() {
+        let remotes = [String::from("git@github.com:acme/repo.git")];
+        let work_tree = Some(Path::new("/src/private/repo"));
+
+        assert!(matches(
+            &rule("remote:github.com/acme/*=work"),
+            &remotes,
+            work_tree
+        ));
+        assert!(matches(
+            &rule("remote:https://github.com/acme/*=work"),
+            &remotes,
+            work_tree
+        ));
+        assert!(!matches(
+            &rule("remote:github.com/other/*=work"),
+            &remotes,
+            work_tree
+        ));
+        assert!(matches(
+            &rule("path:/src/private/**=me"),
+            &remotes,
+            work_tree
+        ));
+        assert!(!matches(
+            &rule("path:/src/work/**=work"),
+            &remotes,
+            work_tree
+        ));
+        assert!(!matches(&rule("path:/src/private/**=me"), &remotes, None));
+    }
+}
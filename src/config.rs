@@ -1,15 +1,22 @@
-use crate::data::{Driver, Id, Navigator};
-use directories::ProjectDirs;
-use eyre::{bail, ensure, eyre, Result, WrapErr};
+use crate::data::{Driver, Email, Id, IdRef, KeyFormat, Kind, Navigator};
+use directories::{BaseDirs, ProjectDirs};
+use eyre::{bail, eyre, Result, WrapErr};
 use std::{
     convert::TryFrom,
     fs::{self, File},
     io::{ErrorKind, Write},
+    iter::Peekable,
     path::{Path, PathBuf},
 };
 
+mod check;
+mod editor;
+mod git;
 mod json;
 
+pub use check::check;
+pub use editor::edit;
+
 const APPLICATION: &str = env!("CARGO_PKG_NAME");
 const OLD_CONFIG_FILE: &str = concat!(env!("CARGO_PKG_NAME"), "_config.json");
 const CONFIG_FILE: &str = concat!(env!("CARGO_PKG_NAME"), "_config.gitdrive");
@@ -18,13 +25,109 @@ const CONFIG_FILE: &str = concat!(env!("CARGO_PKG_NAME"), "_config.gitdrive");
 pub struct Config {
     pub navigators: Vec<Navigator>,
     pub drivers: Vec<Driver>,
+    /// The `include:` directives of the personal config file, as written.
+    pub includes: Vec<String>,
+    /// The entries that each (transitively) included file provides.
+    included: Vec<Source>,
+}
+
+/// The entries that one included config file provides,
+/// i.e. those that are not overridden by a later include or by the personal config file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Source {
+    file: PathBuf,
+    navigators: Vec<Navigator>,
+    drivers: Vec<Driver>,
+}
+
+impl Config {
+    /// Returns the file from which the entry was included,
+    /// or `None` if the entry belongs to the personal config file.
+    pub fn included_from(&self, kind: Kind, id: &Id) -> Option<&Path> {
+        let source = match kind {
+            Kind::Navigator => {
+                let nav = self.navigators.iter().find(|n| id.same_as_nav(n))?;
+                self.source_of_nav(nav)
+            }
+            Kind::Driver => {
+                let drv = self.drivers.iter().find(|d| id.same_as_drv(d))?;
+                self.source_of_drv(drv)
+            }
+        };
+        source.map(|s| s.file.as_path())
+    }
+
+    fn is_included_nav(&self, nav: &Navigator) -> bool {
+        self.source_of_nav(nav).is_some()
+    }
+
+    fn is_included_drv(&self, drv: &Driver) -> bool {
+        self.source_of_drv(drv).is_some()
+    }
+
+    /// Returns the include that provides the navigator.
+    /// An included entry that has been edited since becomes a personal override.
+    fn source_of_nav(&self, nav: &Navigator) -> Option<&Source> {
+        self.included
+            .iter()
+            .find(|s| s.navigators.iter().any(|n| n.alias == nav.alias))
+            .filter(|s| s.navigators.contains(nav))
+    }
+
+    /// Returns the include that provides the driver.
+    /// An included entry that has been edited since becomes a personal override.
+    fn source_of_drv(&self, drv: &Driver) -> Option<&Source> {
+        self.included
+            .iter()
+            .find(|s| s.drivers.iter().any(|d| d.id() == drv.id()))
+            .filter(|s| s.drivers.contains(drv))
+    }
+
+    /// Adds the entries of the included files.
+    /// Later includes take precedence over earlier ones
+    /// and the entries of the personal config take precedence over all includes.
+    fn merge_included(&mut self, mut included: Vec<Source>) {
+        fn layer<T: IdRef + Clone>(own: &mut Vec<T>, mut layers: Vec<&mut Vec<T>>) {
+            let mut merged = Vec::<T>::new();
+            for entry in layers.iter().flat_map(|l| l.iter()) {
+                match merged.iter_mut().find(|e| e.id() == entry.id()) {
+                    Some(existing) => *existing = entry.clone(),
+                    None => merged.push(entry.clone()),
+                }
+            }
+
+            // Every layer keeps only the entries that it provides,
+            // so that an entry is attributed to exactly one source.
+            let mut taken = own.iter().map(|e| e.id().clone()).collect::<Vec<_>>();
+            for layer in layers.iter_mut().rev() {
+                layer.retain(|e| {
+                    let provides = !taken.contains(e.id());
+                    taken.push(e.id().clone());
+                    provides
+                });
+            }
+
+            merged.retain(|e| !own.iter().any(|o| o.id() == e.id()));
+            own.extend(merged);
+        }
+
+        layer(
+            &mut self.navigators,
+            included.iter_mut().map(|s| &mut s.navigators).collect(),
+        );
+        layer(
+            &mut self.drivers,
+            included.iter_mut().map(|s| &mut s.drivers).collect(),
+        );
+        self.included = included;
+    }
 }
 
 impl FromIterator<Navigator> for Config {
     fn from_iter<T: IntoIterator<Item = Navigator>>(iter: T) -> Self {
         Self {
             navigators: iter.into_iter().collect(),
-            drivers: Vec::new(),
+            ..Self::default()
         }
     }
 }
@@ -32,8 +135,8 @@ impl FromIterator<Navigator> for Config {
 impl FromIterator<Driver> for Config {
     fn from_iter<T: IntoIterator<Item = Driver>>(iter: T) -> Self {
         Self {
-            navigators: Vec::new(),
             drivers: iter.into_iter().collect(),
+            ..Self::default()
         }
     }
 }
@@ -73,137 +176,426 @@ impl FromIterator<Entity> for Config {
         Self {
             navigators,
             drivers,
+            ..Self::default()
         }
     }
 }
 
+/// Where the roster is read from and written to.
+pub trait Backend {
+    fn load(&self) -> Result<Config>;
+
+    fn store(&self, config: &Config) -> Result<()>;
+}
+
 pub fn load() -> Result<Config> {
-    let file = config_file(Mode::Read)?;
-
-    match &file {
-        ConfigFile::New(path) => load_from(path),
-        ConfigFile::Old(path) => {
-            let cfg = json::load_from(path)?;
-            store(&cfg)?;
-            Ok(cfg)
+    backend()?.load()
+}
+
+pub fn store(config: &Config) -> Result<()> {
+    backend()?.store(config)
+}
+
+/// Returns the backend that the user selected with `git config --global drive.backend`.
+fn backend() -> Result<Storage> {
+    match git::configured_backend()?.as_deref() {
+        None | Some("file") => Ok(Storage::File(FileBackend)),
+        Some("git") => Ok(Storage::Git(git::GitBackend::global())),
+        Some(otherwise) => bail!(
+            "Unknown backend `{}` in `drive.backend`, expected either `file` or `git`.",
+            otherwise
+        ),
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Storage {
+    File(FileBackend),
+    Git(git::GitBackend),
+}
+
+impl Backend for Storage {
+    fn load(&self) -> Result<Config> {
+        match self {
+            Self::File(backend) => backend.load(),
+            Self::Git(backend) => backend.load(),
         }
-        ConfigFile::Missing => Ok(Config::default()),
     }
-    .wrap_err_with(|| {
-        format!(
-            concat!(
-                "The file `{}` could not be read.\n",
-                "Please make sure that the config file is accessible and is properly formatted.",
-            ),
-            file
-        )
-    })
+
+    fn store(&self, config: &Config) -> Result<()> {
+        match self {
+            Self::File(backend) => backend.store(config),
+            Self::Git(backend) => backend.store(config),
+        }
+    }
+}
+
+/// Stores the roster in a file in the user's config directory.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct FileBackend;
+
+impl Backend for FileBackend {
+    fn load(&self) -> Result<Config> {
+        let file = config_file(Mode::Read)?;
+
+        match &file {
+            ConfigFile::New(path) => load_from(path),
+            ConfigFile::Old(path) => {
+                let cfg = json::load_from(path)?;
+                self.store(&cfg)?;
+                Ok(cfg)
+            }
+            ConfigFile::Missing => Ok(Config::default()),
+        }
+        .wrap_err_with(|| {
+            format!(
+                concat!(
+                    "The file `{}` could not be read.\n",
+                    "Please make sure that the config file is accessible and is properly formatted.",
+                ),
+                file
+            )
+        })
+    }
+
+    fn store(&self, config: &Config) -> Result<()> {
+        let file = match config_file(Mode::Write)? {
+            ConfigFile::New(path) | ConfigFile::Old(path) => path,
+            ConfigFile::Missing => bail!("The configuration directoy could not be found"),
+        };
+
+        store_in(config, &file)
+    }
+}
+
+/// Keeps the roster in memory only.
+#[cfg(test)]
+#[derive(Debug, Default)]
+pub struct MemoryBackend(std::cell::RefCell<Config>);
+
+#[cfg(test)]
+impl Backend for MemoryBackend {
+    fn load(&self) -> Result<Config> {
+        Ok(self.0.borrow().clone())
+    }
+
+    fn store(&self, config: &Config) -> Result<()> {
+        self.0.replace(config.clone());
+        Ok(())
+    }
 }
 
 fn load_from(path: &Path) -> Result<Config> {
+    let mut config = read_config(path)?;
+
+    let mut chain = vec![fs::canonicalize(path)?];
+    let mut included = Vec::new();
+    collect_includes(path, &config.includes, &mut chain, &mut included)?;
+    config.merge_included(included);
+
+    Ok(config)
+}
+
+fn read_config(path: &Path) -> Result<Config> {
     let content = fs::read_to_string(path)?;
     deserialize_config(&content).with_context(|| format!("Reading config from {}", path.display()))
 }
 
+/// Reads all included files, depth first, so that a file comes after everything it includes.
+/// `chain` holds the files that are currently being included and is used to detect cycles.
+fn collect_includes(
+    from: &Path,
+    includes: &[String],
+    chain: &mut Vec<PathBuf>,
+    sources: &mut Vec<Source>,
+) -> Result<()> {
+    for include in includes {
+        let file = include_path(from, include)
+            .wrap_err_with(|| format!("Including `{}` from {}", include, from.display()))?;
+
+        if chain.contains(&file) {
+            let cycle = chain
+                .iter()
+                .chain(Some(&file))
+                .map(|f| f.display().to_string())
+                .collect::<Vec<_>>();
+            bail!("Include cycle detected: {}", cycle.join(" -> "));
+        }
+        if sources.iter().any(|s| s.file == file) {
+            continue;
+        }
+
+        let config = read_config(&file)?;
+        chain.push(file.clone());
+        collect_includes(&file, &config.includes, chain, sources)?;
+        chain.pop();
+
+        sources.push(Source {
+            file,
+            navigators: config.navigators,
+            drivers: config.drivers,
+        });
+    }
+
+    Ok(())
+}
+
+/// Resolves an include relative to the directory of the including file, expanding a leading `~`.
+fn include_path(from: &Path, include: &str) -> Result<PathBuf> {
+    let path = match include.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with(['/', '\\']) => {
+            let dirs = BaseDirs::new().ok_or_else(|| eyre!("Could not find the home directory"))?;
+            dirs.home_dir().join(rest.trim_start_matches(['/', '\\']))
+        }
+        _ => from
+            .parent()
+            .map_or_else(|| PathBuf::from(include), |dir| dir.join(include)),
+    };
+
+    fs::canonicalize(&path).wrap_err_with(|| format!("Could not find {}", path.display()))
+}
+
 fn deserialize_config(content: &str) -> Result<Config> {
     fn read_nav(alias: &str, line: &str, line_number: usize) -> Result<Navigator> {
         let co_author = co_authors::CoAuthor::try_from(line).map_err(|e| {
-            eyre!(
-                "Expected `Co-Authored-By: $name <$email>` in line {}, but got: {}",
+            SyntaxError::new(
                 line_number,
-                e
+                format!(
+                    "Expected `Co-Authored-By: $name <$email>` in line {}, but got: {}",
+                    line_number, e
+                ),
             )
         })?;
         Ok(Navigator {
             alias: Id(String::from(alias)),
+            aliases: Vec::new(),
             name: String::from(co_author.name),
             email: co_author.mail.map(String::from).unwrap_or_default(),
+            emails: Vec::new(),
+            archived: false,
         })
     }
 
-    let mut lines = content.lines().enumerate().map(|(ln, l)| (ln + 1, l));
+    /// Reads the optional `alias:`, `email: $label=$email`, and `archived:` lines
+    /// after the Co-Authored-By.
+    fn read_details<'a>(
+        lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+        nav: &mut Navigator,
+    ) -> Result<()> {
+        while let Some((line_number, line)) = lines.peek().copied() {
+            if let Some(alias) = line.strip_prefix("alias:") {
+                lines.next();
+                let alias = alias.trim();
+                if alias.is_empty() {
+                    return Err(SyntaxError::new(
+                        line_number,
+                        format!("The alias in line {} is empty.", line_number),
+                    )
+                    .into());
+                }
+                nav.aliases.push(Id::from(alias));
+                continue;
+            }
+
+            if let Some(archived) = line.strip_prefix("archived:") {
+                lines.next();
+                nav.archived = match archived.trim() {
+                    "true" => true,
+                    "false" => false,
+                    otherwise => {
+                        return Err(SyntaxError::new(
+                            line_number,
+                            format!(
+                                "Expected `true` or `false` in line {}, but got `{}`.",
+                                line_number, otherwise
+                            ),
+                        )
+                        .into());
+                    }
+                };
+                continue;
+            }
+
+            let email = match line.strip_prefix("email:") {
+                Some(email) => email,
+                None => break,
+            };
+            lines.next();
+
+            let email = email.parse::<Email>().map_err(|e| {
+                SyntaxError::new(
+                    line_number,
+                    format!("Invalid email in line {}: {}", line_number, e),
+                )
+            })?;
+            if nav.email_for(&email.label).is_some() {
+                return Err(SyntaxError::new(
+                    line_number,
+                    format!(
+                        "The label `{}` in line {} is used more than once.",
+                        email.label, line_number
+                    ),
+                )
+                .into());
+            }
+            nav.emails.push(email);
+        }
+        Ok(())
+    }
 
-    let (_, version) = lines
-        .next()
-        .ok_or_else(|| eyre!("The config file is empty, expected at least a version key."))?;
-    let version = version
-        .strip_prefix("version: ")
-        .ok_or_else(|| eyre!("Expected `version: $version`, but got `{version}` in line 1."))?;
-    ensure!(version == "1", "Unknown version: {}.", version);
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(ln, l)| (ln + 1, l))
+        .filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'))
+        .peekable();
+
+    let (line_number, version) = lines.next().ok_or_else(|| {
+        SyntaxError::new(
+            1,
+            "The config file is empty, expected at least a version key.",
+        )
+    })?;
+    let version = version.strip_prefix("version: ").ok_or_else(|| {
+        SyntaxError::new(
+            line_number,
+            format!("Expected `version: $version`, but got `{version}` in line {line_number}."),
+        )
+    })?;
+    if version != "1" {
+        return Err(SyntaxError::new(line_number, format!("Unknown version: {}.", version)).into());
+    }
 
     let mut navigators = Vec::new();
     let mut drivers = Vec::new();
+    let mut includes = Vec::new();
 
     while let Some((line_number, line)) = lines.next() {
         let (kind, alias) = line.split_once(": ").ok_or_else(|| {
-            eyre!("Expected `$type: $alias`, but got `{line}` in line {line_number}.")
+            SyntaxError::new(
+                line_number,
+                format!("Expected `$type: $alias`, but got `{line}` in line {line_number}."),
+            )
         })?;
 
         match kind {
+            "include" => includes.push(String::from(alias.trim())),
             "navigator" => {
                 let (line_number, nav) = lines.next().ok_or_else(|| {
-                    eyre!(
-                        "Expected `Co-Authored-By: $name <$email>` in line {}, but reached the end of the file.",
-                        line_number + 1
+                    SyntaxError::new(
+                        line_number + 1,
+                        format!(
+                            "Expected `Co-Authored-By: $name <$email>` in line {}, but reached the end of the file.",
+                            line_number + 1
+                        ),
                     )
                 })?;
-                let nav = read_nav(alias, nav, line_number)?;
+                let mut nav = read_nav(alias, nav, line_number)?;
+                read_details(&mut lines, &mut nav)?;
                 navigators.push(nav);
             }
             "driver" => {
                 let (line_number, key) = lines.next().ok_or_else(|| {
-                    eyre!(
-                        "Expected `key: $key` in line {}, but reached the end of the file.",
-                        line_number + 1
+                    SyntaxError::new(
+                        line_number + 1,
+                        format!(
+                            "Expected `key: $key` in line {}, but reached the end of the file.",
+                            line_number + 1
+                        ),
                     )
                 })?;
                 let key = match key.split_once(':') {
                     Some(("key", key)) => Some(key).map(str::trim).filter(|k| !k.is_empty()),
                     Some(_) | None => {
-                        bail!("Expected `key: $key` in line {line_number}, but got {key}.")
+                        return Err(SyntaxError::new(
+                            line_number,
+                            format!("Expected `key: $key` in line {line_number}, but got {key}."),
+                        )
+                        .into())
                     }
                 };
 
+                let mut key_format = KeyFormat::default();
+                if let Some((line_number, format)) = lines.peek().copied() {
+                    if let Some(format) = format.strip_prefix("format:") {
+                        lines.next();
+                        key_format = format.trim().parse().map_err(|e| {
+                            SyntaxError::new(
+                                line_number,
+                                format!("Invalid key format in line {}: {}", line_number, e),
+                            )
+                        })?;
+                    }
+                }
+
                 let (line_number, nav) = lines.next().ok_or_else(|| {
-                    eyre!(
-                        "Expected a Co-Authored-By in line {}, but reached the end of the file.",
-                        line_number + 1
+                    SyntaxError::new(
+                        line_number + 1,
+                        format!(
+                            "Expected a Co-Authored-By in line {}, but reached the end of the file.",
+                            line_number + 1
+                        ),
                     )
                 })?;
-                let nav = read_nav(alias, nav, line_number)?;
+                let mut nav = read_nav(alias, nav, line_number)?;
+                read_details(&mut lines, &mut nav)?;
                 let drv = Driver {
                     navigator: nav,
                     key: key.map(String::from),
+                    key_format,
                 };
 
                 drivers.push(drv);
             }
-            otherwise => bail!(
-                concat!(
-                    "Unexpted type `{} in line {}, ",
-                    "expected either `navigator` or `driver`."
-                ),
-                otherwise,
-                line_number
-            ),
+            otherwise => {
+                return Err(SyntaxError::new(
+                    line_number,
+                    format!(
+                        concat!(
+                            "Unexpted type `{} in line {}, ",
+                            "expected either `navigator`, `driver`, or `include`."
+                        ),
+                        otherwise, line_number
+                    ),
+                )
+                .into())
+            }
         }
     }
 
     Ok(Config {
         navigators,
         drivers,
+        includes,
+        included: Vec::new(),
     })
 }
 
-pub fn store(config: &Config) -> Result<()> {
-    let file = match config_file(Mode::Write)? {
-        ConfigFile::New(path) | ConfigFile::Old(path) => path,
-        ConfigFile::Missing => bail!("The configuration directoy could not be found"),
-    };
+/// An error in the content of a config file, pointing at the offending line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SyntaxError {
+    /// The 1-based line number, one past the last line if the file ended too early.
+    pub line: usize,
+    message: String,
+}
+
+impl SyntaxError {
+    fn new(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            message: message.into(),
+        }
+    }
+}
 
-    store_in(config, &file)
+impl std::fmt::Display for SyntaxError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
 }
 
+impl std::error::Error for SyntaxError {}
+
 fn store_in(config: &Config, path: &Path) -> Result<()> {
     let content = serialize_config(config);
 
@@ -227,17 +619,39 @@ fn serialize_config(config: &Config) -> String {
         content.push_str(" <");
         content.push_str(&nav.email);
         content.push_str(">\n");
+        for alias in &nav.aliases {
+            content.push_str("alias: ");
+            content.push_str(alias);
+            content.push('\n');
+        }
+        for email in &nav.emails {
+            content.push_str("email: ");
+            content.push_str(&email.to_string());
+            content.push('\n');
+        }
+        if nav.archived {
+            content.push_str("archived: true\n");
+        }
     }
 
     let mut content = String::with_capacity(8192);
     content.push_str("version: 1\n");
-    for nav in &config.navigators {
+    for include in &config.includes {
+        content.push_str("include: ");
+        content.push_str(include);
+        content.push('\n');
+    }
+    for nav in config
+        .navigators
+        .iter()
+        .filter(|n| !config.is_included_nav(n))
+    {
         content.push_str("navigator: ");
         content.push_str(&nav.alias);
         content.push('\n');
         write_nav(&mut content, nav);
     }
-    for drv in &config.drivers {
+    for drv in config.drivers.iter().filter(|d| !config.is_included_drv(d)) {
         content.push_str("driver: ");
         content.push_str(&drv.navigator.alias);
         content.push('\n');
@@ -247,6 +661,11 @@ fn serialize_config(config: &Config) -> String {
             content.push_str(key);
         }
         content.push('\n');
+        if drv.key_format != KeyFormat::default() {
+            content.push_str("format: ");
+            content.push_str(&drv.key_format.to_string());
+            content.push('\n');
+        }
         write_nav(&mut content, &drv.navigator);
     }
 
@@ -275,6 +694,13 @@ fn store_to(file: &Path, data: &[u8], create_parent: bool) -> Result<()> {
     Ok(())
 }
 
+/// The directory of the config file, which also holds the global drive session.
+pub fn config_dir() -> Result<PathBuf> {
+    ProjectDirs::from("de", "knutwalker", APPLICATION)
+        .map(|dirs| dirs.config_dir().to_path_buf())
+        .ok_or_else(|| eyre!("Could not find the config directory of the user"))
+}
+
 fn config_file(mode: Mode) -> Result<ConfigFile> {
     let dirs = match ProjectDirs::from("de", "knutwalker", APPLICATION) {
         Some(dirs) => dirs,
@@ -413,6 +839,26 @@ mod tests {
         );
     }
 
+    #[test]
+    fn serialize_one_driver_with_ssh_key() {
+        let config = Config::from_iter(Some(Driver {
+            key_format: KeyFormat::Ssh,
+            ..drv1("~/.ssh/id_ed25519.pub")
+        }));
+        let config = serialize_config(&config);
+
+        assert_eq!(
+            config,
+            concat!(
+                "version: 1\n",
+                "driver: drv1\n",
+                "key: ~/.ssh/id_ed25519.pub\n",
+                "format: ssh\n",
+                "Co-Authored-By: ralle <qux@bar.org>\n",
+            )
+        );
+    }
+
     #[test]
     fn serialize_all() {
         let config = Config::from_iter([nav1().ent(), nav2().ent(), drv1("my-key.pub").ent()]);
@@ -498,6 +944,123 @@ mod tests {
         assert_eq!(config, expected);
     }
 
+    #[test]
+    fn deserialize_one_driver_with_ssh_key() {
+        let config = concat!(
+            "version: 1\n",
+            "driver: drv1\n",
+            "key: ~/.ssh/id_ed25519.pub\n",
+            "format: ssh\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+        );
+        let config = deserialize_config(config).unwrap();
+
+        let expected = Config::from_iter(Some(Driver {
+            key_format: KeyFormat::Ssh,
+            ..drv1("~/.ssh/id_ed25519.pub")
+        }));
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn deserialize_unknown_key_format() {
+        let config = concat!(
+            "version: 1\n",
+            "driver: drv1\n",
+            "key: my-key.pub\n",
+            "format: pgp\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+        );
+        let err = deserialize_config(config).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Invalid key format in line 4: Unknown key format `pgp`, expected one of `openpgp`, `x509`, or `ssh`."
+        );
+    }
+
+    #[test]
+    fn deserialize_navigator_with_other_emails() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "email: oss=bernd@example.org\n",
+            "email: home=bernd@home.net\n",
+            "navigator: nav2\n",
+            "Co-Authored-By: ronny <baz@bar.org>\n",
+        );
+        let config = deserialize_config(content).unwrap();
+
+        let nav = &config.navigators[0];
+        assert_eq!(nav.email, "foo@bar.org");
+        assert_eq!(nav.email_for("oss"), Some("bernd@example.org"));
+        assert_eq!(nav.email_for("home"), Some("bernd@home.net"));
+        assert_eq!(config.navigators[1], nav2());
+
+        assert_eq!(serialize_config(&config), content);
+    }
+
+    #[test]
+    fn deserialize_navigator_with_secondary_aliases() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "alias: bernie\n",
+            "alias: b\n",
+            "email: oss=bernd@example.org\n",
+        );
+        let config = deserialize_config(content).unwrap();
+
+        let nav = &config.navigators[0];
+        assert_eq!(nav.aliases, [Id::from("bernie"), Id::from("b")]);
+        assert!(nav.has_alias("bernie"));
+        assert_eq!(nav.email_for("oss"), Some("bernd@example.org"));
+
+        assert_eq!(serialize_config(&config), content);
+    }
+
+    #[test]
+    fn deserialize_archived_navigator() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "archived: true\n",
+            "navigator: nav2\n",
+            "Co-Authored-By: ronny <baz@bar.org>\n",
+        );
+        let config = deserialize_config(content).unwrap();
+
+        assert!(config.navigators[0].archived);
+        assert!(!config.navigators[1].archived);
+        assert_eq!(serialize_config(&config), content);
+
+        let err = deserialize_config(&content.replace("true", "yes")).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected `true` or `false` in line 4, but got `yes`."
+        );
+    }
+
+    #[test]
+    fn deserialize_duplicate_email_label() {
+        let config = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "email: oss=bernd@example.org\n",
+            "email: oss=bernd@home.net\n",
+        );
+        let err = deserialize_config(config).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "The label `oss` in line 5 is used more than once."
+        );
+    }
+
     #[test]
     fn deserialize_all() {
         let config = concat!(
@@ -516,6 +1079,50 @@ mod tests {
         assert_eq!(config, expected);
     }
 
+    #[test]
+    fn serialize_includes() {
+        let config = Config {
+            includes: vec![
+                String::from("~/company.gitdrive"),
+                String::from("team.gitdrive"),
+            ],
+            ..Config::from_iter(Some(nav1()))
+        };
+        let config = serialize_config(&config);
+
+        assert_eq!(
+            config,
+            concat!(
+                "version: 1\n",
+                "include: ~/company.gitdrive\n",
+                "include: team.gitdrive\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn deserialize_includes() {
+        let config = concat!(
+            "version: 1\n",
+            "include: ~/company.gitdrive\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "include: team.gitdrive\n",
+        );
+        let config = deserialize_config(config).unwrap();
+
+        let expected = Config {
+            includes: vec![
+                String::from("~/company.gitdrive"),
+                String::from("team.gitdrive"),
+            ],
+            ..Config::from_iter(Some(nav1()))
+        };
+        assert_eq!(config, expected);
+    }
+
     #[test]
     fn deserialize_empty() {
         let config = "";
@@ -549,7 +1156,7 @@ mod tests {
         let config = deserialize_config(config).unwrap_err();
         assert_eq!(
             config.to_string(),
-            "Unexpted type `foo in line 2, expected either `navigator` or `driver`."
+            "Unexpted type `foo in line 2, expected either `navigator`, `driver`, or `include`."
         );
     }
 
@@ -593,6 +1200,29 @@ mod tests {
         );
     }
 
+    #[test]
+    fn deserialize_skips_comments_and_blank_lines() {
+        let config = concat!(
+            "# The roster\n",
+            "version: 1\n",
+            "\n",
+            "navigator: nav1\n",
+            "# bernd\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+        );
+        let config = deserialize_config(config).unwrap();
+
+        let expected = Config::from_iter(Some(nav1()));
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn deserialize_error_points_at_the_line() {
+        let config = "version: 1\n\nnavigator: foo\nbernd <foo@bar.org>";
+        let err = deserialize_config(config).unwrap_err();
+        assert_eq!(err.downcast_ref::<SyntaxError>().unwrap().line, 4);
+    }
+
     #[test]
     fn find_config_file_for_reading_in_empty_dir() {
         let dir = TempDir::new().unwrap();
@@ -689,6 +1319,237 @@ mod tests {
         dir.close().unwrap();
     }
 
+    #[test]
+    fn load_layered_includes() {
+        use assert_fs::prelude::*;
+        let dir = TempDir::new().unwrap();
+
+        dir.child("shared/company.gitdrive")
+            .write_str(concat!(
+                "version: 1\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: old bernd <old@bar.org>\n",
+                "navigator: nav2\n",
+                "Co-Authored-By: ronny <baz@bar.org>\n",
+            ))
+            .unwrap();
+        dir.child("shared/team.gitdrive")
+            .write_str(concat!(
+                "version: 1\n",
+                "include: company.gitdrive\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n",
+            ))
+            .unwrap();
+        let personal = dir.child("personal.gitdrive");
+        personal
+            .write_str(concat!(
+                "version: 1\n",
+                "include: shared/team.gitdrive\n",
+                "driver: drv1\n",
+                "key: my-key.pub\n",
+                "Co-Authored-By: ralle <qux@bar.org>\n",
+            ))
+            .unwrap();
+
+        let config = load_from(personal.path()).unwrap();
+
+        assert_eq!(config.navigators, vec![nav1(), nav2()]);
+        assert_eq!(config.drivers, vec![drv1("my-key.pub")]);
+
+        let team = fs::canonicalize(dir.child("shared/team.gitdrive").path()).unwrap();
+        let company = fs::canonicalize(dir.child("shared/company.gitdrive").path()).unwrap();
+        assert_eq!(
+            config.included_from(Kind::Navigator, &Id::from("nav1")),
+            Some(team.as_path())
+        );
+        assert_eq!(
+            config.included_from(Kind::Navigator, &Id::from("nav2")),
+            Some(company.as_path())
+        );
+        assert_eq!(config.included_from(Kind::Driver, &Id::from("drv1")), None);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn personal_entries_take_precedence_over_includes() {
+        use assert_fs::prelude::*;
+        let dir = TempDir::new().unwrap();
+
+        dir.child("team.gitdrive")
+            .write_str(concat!(
+                "version: 1\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: old bernd <old@bar.org>\n",
+            ))
+            .unwrap();
+        let personal = dir.child("personal.gitdrive");
+        personal
+            .write_str(concat!(
+                "version: 1\n",
+                "include: team.gitdrive\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n",
+            ))
+            .unwrap();
+
+        let config = load_from(personal.path()).unwrap();
+
+        assert_eq!(config.navigators, vec![nav1()]);
+        assert_eq!(
+            config.included_from(Kind::Navigator, &Id::from("nav1")),
+            None
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn include_cycles_are_detected() {
+        use assert_fs::prelude::*;
+        let dir = TempDir::new().unwrap();
+
+        dir.child("a.gitdrive")
+            .write_str("version: 1\ninclude: b.gitdrive\n")
+            .unwrap();
+        dir.child("b.gitdrive")
+            .write_str("version: 1\ninclude: a.gitdrive\n")
+            .unwrap();
+
+        let err = load_from(dir.child("a.gitdrive").path()).unwrap_err();
+        assert!(
+            err.to_string().starts_with("Include cycle detected: "),
+            "{}",
+            err
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn missing_include_is_an_error() {
+        use assert_fs::prelude::*;
+        let dir = TempDir::new().unwrap();
+        let personal = dir.child("personal.gitdrive");
+        personal
+            .write_str("version: 1\ninclude: missing.gitdrive\n")
+            .unwrap();
+
+        let err = load_from(personal.path()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            format!(
+                "Including `missing.gitdrive` from {}",
+                personal.path().display()
+            )
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn store_does_not_inline_included_entries() {
+        use assert_fs::prelude::*;
+        let dir = TempDir::new().unwrap();
+
+        dir.child("team.gitdrive")
+            .write_str(concat!(
+                "version: 1\n",
+                "navigator: nav2\n",
+                "Co-Authored-By: ronny <baz@bar.org>\n",
+            ))
+            .unwrap();
+        let personal = dir.child("personal.gitdrive");
+        personal
+            .write_str(concat!(
+                "version: 1\n",
+                "include: team.gitdrive\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n",
+            ))
+            .unwrap();
+
+        let mut config = load_from(personal.path()).unwrap();
+        assert_eq!(config.navigators, vec![nav1(), nav2()]);
+
+        config.drivers.push(drv1(None));
+        store_in(&config, personal.path()).unwrap();
+
+        personal.assert(concat!(
+            "version: 1\n",
+            "include: team.gitdrive\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "driver: drv1\n",
+            "key:\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+        ));
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn personal_entries_equal_to_included_ones_stay_personal() {
+        use assert_fs::prelude::*;
+        let dir = TempDir::new().unwrap();
+
+        dir.child("team.gitdrive")
+            .write_str(concat!(
+                "version: 1\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n",
+            ))
+            .unwrap();
+        let content = concat!(
+            "version: 1\n",
+            "include: team.gitdrive\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+        );
+        let personal = dir.child("personal.gitdrive");
+        personal.write_str(content).unwrap();
+
+        let config = load_from(personal.path()).unwrap();
+        assert_eq!(config.navigators, vec![nav1()]);
+        assert_eq!(
+            config.included_from(Kind::Navigator, &Id::from("nav1")),
+            None
+        );
+
+        store_in(&config, personal.path()).unwrap();
+        personal.assert(content);
+
+        dir.close().unwrap();
+    }
+
+    fn assert_backend_roundtrip(backend: &impl Backend) {
+        assert_eq!(backend.load().unwrap(), Config::default());
+
+        let config = Config::from_iter([nav1().ent(), nav2().ent(), drv1("my-key.pub").ent()]);
+        backend.store(&config).unwrap();
+        assert_eq!(backend.load().unwrap(), config);
+
+        let config = Config::from_iter([nav2().ent(), drv1(None).ent()]);
+        backend.store(&config).unwrap();
+        assert_eq!(backend.load().unwrap(), config);
+    }
+
+    #[test]
+    fn memory_backend_roundtrip() {
+        assert_backend_roundtrip(&MemoryBackend::default());
+    }
+
+    #[test]
+    fn git_backend_roundtrip() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("gitconfig");
+
+        assert_backend_roundtrip(&git::GitBackend::file(file.path()));
+
+        dir.close().unwrap();
+    }
+
     #[test]
     fn config_roundtrip() {
         use assert_fs::prelude::*;
@@ -0,0 +1,893 @@
+use super::{backend, config_file, include_path, ConfigFile, Mode, Storage};
+use crate::data::{CheckConfig, Email, KeyFormat};
+use console::style;
+use eyre::{bail, Result, WrapErr};
+use std::{convert::TryFrom, fs, iter::Peekable, path::Path};
+
+/// Checks the personal config file and reports all problems at once,
+/// instead of stopping at the first one like loading the config does.
+/// With `--fix`, the problems that have an unambiguous fix are fixed in place.
+pub fn check(check: &CheckConfig) -> Result<()> {
+    if let Storage::Git(_) = backend()? {
+        bail!("Only a roster in a file can be checked, but `drive.backend` is set to `git`.");
+    }
+    let path = match config_file(Mode::Read)? {
+        ConfigFile::New(path) => path,
+        ConfigFile::Old(path) => bail!(
+            "The config file {} has the old format, it is converted by any other command.",
+            path.display()
+        ),
+        ConfigFile::Missing => {
+            println!("There is no config file yet.");
+            return Ok(());
+        }
+    };
+
+    let mut content =
+        fs::read_to_string(&path).wrap_err_with(|| format!("File: {}", path.display()))?;
+    let mut problems = diagnose(&content, &path);
+
+    let fixable = problems.iter().filter(|p| p.fix.is_some()).count();
+    if check.fix && fixable > 0 {
+        content = apply_fixes(&content, &problems);
+        fs::write(&path, &content).wrap_err_with(|| format!("File: {}", path.display()))?;
+        println!("Fixed {} problem(s) in {}.", fixable, path.display());
+        problems = diagnose(&content, &path);
+    }
+
+    if problems.is_empty() {
+        println!("No problems found in {}.", path.display());
+        return Ok(());
+    }
+
+    let lines = content.lines().collect::<Vec<_>>();
+    for problem in &problems {
+        eprintln!("{}", problem.render(&path, &lines));
+    }
+
+    let fixable = problems.iter().filter(|p| p.fix.is_some()).count();
+    if fixable > 0 {
+        bail!(
+            "Found {} problem(s) in {}, {} of them can be fixed with {}.",
+            problems.len(),
+            path.display(),
+            fixable,
+            style("--fix").yellow()
+        );
+    }
+    bail!("Found {} problem(s) in {}.", problems.len(), path.display())
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Problem {
+    /// The 1-based line number, one past the last line if the file ended too early.
+    line: usize,
+    /// The 1-based column, counted in characters.
+    column: usize,
+    message: String,
+    fix: Option<Fix>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Fix {
+    /// Replace the whole line.
+    Replace(String),
+    /// Insert a new line before the line.
+    InsertBefore(String),
+}
+
+impl Problem {
+    fn new(line: usize, column: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            column,
+            message: message.into(),
+            fix: None,
+        }
+    }
+
+    fn with_fix(self, fix: Fix) -> Self {
+        Self {
+            fix: Some(fix),
+            ..self
+        }
+    }
+
+    /// Renders the problem with its location and the offending line, like the compiler does.
+    fn render(&self, path: &Path, lines: &[&str]) -> String {
+        let snippet = lines.get(self.line - 1).copied().unwrap_or_default();
+        let number = self.line.to_string();
+        let gutter = " ".repeat(number.len());
+        let hint = if self.fix.is_some() { " (fixable)" } else { "" };
+
+        format!(
+            "{}: {}{}\n{}--> {}:{}:{}\n{} |\n{} | {}\n{} | {}{}\n",
+            style("error").red().bold(),
+            self.message,
+            style(hint).dim(),
+            gutter,
+            path.display(),
+            self.line,
+            self.column,
+            gutter,
+            number,
+            snippet,
+            gutter,
+            " ".repeat(self.column - 1),
+            style("^").red().bold()
+        )
+    }
+}
+
+/// Collects all problems of the config file.
+/// `from` is the path of the file, against which includes are resolved.
+fn diagnose(content: &str, from: &Path) -> Vec<Problem> {
+    let end = content.lines().count() + 1;
+    let mut problems = Vec::new();
+    let mut lines = content
+        .lines()
+        .enumerate()
+        .map(|(ln, l)| (ln + 1, l))
+        .filter(|(_, l)| !l.trim().is_empty() && !l.starts_with('#'))
+        .peekable();
+
+    match lines.peek().copied() {
+        None => {
+            problems.push(
+                Problem::new(
+                    1,
+                    1,
+                    "The config file is empty, expected at least a version key.",
+                )
+                .with_fix(Fix::InsertBefore(String::from("version: 1"))),
+            );
+            return problems;
+        }
+        Some((ln, line)) => match line.split_once(':') {
+            Some((key, version)) if key.trim().eq_ignore_ascii_case("version") => {
+                lines.next();
+                let version = version.trim();
+                if version != "1" {
+                    problems.push(Problem::new(
+                        ln,
+                        column_of(line, version),
+                        format!("Unknown version `{}`, expected `1`.", version),
+                    ));
+                } else if line != "version: 1" {
+                    problems.push(
+                        Problem::new(ln, 1, "Expected `version: 1`.")
+                            .with_fix(Fix::Replace(String::from("version: 1"))),
+                    );
+                }
+            }
+            _ => problems.push(
+                Problem::new(ln, 1, "Expected `version: 1` before the first entry.")
+                    .with_fix(Fix::InsertBefore(String::from("version: 1"))),
+            ),
+        },
+    }
+
+    let mut navigators = Vec::new();
+    let mut drivers = Vec::new();
+    while let Some((ln, line)) = lines.next() {
+        let (kind, alias) = match line.split_once(':') {
+            Some((kind, alias)) => (kind.trim(), alias.trim()),
+            None => {
+                problems.push(Problem::new(
+                    ln,
+                    1,
+                    format!("Expected `$type: $alias`, but got `{}`.", line),
+                ));
+                continue;
+            }
+        };
+        let kind_name = kind.to_ascii_lowercase();
+        if !is_entry_kind(&kind_name) {
+            problems.push(Problem::new(
+                ln,
+                1,
+                format!(
+                    "Unknown type `{}`, expected either `navigator`, `driver`, or `include`.",
+                    kind
+                ),
+            ));
+            while lines.peek().map_or(false, |(_, l)| is_body_line(l)) {
+                lines.next();
+            }
+            continue;
+        }
+
+        let canonical = format!("{}: {}", kind_name, alias);
+        if alias.is_empty() {
+            problems.push(Problem::new(
+                ln,
+                line.chars().count() + 1,
+                format!("The {} is missing its alias.", kind_name),
+            ));
+        } else if line != canonical {
+            problems.push(
+                Problem::new(ln, 1, format!("Expected `{}`.", canonical))
+                    .with_fix(Fix::Replace(canonical)),
+            );
+        }
+
+        match kind_name.as_str() {
+            "include" => {
+                if !alias.is_empty() && include_path(from, alias).is_err() {
+                    problems.push(Problem::new(
+                        ln,
+                        column_of(line, alias),
+                        format!("The included file `{}` could not be found.", alias),
+                    ));
+                }
+            }
+            "navigator" => {
+                check_duplicate(&mut navigators, &mut problems, ln, line, alias);
+                check_co_author(&mut lines, &mut problems, ln, end);
+                check_details(&mut lines, &mut problems, &mut navigators);
+            }
+            _ => {
+                check_duplicate(&mut drivers, &mut problems, ln, line, alias);
+                check_key(&mut lines, &mut problems, ln, end);
+                check_key_format(&mut lines, &mut problems);
+                check_co_author(&mut lines, &mut problems, ln, end);
+                check_details(&mut lines, &mut problems, &mut drivers);
+            }
+        }
+    }
+
+    problems
+}
+
+fn check_duplicate<'a>(
+    seen: &mut Vec<&'a str>,
+    problems: &mut Vec<Problem>,
+    ln: usize,
+    line: &str,
+    alias: &'a str,
+) {
+    if alias.is_empty() {
+        return;
+    }
+    if seen.contains(&alias) {
+        problems.push(Problem::new(
+            ln,
+            column_of(line, alias),
+            format!("The alias `{}` is already used above.", alias),
+        ));
+    } else {
+        seen.push(alias);
+    }
+}
+
+/// Checks the `key:` line of the driver that starts in line `ln`.
+fn check_key<'a>(
+    lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+    problems: &mut Vec<Problem>,
+    ln: usize,
+    end: usize,
+) {
+    match lines.peek().copied() {
+        Some((key_ln, line)) if is_key_line(line) => {
+            lines.next();
+            let key = line.split_once(':').map_or("", |(_, key)| key.trim());
+            let canonical = if key.is_empty() {
+                String::from("key:")
+            } else {
+                format!("key: {}", key)
+            };
+            if line != canonical {
+                problems.push(
+                    Problem::new(key_ln, 1, format!("Expected `{}`.", canonical))
+                        .with_fix(Fix::Replace(canonical)),
+                );
+            }
+        }
+        Some((co_author_ln, line)) if is_co_author_line(line) => problems.push(
+            Problem::new(
+                co_author_ln,
+                1,
+                "Expected `key: $key` before the Co-Authored-By.",
+            )
+            .with_fix(Fix::InsertBefore(String::from("key:"))),
+        ),
+        Some((_, line)) if is_entry_line(line) => problems.push(Problem::new(
+            ln,
+            1,
+            "The driver is missing its `key: $key` line.",
+        )),
+        Some((other_ln, line)) => {
+            lines.next();
+            problems.push(Problem::new(
+                other_ln,
+                1,
+                format!("Expected `key: $key`, but got `{}`.", line),
+            ));
+        }
+        None => problems.push(Problem::new(
+            end,
+            1,
+            "Expected `key: $key`, but reached the end of the file.",
+        )),
+    }
+}
+
+/// Checks the optional `format:` line after the key of a driver.
+fn check_key_format<'a>(
+    lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+    problems: &mut Vec<Problem>,
+) {
+    let (ln, line) = match lines.peek().copied() {
+        Some((ln, line)) if is_format_line(line) => (ln, line),
+        _ => return,
+    };
+    lines.next();
+
+    let format = line.split_once(':').map_or("", |(_, format)| format.trim());
+    match format.to_ascii_lowercase().parse::<KeyFormat>() {
+        Ok(key_format) => {
+            let canonical = format!("format: {}", key_format);
+            if line != canonical {
+                problems.push(
+                    Problem::new(ln, 1, format!("Expected `{}`.", canonical))
+                        .with_fix(Fix::Replace(canonical)),
+                );
+            }
+        }
+        Err(e) => problems.push(Problem::new(ln, column_of(line, format), e)),
+    }
+}
+
+/// Checks the `Co-Authored-By` line of the entry that starts in line `ln`.
+fn check_co_author<'a>(
+    lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+    problems: &mut Vec<Problem>,
+    ln: usize,
+    end: usize,
+) {
+    let (ln, line) = match lines.peek().copied() {
+        Some((_, line)) if is_entry_line(line) => {
+            problems.push(Problem::new(
+                ln,
+                1,
+                "The entry is missing its `Co-Authored-By: $name <$email>` line.",
+            ));
+            return;
+        }
+        Some(next) => {
+            lines.next();
+            next
+        }
+        None => {
+            problems.push(Problem::new(
+                end,
+                1,
+                "Expected `Co-Authored-By: $name <$email>`, but reached the end of the file.",
+            ));
+            return;
+        }
+    };
+
+    if !is_co_author_line(line) {
+        problems.push(Problem::new(
+            ln,
+            1,
+            format!(
+                "Expected `Co-Authored-By: $name <$email>`, but got `{}`.",
+                line
+            ),
+        ));
+        return;
+    }
+
+    // the key is ASCII, so its length is the same in the lowercased line
+    let start = line.len() - line.trim_start().len() + "co-authored-by".len();
+    let rest = line[start..].trim_start_matches(|c: char| c == ':' || c.is_whitespace());
+    let trailer = format!("Co-Authored-By: {}", rest);
+    let co_author = match co_authors::CoAuthor::try_from(trailer.as_str()) {
+        Ok(co_author) => co_author,
+        Err(e) => {
+            problems.push(Problem::new(
+                ln,
+                1,
+                format!("Expected `Co-Authored-By: $name <$email>`, but got: {}", e),
+            ));
+            return;
+        }
+    };
+
+    let email = match co_author.mail.map(str::trim).filter(|m| !m.is_empty()) {
+        Some(email) => email,
+        None => {
+            problems.push(Problem::new(
+                ln,
+                line.chars().count() + 1,
+                format!("The email of {} is missing.", co_author.name),
+            ));
+            return;
+        }
+    };
+    if !is_valid_email(email) {
+        problems.push(Problem::new(
+            ln,
+            column_of(line, email),
+            format!("The email `{}` is invalid.", email),
+        ));
+        return;
+    }
+
+    let canonical = format!("Co-Authored-By: {} <{}>", co_author.name, email);
+    if line != canonical {
+        problems.push(
+            Problem::new(ln, 1, format!("Expected `{}`.", canonical))
+                .with_fix(Fix::Replace(canonical)),
+        );
+    }
+}
+
+/// Checks the optional `alias:`, `email: $label=$email`, and `archived:` lines
+/// after the Co-Authored-By of an entry.
+/// The secondary aliases must not be used by any other entry of the same type.
+fn check_details<'a>(
+    lines: &mut Peekable<impl Iterator<Item = (usize, &'a str)>>,
+    problems: &mut Vec<Problem>,
+    seen: &mut Vec<&'a str>,
+) {
+    let mut labels = Vec::new();
+    while let Some((ln, line)) = lines.peek().copied() {
+        if is_alias_line(line) {
+            lines.next();
+            check_secondary_alias(seen, problems, ln, line);
+            continue;
+        }
+        if is_archived_line(line) {
+            lines.next();
+            check_archived(problems, ln, line);
+            continue;
+        }
+        if !is_email_line(line) {
+            return;
+        }
+        lines.next();
+
+        let value = line.split_once(':').map_or("", |(_, value)| value.trim());
+        let email = match value.parse::<Email>() {
+            Ok(email) => email,
+            Err(e) => {
+                problems.push(Problem::new(ln, column_of(line, value), e));
+                continue;
+            }
+        };
+        if !is_valid_email(&email.address) {
+            problems.push(Problem::new(
+                ln,
+                column_of(line, &email.address),
+                format!("The email `{}` is invalid.", email.address),
+            ));
+            continue;
+        }
+        if labels.contains(&email.label) {
+            problems.push(Problem::new(
+                ln,
+                column_of(line, &email.label),
+                format!("The label `{}` is already used above.", email.label),
+            ));
+            continue;
+        }
+
+        let canonical = format!("email: {}", email);
+        if line != canonical {
+            problems.push(
+                Problem::new(ln, 1, format!("Expected `{}`.", canonical))
+                    .with_fix(Fix::Replace(canonical)),
+            );
+        }
+        labels.push(email.label);
+    }
+}
+
+fn check_secondary_alias<'a>(
+    seen: &mut Vec<&'a str>,
+    problems: &mut Vec<Problem>,
+    ln: usize,
+    line: &'a str,
+) {
+    let alias = line.split_once(':').map_or("", |(_, alias)| alias.trim());
+    if alias.is_empty() {
+        problems.push(Problem::new(
+            ln,
+            line.chars().count() + 1,
+            "The secondary alias is empty.",
+        ));
+        return;
+    }
+
+    let canonical = format!("alias: {}", alias);
+    if line != canonical {
+        problems.push(
+            Problem::new(ln, 1, format!("Expected `{}`.", canonical))
+                .with_fix(Fix::Replace(canonical)),
+        );
+    }
+    check_duplicate(seen, problems, ln, line, alias);
+}
+
+fn check_archived(problems: &mut Vec<Problem>, ln: usize, line: &str) {
+    let value = line.split_once(':').map_or("", |(_, value)| value.trim());
+    let archived = if value.eq_ignore_ascii_case("true") {
+        true
+    } else if value.eq_ignore_ascii_case("false") {
+        false
+    } else {
+        problems.push(Problem::new(
+            ln,
+            if value.is_empty() {
+                line.chars().count() + 1
+            } else {
+                column_of(line, value)
+            },
+            format!("Expected `true` or `false`, but got `{}`.", value),
+        ));
+        return;
+    };
+
+    let canonical = format!("archived: {}", archived);
+    if line != canonical {
+        problems.push(
+            Problem::new(ln, 1, format!("Expected `{}`.", canonical))
+                .with_fix(Fix::Replace(canonical)),
+        );
+    }
+}
+
+/// Applies the fixes of the problems, line by line.
+fn apply_fixes(content: &str, problems: &[Problem]) -> String {
+    let mut fixed = String::with_capacity(content.len());
+    let mut lines = content.lines().map(Some).collect::<Vec<_>>();
+    // a fix may refer to the line past the end of the file
+    lines.push(None);
+
+    for (ln, line) in (1..).zip(lines) {
+        let mut replacement = None;
+        for problem in problems.iter().filter(|p| p.line == ln) {
+            match &problem.fix {
+                Some(Fix::InsertBefore(text)) => {
+                    fixed.push_str(text);
+                    fixed.push('\n');
+                }
+                Some(Fix::Replace(text)) => replacement = Some(text.as_str()),
+                None => {}
+            }
+        }
+        if let Some(line) = replacement.or(line) {
+            fixed.push_str(line);
+            fixed.push('\n');
+        }
+    }
+
+    fixed
+}
+
+fn is_entry_kind(kind: &str) -> bool {
+    matches!(kind, "include" | "navigator" | "driver")
+}
+
+fn is_entry_line(line: &str) -> bool {
+    line.split_once(':').map_or(false, |(kind, _)| {
+        is_entry_kind(&kind.trim().to_ascii_lowercase())
+    })
+}
+
+fn is_key_line(line: &str) -> bool {
+    line.split_once(':')
+        .map_or(false, |(key, _)| key.trim().eq_ignore_ascii_case("key"))
+}
+
+fn is_format_line(line: &str) -> bool {
+    line.split_once(':')
+        .map_or(false, |(key, _)| key.trim().eq_ignore_ascii_case("format"))
+}
+
+fn is_alias_line(line: &str) -> bool {
+    line.split_once(':')
+        .map_or(false, |(key, _)| key.trim().eq_ignore_ascii_case("alias"))
+}
+
+fn is_archived_line(line: &str) -> bool {
+    line.split_once(':').map_or(false, |(key, _)| {
+        key.trim().eq_ignore_ascii_case("archived")
+    })
+}
+
+fn is_email_line(line: &str) -> bool {
+    line.split_once(':')
+        .map_or(false, |(key, _)| key.trim().eq_ignore_ascii_case("email"))
+}
+
+fn is_co_author_line(line: &str) -> bool {
+    line.trim_start()
+        .to_ascii_lowercase()
+        .starts_with("co-authored-by")
+}
+
+fn is_body_line(line: &str) -> bool {
+    is_key_line(line)
+        || is_format_line(line)
+        || is_co_author_line(line)
+        || is_alias_line(line)
+        || is_email_line(line)
+        || is_archived_line(line)
+}
+
+fn is_valid_email(email: &str) -> bool {
+    match email.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && !domain.is_empty() && !email.contains(char::is_whitespace)
+        }
+        None => false,
+    }
+}
+
+/// The 1-based column of the last occurrence of `needle` in the line.
+fn column_of(line: &str, needle: &str) -> usize {
+    line.rfind(needle)
+        .map_or(1, |offset| line[..offset].chars().count() + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn diagnose(content: &str) -> Vec<(usize, usize, String, bool)> {
+        super::diagnose(content, Path::new("/config"))
+            .into_iter()
+            .map(|p| (p.line, p.column, p.message, p.fix.is_some()))
+            .collect()
+    }
+
+    #[test]
+    fn no_problems_in_a_valid_file() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "\n",
+            "driver: drv1\n",
+            "key:\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+        );
+        assert_eq!(diagnose(content), Vec::new());
+    }
+
+    #[test]
+    fn collect_all_problems() {
+        let content = concat!(
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo.bar.org>\n",
+            "foo: bar\n",
+            "Co-Authored-By: baz <baz@bar.org>\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: ronny\n",
+            "driver: drv1\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+        );
+        assert_eq!(
+            diagnose(content),
+            vec![
+                (
+                    1,
+                    1,
+                    String::from("Expected `version: 1` before the first entry."),
+                    true
+                ),
+                (
+                    2,
+                    24,
+                    String::from("The email `foo.bar.org` is invalid."),
+                    false
+                ),
+                (
+                    3,
+                    1,
+                    String::from(
+                        "Unknown type `foo`, expected either `navigator`, `driver`, or `include`."
+                    ),
+                    false
+                ),
+                (
+                    5,
+                    12,
+                    String::from("The alias `nav1` is already used above."),
+                    false
+                ),
+                (6, 22, String::from("The email of ronny is missing."), false),
+                (
+                    8,
+                    1,
+                    String::from("Expected `key: $key` before the Co-Authored-By."),
+                    true
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_the_key_format() {
+        let content = concat!(
+            "version: 1\n",
+            "driver: drv1\n",
+            "key: ~/.ssh/id_ed25519.pub\n",
+            "Format: SSH\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+            "driver: drv2\n",
+            "key: my-key.pub\n",
+            "format: pgp\n",
+            "Co-Authored-By: ronny <baz@bar.org>\n",
+        );
+        assert_eq!(
+            diagnose(content),
+            vec![
+                (4, 1, String::from("Expected `format: ssh`."), true),
+                (
+                    8,
+                    9,
+                    String::from(
+                        "Unknown key format `pgp`, expected one of `openpgp`, `x509`, or `ssh`."
+                    ),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_other_emails() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "Email: oss = bernd@example.org\n",
+            "email: oss=bernd@home.net\n",
+            "email: home\n",
+            "email: work=bernd\n",
+        );
+        assert_eq!(
+            diagnose(content),
+            vec![
+                (
+                    4,
+                    1,
+                    String::from("Expected `email: oss=bernd@example.org`."),
+                    true
+                ),
+                (
+                    5,
+                    8,
+                    String::from("The label `oss` is already used above."),
+                    false
+                ),
+                (
+                    6,
+                    8,
+                    String::from("Expected `$label=$email`, but got `home`."),
+                    false
+                ),
+                (7, 13, String::from("The email `bernd` is invalid."), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_secondary_aliases() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "Alias:  bernie\n",
+            "navigator: nav2\n",
+            "Co-Authored-By: ronny <baz@bar.org>\n",
+            "alias: bernie\n",
+            "alias:\n",
+            "navigator: ron\n",
+            "Co-Authored-By: ron <ron@bar.org>\n",
+            "driver: bernie\n",
+            "key:\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+        );
+        assert_eq!(
+            diagnose(content),
+            vec![
+                (4, 1, String::from("Expected `alias: bernie`."), true),
+                (
+                    7,
+                    8,
+                    String::from("The alias `bernie` is already used above."),
+                    false
+                ),
+                (8, 7, String::from("The secondary alias is empty."), false),
+            ]
+        );
+    }
+
+    #[test]
+    fn check_archived_navigators() {
+        let content = concat!(
+            "version: 1\n",
+            "navigator: nav1\n",
+            "Co-Authored-By: bernd <foo@bar.org>\n",
+            "Archived: TRUE\n",
+            "navigator: nav2\n",
+            "Co-Authored-By: ronny <baz@bar.org>\n",
+            "archived: yes\n",
+        );
+        assert_eq!(
+            diagnose(content),
+            vec![
+                (4, 1, String::from("Expected `archived: true`."), true),
+                (
+                    7,
+                    11,
+                    String::from("Expected `true` or `false`, but got `yes`."),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn missing_lines_at_the_end() {
+        let content = "version: 1\ndriver: drv1\n";
+        assert_eq!(
+            diagnose(content),
+            vec![
+                (
+                    3,
+                    1,
+                    String::from("Expected `key: $key`, but reached the end of the file."),
+                    false
+                ),
+                (
+                    3,
+                    1,
+                    String::from(
+                        "Expected `Co-Authored-By: $name <$email>`, but reached the end of the file."
+                    ),
+                    false
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn fix_the_fixable_problems() {
+        let content = concat!(
+            "Version:1\n",
+            "Navigator:  nav1\n",
+            "co-authored-by bernd <foo@bar.org>\n",
+            "driver: drv1\n",
+            "Co-Authored-By: ralle <qux@bar.org>\n",
+            "navigator: nav2\n",
+            "Co-Authored-By: ronny <baz@bar>\n",
+        );
+        let problems = super::diagnose(content, Path::new("/config"));
+        assert_eq!(
+            apply_fixes(content, &problems),
+            concat!(
+                "version: 1\n",
+                "navigator: nav1\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n",
+                "driver: drv1\n",
+                "key:\n",
+                "Co-Authored-By: ralle <qux@bar.org>\n",
+                "navigator: nav2\n",
+                "Co-Authored-By: ronny <baz@bar>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn fix_an_empty_file() {
+        let problems = super::diagnose("", Path::new("/config"));
+        assert_eq!(apply_fixes("", &problems), "version: 1\n");
+    }
+}
@@ -0,0 +1,190 @@
+use super::{deserialize_config, serialize_config, Config, SyntaxError};
+use crate::data::Modification;
+use eyre::{bail, Result, WrapErr};
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    process::Command,
+};
+
+const HEADER: &str = concat!(
+    "# Edit the roster of ",
+    env!("CARGO_PKG_NAME"),
+    ", then save the file and close the editor.\n",
+    "# Lines starting with `#` are ignored. Remove everything to abort.\n",
+);
+
+const ERROR_PREFIX: &str = "# ERROR: ";
+
+/// Opens the personal roster in `$VISUAL` or `$EDITOR` and takes over the edited content.
+/// If the content cannot be read, the editor is opened again,
+/// with the error as a comment above the offending line.
+pub fn edit(config: &mut Config) -> Result<Modification> {
+    let original = serialize_config(config);
+    let file = edit_file();
+
+    let mut content = format!("{}{}", HEADER, original);
+    let edited = loop {
+        fs::write(&file, &content).wrap_err_with(|| format!("File: {}", file.display()))?;
+        run_editor(&file)?;
+        content =
+            fs::read_to_string(&file).wrap_err_with(|| format!("File: {}", file.display()))?;
+
+        if content
+            .lines()
+            .all(|line| line.trim().is_empty() || line.starts_with('#'))
+        {
+            fs::remove_file(&file)?;
+            eprintln!("The roster is empty, aborting without changes.");
+            return Ok(Modification::Unchanged);
+        }
+
+        match deserialize_config(&content) {
+            Ok(edited) => break edited,
+            Err(e) => content = annotate(&content, &e),
+        }
+    };
+    fs::remove_file(&file)?;
+
+    if serialize_config(&edited) == original {
+        return Ok(Modification::Unchanged);
+    }
+
+    *config = edited;
+    Ok(Modification::Changed)
+}
+
+fn edit_file() -> PathBuf {
+    std::env::temp_dir().join(format!(
+        "{}-roster-{}.gitdrive",
+        env!("CARGO_PKG_NAME"),
+        std::process::id()
+    ))
+}
+
+/// Puts the error as a comment above the line it points at,
+/// replacing the errors from earlier attempts.
+fn annotate(content: &str, error: &eyre::Report) -> String {
+    let line = error.downcast_ref::<SyntaxError>().map_or(1, |e| e.line);
+    let comment = error
+        .to_string()
+        .lines()
+        .map(|l| format!("{}{}\n", ERROR_PREFIX, l))
+        .collect::<String>();
+
+    let mut annotated = String::with_capacity(content.len() + comment.len());
+    let mut inserted = false;
+    for (line_number, l) in (1..).zip(content.lines()) {
+        if line_number == line {
+            annotated.push_str(&comment);
+            inserted = true;
+        }
+        if !l.starts_with(ERROR_PREFIX) {
+            annotated.push_str(l);
+            annotated.push('\n');
+        }
+    }
+    if !inserted {
+        annotated.push_str(&comment);
+    }
+
+    annotated
+}
+
+/// The editor that git would use, apart from `core.editor`.
+fn editor() -> String {
+    ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|editor| !editor.trim().is_empty())
+        .unwrap_or_else(|| String::from("vi"))
+}
+
+fn run_editor(file: &Path) -> Result<()> {
+    let editor = editor();
+    let status = editor_command(&editor, file)
+        .status()
+        .wrap_err_with(|| format!("Could not start the editor `{}`.", editor))?;
+
+    if !status.success() {
+        bail!(
+            "The editor `{}` failed with {}. The edited roster is kept in {}",
+            editor,
+            status,
+            file.display()
+        );
+    }
+
+    Ok(())
+}
+
+/// Runs the editor through the shell, so that it can contain arguments, e.g. `code --wait`.
+#[cfg(not(windows))]
+fn editor_command(editor: &str, file: &Path) -> Command {
+    let mut command = Command::new("sh");
+    command
+        .arg("-c")
+        .arg(format!("{} \"$@\"", editor))
+        .arg(editor)
+        .arg(file);
+    command
+}
+
+#[cfg(windows)]
+fn editor_command(editor: &str, file: &Path) -> Command {
+    let mut command = Command::new("cmd");
+    command
+        .arg("/C")
+        .arg(format!("{} \"{}\"", editor, file.display()));
+    command
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn annotate_the_offending_line() {
+        let content = "version: 1\nnavigator: foo\nbernd <foo@bar.org>\n";
+        let error = deserialize_config(content).unwrap_err();
+
+        let annotated = annotate(content, &error);
+        assert_eq!(
+            annotated,
+            concat!(
+                "version: 1\n",
+                "navigator: foo\n",
+                "# ERROR: Expected `Co-Authored-By: $name <$email>` in line 3, ",
+                "but got: The trailer is missing the `Co-Authored-By:` key.\n",
+                "bernd <foo@bar.org>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn annotate_replaces_earlier_errors() {
+        let content = "# ERROR: Unknown version: 2.\nversion: 1\nnavigator: foo\n";
+        let error = deserialize_config(content).unwrap_err();
+
+        let annotated = annotate(content, &error);
+        assert_eq!(
+            annotated,
+            concat!(
+                "version: 1\n",
+                "navigator: foo\n",
+                "# ERROR: Expected `Co-Authored-By: $name <$email>` in line 4, ",
+                "but reached the end of the file.\n",
+            )
+        );
+    }
+
+    #[test]
+    fn annotated_content_can_be_read_again() {
+        let content = "version: 1\nnavigator: nav1\nCo-Authored-By: bernd <foo@bar.org>\n";
+        let error = eyre::eyre!("Something went wrong");
+
+        let annotated = annotate(content, &error);
+        assert!(annotated.starts_with("# ERROR: Something went wrong\nversion: 1\n"));
+        assert!(deserialize_config(&annotated).is_ok());
+    }
+}
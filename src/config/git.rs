@@ -0,0 +1,415 @@
+use super::{Backend, Config};
+use crate::{
+    data::{Driver, Id, IdRef, KeyFormat, Navigator},
+    repo::{self, ConfigFile},
+};
+use eyre::{bail, ensure, eyre, Result, WrapErr};
+
+/// Stores the roster in a git config file, by default the global one.
+///
+/// The roster is read from the same file that it is written to,
+/// i.e. `~/.gitconfig`, unless only the XDG config file exists.
+/// Entries in other files or included ones are not part of the roster.
+///
+/// Navigators are stored as `drive.navigator.<alias>.name` and `drive.navigator.<alias>.email`,
+/// drivers as `drive.driver.<alias>.name`, `drive.driver.<alias>.email`,
+/// `drive.driver.<alias>.signingkey`, and `drive.driver.<alias>.format`.
+/// Secondary aliases are stored as multiple `drive.<type>.<alias>.aliases`,
+/// other emails as multiple `drive.<type>.<alias>.emails` of `label=email`.
+/// Archived navigators have `drive.navigator.<alias>.archived` set to `true`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct GitBackend {
+    location: Location,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Location {
+    Global,
+    #[cfg(test)]
+    File(std::path::PathBuf),
+}
+
+impl GitBackend {
+    pub const fn global() -> Self {
+        Self {
+            location: Location::Global,
+        }
+    }
+
+    #[cfg(test)]
+    pub fn file(path: impl Into<std::path::PathBuf>) -> Self {
+        Self {
+            location: Location::File(path.into()),
+        }
+    }
+
+    /// The roster entries as `git config --null --get-regexp` prints them.
+    fn read_entries(&self) -> Result<String> {
+        let file = self.config_file()?;
+        let mut output = String::new();
+        for (key, value) in file.section_entries("drive") {
+            if key.starts_with("drive.navigator.") || key.starts_with("drive.driver.") {
+                output.push_str(&key);
+                output.push('\n');
+                output.push_str(value);
+                output.push('\0');
+            }
+        }
+        Ok(output)
+    }
+
+    /// Opens the config file that `git config` would write to.
+    fn config_file(&self) -> Result<ConfigFile> {
+        let path = match &self.location {
+            Location::Global => repo::global_config_file()?,
+            #[cfg(test)]
+            Location::File(path) => path.clone(),
+        };
+        ConfigFile::open(path)
+    }
+}
+
+impl Backend for GitBackend {
+    fn load(&self) -> Result<Config> {
+        let entries = self.read_entries()?;
+        parse_entries(&entries).wrap_err("The roster in the git config could not be read.")
+    }
+
+    fn store(&self, config: &Config) -> Result<()> {
+        ensure!(
+            config.includes.is_empty(),
+            "Includes are only supported when storing the roster in a file."
+        );
+
+        // the whole roster is replaced in one write, so that it is never left half-written
+        let mut file = self.config_file()?;
+        let sections = file
+            .subsections("drive")
+            .into_iter()
+            .filter(|s| s.starts_with("navigator.") || s.starts_with("driver."))
+            .map(|s| format!("drive.{}", s))
+            .collect::<Vec<_>>();
+        for section in &sections {
+            file.remove_section(section);
+        }
+
+        for nav in &config.navigators {
+            let alias = nav.alias.as_ref();
+            file.set(&format!("drive.navigator.{alias}.name"), &nav.name)?;
+            file.set(&format!("drive.navigator.{alias}.email"), &nav.email)?;
+            for other in &nav.aliases {
+                file.add(&format!("drive.navigator.{alias}.aliases"), other)?;
+            }
+            for email in &nav.emails {
+                file.add(
+                    &format!("drive.navigator.{alias}.emails"),
+                    &email.to_string(),
+                )?;
+            }
+            if nav.archived {
+                file.set(&format!("drive.navigator.{alias}.archived"), "true")?;
+            }
+        }
+        for drv in &config.drivers {
+            let nav = &drv.navigator;
+            let alias = nav.alias.as_ref();
+            file.set(&format!("drive.driver.{alias}.name"), &nav.name)?;
+            file.set(&format!("drive.driver.{alias}.email"), &nav.email)?;
+            for other in &nav.aliases {
+                file.add(&format!("drive.driver.{alias}.aliases"), other)?;
+            }
+            for email in &nav.emails {
+                file.add(&format!("drive.driver.{alias}.emails"), &email.to_string())?;
+            }
+            if let Some(key) = drv.key.as_deref() {
+                file.set(&format!("drive.driver.{alias}.signingkey"), key)?;
+            }
+            if drv.key_format != KeyFormat::default() {
+                let format = drv.key_format.to_string();
+                file.set(&format!("drive.driver.{alias}.format"), &format)?;
+            }
+        }
+
+        file.save()
+    }
+}
+
+/// Reads `drive.backend` from the system and global git config.
+pub fn configured_backend() -> Result<Option<String>> {
+    repo::config_value(None, "drive.backend")
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Entry<'a> {
+    kind: &'a str,
+    alias: &'a str,
+    key: &'a str,
+    value: &'a str,
+}
+
+/// Splits the output of `git config --null --get-regexp`,
+/// where every entry is `$key\n$value\0`.
+fn split_entries(output: &str) -> impl Iterator<Item = Result<Entry<'_>>> {
+    output.split('\0').filter(|e| !e.is_empty()).map(|entry| {
+        let (key, value) = entry.split_once('\n').unwrap_or((entry, ""));
+        let (kind, alias, name) = key
+            .strip_prefix("drive.")
+            .and_then(|key| key.rsplit_once('.'))
+            .and_then(|(section, name)| {
+                let (kind, alias) = section.split_once('.')?;
+                Some((kind, alias, name))
+            })
+            .ok_or_else(|| eyre!("Expected `drive.$type.$alias.$key`, but got `{}`.", key))?;
+
+        Ok(Entry {
+            kind,
+            alias,
+            key: name,
+            value,
+        })
+    })
+}
+
+fn parse_entries(output: &str) -> Result<Config> {
+    fn entry_for<'a, T: IdRef>(
+        entries: &'a mut Vec<T>,
+        alias: &str,
+        new: impl FnOnce(Navigator) -> T,
+    ) -> &'a mut T {
+        let pos = entries
+            .iter()
+            .position(|e| &**e.id() == alias)
+            .unwrap_or_else(|| {
+                entries.push(new(Navigator {
+                    alias: Id::from(alias),
+                    aliases: Vec::new(),
+                    name: String::new(),
+                    email: String::new(),
+                    emails: Vec::new(),
+                    archived: false,
+                }));
+                entries.len() - 1
+            });
+        &mut entries[pos]
+    }
+
+    fn set_nav(nav: &mut Navigator, entry: Entry<'_>) -> Result<()> {
+        match entry.key {
+            "name" => nav.name = String::from(entry.value),
+            "email" => nav.email = String::from(entry.value),
+            "aliases" => nav.aliases.push(Id::from(entry.value.trim())),
+            "emails" => nav
+                .emails
+                .push(entry.value.parse().map_err(|e| eyre!("{}", e))?),
+            "archived" => {
+                nav.archived = match entry.value.trim().to_ascii_lowercase().as_str() {
+                    "true" | "yes" | "on" | "1" => true,
+                    "false" | "no" | "off" | "0" | "" => false,
+                    otherwise => bail!(
+                        "Expected a boolean for `archived` of the {} `{}`, but got `{}`.",
+                        entry.kind,
+                        entry.alias,
+                        otherwise
+                    ),
+                }
+            }
+            otherwise => bail!(
+                "Unexpected key `{}` for the {} `{}`.",
+                otherwise,
+                entry.kind,
+                entry.alias
+            ),
+        }
+        Ok(())
+    }
+
+    let mut navigators = Vec::new();
+    let mut drivers = Vec::new();
+
+    for entry in split_entries(output) {
+        let entry = entry?;
+        match entry.kind {
+            "navigator" => set_nav(entry_for(&mut navigators, entry.alias, |n| n), entry)?,
+            "driver" => {
+                let drv = entry_for(&mut drivers, entry.alias, |navigator| Driver {
+                    navigator,
+                    key: None,
+                    key_format: KeyFormat::default(),
+                });
+                match entry.key {
+                    "signingkey" => {
+                        drv.key = Some(entry.value.trim())
+                            .filter(|k| !k.is_empty())
+                            .map(String::from);
+                    }
+                    "format" => {
+                        drv.key_format = entry.value.trim().parse().map_err(|e| eyre!("{}", e))?;
+                    }
+                    _ => set_nav(&mut drv.navigator, entry)?,
+                }
+            }
+            otherwise => bail!(
+                "Unexpected type `{}`, expected either `navigator` or `driver`.",
+                otherwise
+            ),
+        }
+    }
+
+    let missing_name = navigators
+        .iter()
+        .chain(drivers.iter().map(|d| &d.navigator))
+        .find(|n| n.name.is_empty());
+    if let Some(nav) = missing_name {
+        bail!("The entry `{}` is missing a name.", nav.alias.as_ref());
+    }
+
+    Ok(Config {
+        navigators,
+        drivers,
+        ..Config::default()
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::tests::{drv1, nav1, nav2};
+    use assert_fs::{prelude::*, TempDir};
+
+    #[test]
+    fn store_replaces_the_roster_in_one_write() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("gitconfig");
+        file.write_str(concat!(
+            "[user]\n\tname = ralle\n",
+            "[drive \"navigator.old\"]\n\tname = old\n\temail = old@bar.org\n",
+            "[drive]\n\tbackend = git\n",
+        ))
+        .unwrap();
+
+        let config = Config::from_iter([nav1().ent(), nav2().ent()]);
+        GitBackend::file(file.path()).store(&config).unwrap();
+
+        file.assert(concat!(
+            "[user]\n\tname = ralle\n",
+            "[drive]\n\tbackend = git\n",
+            "[drive \"navigator.nav1\"]\n\tname = bernd\n\temail = foo@bar.org\n",
+            "[drive \"navigator.nav2\"]\n\tname = ronny\n\temail = baz@bar.org\n",
+        ));
+        assert!(!dir.child("gitconfig.lock").path().exists());
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn load_ignores_entries_of_included_files() {
+        let dir = TempDir::new().unwrap();
+        dir.child("included")
+            .write_str("[drive \"navigator.nav2\"]\n\tname = ronny\n\temail = baz@bar.org\n")
+            .unwrap();
+        let file = dir.child("gitconfig");
+        file.write_str("[include]\n\tpath = included\n").unwrap();
+
+        let backend = GitBackend::file(file.path());
+        let config = Config::from_iter([nav1().ent()]);
+        backend.store(&config).unwrap();
+
+        assert_eq!(backend.load().unwrap(), config);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn parse_git_config_entries() {
+        let output = concat!(
+            "drive.navigator.nav1.name\nbernd\0",
+            "drive.driver.drv1.name\nralle\0",
+            "drive.navigator.nav1.email\nfoo@bar.org\0",
+            "drive.driver.drv1.email\nqux@bar.org\0",
+            "drive.driver.drv1.signingkey\nmy-key.pub\0",
+        );
+        let config = parse_entries(output).unwrap();
+
+        let expected = Config::from_iter([nav1().ent(), drv1("my-key.pub").ent()]);
+        assert_eq!(config, expected);
+    }
+
+    #[test]
+    fn parse_git_config_entries_with_key_format() {
+        let output = concat!(
+            "drive.driver.drv1.name\nralle\0",
+            "drive.driver.drv1.email\nqux@bar.org\0",
+            "drive.driver.drv1.signingkey\nmy-key.pub\0",
+            "drive.driver.drv1.format\nssh\0",
+        );
+        let config = parse_entries(output).unwrap();
+
+        assert_eq!(config.drivers[0].key_format, KeyFormat::Ssh);
+    }
+
+    #[test]
+    fn parse_git_config_entries_with_other_emails_and_aliases() {
+        let output = concat!(
+            "drive.navigator.nav1.name\nbernd\0",
+            "drive.navigator.nav1.email\nfoo@bar.org\0",
+            "drive.navigator.nav1.emails\noss=bernd@example.org\0",
+            "drive.navigator.nav1.emails\nhome=bernd@home.net\0",
+            "drive.navigator.nav1.aliases\nbernie\0",
+        );
+        let config = parse_entries(output).unwrap();
+
+        let nav = &config.navigators[0];
+        assert_eq!(nav.aliases, [Id::from("bernie")]);
+        assert_eq!(nav.email, "foo@bar.org");
+        assert_eq!(nav.email_for("oss"), Some("bernd@example.org"));
+        assert_eq!(nav.email_for("home"), Some("bernd@home.net"));
+    }
+
+    #[test]
+    fn parse_git_config_entries_with_archived_navigator() {
+        let output = concat!(
+            "drive.navigator.nav1.name\nbernd\0",
+            "drive.navigator.nav1.email\nfoo@bar.org\0",
+            "drive.navigator.nav1.archived\ntrue\0",
+            "drive.navigator.nav2.name\nronny\0",
+            "drive.navigator.nav2.email\nbaz@bar.org\0",
+        );
+        let config = parse_entries(output).unwrap();
+
+        assert!(config.navigators[0].archived);
+        assert!(!config.navigators[1].archived);
+
+        let err = parse_entries("drive.navigator.nav1.archived\nmaybe\0").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected a boolean for `archived` of the navigator `nav1`, but got `maybe`."
+        );
+    }
+
+    #[test]
+    fn parse_git_config_entries_with_dots_in_alias() {
+        let output = "drive.navigator.first.last.name\nbernd\0";
+        let config = parse_entries(output).unwrap();
+
+        assert_eq!(&*config.navigators[0].alias, "first.last");
+    }
+
+    #[test]
+    fn parse_git_config_entries_without_name() {
+        let output = "drive.navigator.nav1.email\nfoo@bar.org\0";
+        let err = parse_entries(output).unwrap_err();
+
+        assert_eq!(err.to_string(), "The entry `nav1` is missing a name.");
+    }
+
+    #[test]
+    fn parse_git_config_entries_with_unknown_key() {
+        let output = "drive.navigator.nav1.foo\nbar\0";
+        let err = parse_entries(output).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Unexpected key `foo` for the navigator `nav1`."
+        );
+    }
+}
@@ -1,5 +1,5 @@
 use super::Config;
-use crate::data::{Driver, Field, Id, Navigator, PartialNav};
+use crate::data::{Driver, Field, Id, KeyFormat, Navigator, PartialNav};
 use eyre::{Context, Result};
 use nom::{
     branch::alt,
@@ -226,8 +226,11 @@ fn inner_navigator(input: &str) -> IResult<&str, Navigator> {
         (Some(id), Some(name), Some(email)) => {
             let navigator = Navigator {
                 alias: Id(id),
+                aliases: Vec::new(),
                 name,
                 email,
+                emails: Vec::new(),
+                archived: false,
             };
             Ok((rest, navigator))
         }
@@ -243,6 +246,7 @@ fn driver(input: &str) -> IResult<&str, Driver> {
     map(navigator, |navigator| Driver {
         navigator,
         key: None,
+        key_format: KeyFormat::OpenPgp,
     })(input)
 }
 
@@ -337,6 +341,7 @@ mod tests {
         let expected = Config {
             navigators: vec![nav1(), nav2()],
             drivers: vec![drv1(None)],
+            ..Config::default()
         };
         assert_eq!(config, expected);
     }
@@ -354,8 +359,11 @@ mod tests {
 
         let expected = Config::from_iter([Navigator {
             alias: Id::from("nav"),
+            aliases: Vec::new(),
             name: String::from("foo 😅 bar\tbaz π "),
             email: String::from("\"foo\"@\\/bar.org\r\n"),
+            emails: Vec::new(),
+            archived: false,
         }]);
         assert_eq!(config, expected);
     }
@@ -373,8 +381,11 @@ mod tests {
 
         let expected = Config::from_iter([Navigator {
             alias: Id::from("nav"),
+            aliases: Vec::new(),
             name: String::from("\u{d7fe}\u{fffd}"),
             email: String::from("foo"),
+            emails: Vec::new(),
+            archived: false,
         }]);
         assert_eq!(config, expected);
     }
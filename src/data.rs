@@ -1,4 +1,4 @@
-use std::{borrow::Borrow, fmt, ops::Deref};
+use std::{borrow::Borrow, fmt, ops::Deref, path::PathBuf, str::FromStr, time::Duration};
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 #[repr(transparent)]
@@ -6,25 +6,67 @@ pub struct Id(pub String);
 
 impl Id {
     pub fn same_as_nav(&self, other: &Navigator) -> bool {
-        self == &other.alias
+        other.has_alias(self)
     }
 
     pub fn same_as_drv(&self, other: &Driver) -> bool {
-        self == &other.navigator.alias
+        other.has_alias(self)
     }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Navigator {
     pub alias: Id,
+    /// Secondary aliases, accepted in place of the alias, which is the one that is shown.
+    pub aliases: Vec<Id>,
     pub name: String,
+    /// The default email.
     pub email: String,
+    /// Other emails, chosen by their label.
+    pub emails: Vec<Email>,
+    /// Archived navigators are kept for looking up past commits,
+    /// but are not offered for driving anymore.
+    pub archived: bool,
+}
+
+/// An email with a label, e.g. `oss=alice@example.org`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Email {
+    pub label: String,
+    pub address: String,
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct Driver {
     pub navigator: Navigator,
     pub key: Option<String>,
+    pub key_format: KeyFormat,
+}
+
+/// The format of a signing key, as used for `gpg.format`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum KeyFormat {
+    /// A GPG key id, the default of git.
+    #[default]
+    OpenPgp,
+    /// An X.509 certificate, used with `gpgsm`.
+    X509,
+    /// A path to an SSH key, or a literal public key prefixed with `key::`.
+    Ssh,
+}
+
+/// How the navigators are ordered when selecting them for driving, set in `drive.order`.
+#[derive(Debug, Copy, Clone, Default, PartialEq, Eq)]
+pub enum NavigatorOrder {
+    /// The most recently driven with first.
+    #[default]
+    Recent,
+    /// The most often driven with first.
+    Frequent,
+    /// By alias.
+    Alpha,
+    /// As they appear in the roster.
+    ConfigOrder,
 }
 
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
@@ -36,8 +78,10 @@ pub enum Kind {
 #[derive(Debug, Copy, Clone, PartialEq, Eq)]
 pub enum Field {
     Alias,
+    Aliases,
     Name,
     Email,
+    Emails,
     Key,
 }
 
@@ -45,8 +89,10 @@ impl fmt::Display for Field {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
             Self::Alias => f.write_str("alias"),
+            Self::Aliases => f.write_str("other aliases"),
             Self::Name => f.write_str("name"),
             Self::Email => f.write_str("email"),
+            Self::Emails => f.write_str("other emails"),
             Self::Key => f.write_str("signing key"),
         }
     }
@@ -55,17 +101,24 @@ impl fmt::Display for Field {
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct PartialNav {
     pub id: Option<String>,
+    /// The secondary aliases, separated by commas.
+    pub aliases: Option<String>,
     pub name: Option<String>,
     pub email: Option<String>,
+    /// The other emails as `label=email`, separated by commas.
+    pub emails: Option<String>,
     pub key: Option<String>,
+    pub key_format: Option<KeyFormat>,
 }
 
 impl PartialNav {
     pub fn with(self, field: Field, value: impl Into<Option<String>>) -> Self {
         match field {
             Field::Alias => self.with_id(value),
+            Field::Aliases => self.with_aliases(value),
             Field::Name => self.with_name(value),
             Field::Email => self.with_email(value),
+            Field::Emails => self.with_emails(value),
             Field::Key => self.with_key(value),
         }
     }
@@ -77,6 +130,13 @@ impl PartialNav {
         }
     }
 
+    pub fn with_aliases(self, aliases: impl Into<Option<String>>) -> Self {
+        Self {
+            aliases: aliases.into(),
+            ..self
+        }
+    }
+
     pub fn with_name(self, name: impl Into<Option<String>>) -> Self {
         Self {
             name: name.into(),
@@ -91,38 +151,61 @@ impl PartialNav {
         }
     }
 
+    pub fn with_emails(self, emails: impl Into<Option<String>>) -> Self {
+        Self {
+            emails: emails.into(),
+            ..self
+        }
+    }
+
     pub fn with_key(self, key: impl Into<Option<String>>) -> Self {
         Self {
             key: key.into(),
             ..self
         }
     }
+
+    pub fn with_key_format(self, key_format: impl Into<Option<KeyFormat>>) -> Self {
+        Self {
+            key_format: key_format.into(),
+            ..self
+        }
+    }
 }
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct PartialIdNav {
     pub id: Id,
+    pub aliases: Option<String>,
     pub name: Option<String>,
     pub email: Option<String>,
+    pub emails: Option<String>,
     pub key: Option<String>,
+    pub key_format: Option<KeyFormat>,
 }
 
 impl PartialIdNav {
     pub fn new(id: impl Into<String>) -> Self {
         Self {
             id: Id(id.into()),
+            aliases: None,
             name: None,
             email: None,
+            emails: None,
             key: None,
+            key_format: None,
         }
     }
 
     pub fn merge(self, partial: PartialNav) -> Self {
         Self {
             id: self.id,
+            aliases: partial.aliases.or(self.aliases),
             name: partial.name.or(self.name),
             email: partial.email.or(self.email),
+            emails: partial.emails.or(self.emails),
             key: partial.key.or(self.key),
+            key_format: partial.key_format.or(self.key_format),
         }
     }
 
@@ -142,6 +225,14 @@ impl PartialIdNav {
         }
     }
 
+    #[cfg(test)]
+    pub fn with_emails<S: Into<String>>(self, emails: impl Into<Option<S>>) -> Self {
+        Self {
+            emails: emails.into().map(Into::into),
+            ..self
+        }
+    }
+
     #[cfg(test)]
     pub fn with_key<S: Into<String>>(self, key: impl Into<Option<S>>) -> Self {
         Self {
@@ -151,6 +242,189 @@ impl PartialIdNav {
     }
 }
 
+/// Where a drive session is stored and for which working trees it applies.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Scope {
+    /// Only the current working tree.
+    Worktree,
+    /// All working trees of the repository.
+    Shared,
+    /// All repositories of the user.
+    Global,
+}
+
+/// Options that apply to the drive session, independent of the action.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Session {
+    pub scope: Option<Scope>,
+    /// The trailers to add, overriding the configured ones if not empty.
+    pub trailers: Vec<Trailer>,
+    /// When a new drive ends by itself, if at all.
+    pub expires: Option<Expiry>,
+}
+
+/// When a drive session ends by itself.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Expiry {
+    /// After the duration, counting from the start of the drive.
+    After(Duration),
+    /// At the next occurrence of the time, as understood by git, e.g. `18:00`.
+    At(String),
+}
+
+/// Parses durations like `2h`, `90m`, or `1h30m`, with units of `d`, `h`, `m`, and `s`.
+pub fn parse_duration(s: &str) -> Result<Duration, String> {
+    let invalid = || {
+        format!(
+            "Invalid duration `{}`, expected e.g. `2h`, `90m`, or `1h30m`.",
+            s
+        )
+    };
+
+    let mut total = 0_u64;
+    let mut value = None::<u64>;
+    for c in s.trim().chars() {
+        if let Some(digit) = c.to_digit(10) {
+            value = Some(
+                value
+                    .unwrap_or(0)
+                    .saturating_mul(10)
+                    .saturating_add(u64::from(digit)),
+            );
+            continue;
+        }
+        let unit = match c {
+            'd' => 86_400,
+            'h' => 3_600,
+            'm' => 60,
+            's' => 1,
+            _ => return Err(invalid()),
+        };
+        let value = value.take().ok_or_else(invalid)?;
+        total = total.saturating_add(value.saturating_mul(unit));
+    }
+
+    if value.is_some() || total == 0 {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs(total))
+}
+
+/// A trailer that is added to commit messages while driving, e.g. `Co-Authored-By`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Trailer {
+    pub key: String,
+    pub target: TrailerTarget,
+}
+
+/// Whose identity goes into a trailer.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum TrailerTarget {
+    /// One trailer for every navigator.
+    Navigators,
+    /// One trailer for the driver, e.g. for `Signed-off-by`.
+    Driver,
+}
+
+/// Selects a driver for the repositories whose remote or path matches a pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DriverRule {
+    pub target: RuleTarget,
+    pub pattern: String,
+    pub driver: Id,
+}
+
+/// What the pattern of a driver rule is matched against.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum RuleTarget {
+    /// The URL of any remote, e.g. `github.com/acme/*`.
+    Remote,
+    /// The root of the working tree, e.g. `~/work/**`.
+    Path,
+}
+
+impl Trailer {
+    pub fn co_authored_by() -> Self {
+        Self {
+            key: String::from("Co-Authored-By"),
+            target: TrailerTarget::Navigators,
+        }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AmendCommits {
+    pub range: Option<String>,
+    /// The navigators to add, instead of the ones from the current session.
+    pub navigators: Vec<Id>,
+    /// Also rewrite commits that are already on the upstream branch.
+    pub force: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct VerifyCommits {
+    pub range: String,
+    /// The navigators to expect on every commit, instead of the ones from the session history.
+    pub navigators: Vec<Id>,
+}
+
+/// A git hook that is provided by the drive.
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum Hook {
+    /// Checks the commit message for the trailers of the navigators.
+    CommitMsg,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct InstallHook {
+    pub hook: Hook,
+    /// Add missing trailers instead of rejecting the commit.
+    pub fix: bool,
+    /// Replace an existing hook that was not installed by us.
+    pub force: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunHook {
+    pub hook: Hook,
+    /// Add missing trailers instead of rejecting the commit.
+    pub fix: bool,
+    /// The file with the commit message, as given to the hook by git.
+    pub message_file: PathBuf,
+}
+
+/// Copies or moves a navigator to the drivers, or a driver to the navigators.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ChangeSeat {
+    pub id: Option<Id>,
+    /// The signing key when promoting to a driver.
+    pub key: Option<String>,
+    pub key_format: Option<KeyFormat>,
+    /// Keep the original entry instead of moving it.
+    pub keep: bool,
+}
+
+/// Drives with navigators and adds the guests among them to the roster.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct SaveGuests {
+    /// Aliases, or guests given as `Name <email>`.
+    pub ids: Vec<Id>,
+    /// The alias for the only guest, instead of the local part of the email.
+    pub alias: Option<Id>,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct RenameAlias {
+    pub old: Option<Id>,
+    pub new: Option<Id>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct CheckConfig {
+    /// Fix the problems that have an unambiguous fix.
+    pub fix: bool,
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ShowNav {
     pub color: String,
@@ -177,6 +451,272 @@ impl<T: Into<String>> From<T> for Id {
     }
 }
 
+impl fmt::Display for Scope {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Worktree => f.pad("worktree"),
+            Self::Shared => f.pad("shared"),
+            Self::Global => f.pad("global"),
+        }
+    }
+}
+
+impl FromStr for Hook {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "commit-msg" => Ok(Self::CommitMsg),
+            otherwise => Err(format!(
+                "Unsupported hook `{}`, expected `commit-msg`.",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl Navigator {
+    /// Whether the id is the alias or one of the secondary aliases.
+    pub fn has_alias(&self, id: &str) -> bool {
+        &*self.alias == id || self.aliases.iter().any(|a| &**a == id)
+    }
+
+    /// The email with the label, or `None` if there is no such email.
+    pub fn email_for(&self, label: &str) -> Option<&str> {
+        self.emails
+            .iter()
+            .find(|e| e.label == label)
+            .map(|e| e.address.as_str())
+    }
+}
+
+impl FromStr for Email {
+    type Err = String;
+
+    /// Parses `label=email`, where the label consists of letters, digits, `-`, or `_`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (label, address) = match s.split_once('=') {
+            Some((label, address)) => (label.trim(), address.trim()),
+            None => return Err(format!("Expected `$label=$email`, but got `{}`.", s.trim())),
+        };
+        if label.is_empty()
+            || !label
+                .chars()
+                .all(|c| c.is_alphanumeric() || c == '-' || c == '_')
+        {
+            return Err(format!(
+                "Invalid label `{}`, expected only letters, digits, `-`, or `_`.",
+                label
+            ));
+        }
+        if address.is_empty() {
+            return Err(format!("The email for the label `{}` is missing.", label));
+        }
+
+        Ok(Self {
+            label: String::from(label),
+            address: String::from(address),
+        })
+    }
+}
+
+impl fmt::Display for Email {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}={}", self.label, self.address)
+    }
+}
+
+impl Driver {
+    pub fn has_alias(&self, id: &str) -> bool {
+        self.navigator.has_alias(id)
+    }
+}
+
+/// Parses a comma separated list of aliases, ignoring duplicates.
+pub fn parse_aliases(list: &str) -> Vec<Id> {
+    let mut aliases = Vec::<Id>::new();
+    for alias in list.split(',').map(str::trim).filter(|a| !a.is_empty()) {
+        if !aliases.iter().any(|a| &**a == alias) {
+            aliases.push(Id::from(alias));
+        }
+    }
+    aliases
+}
+
+/// The inverse of [`parse_aliases`].
+pub fn join_aliases(aliases: &[Id]) -> String {
+    aliases
+        .iter()
+        .map(AsRef::as_ref)
+        .collect::<Vec<&str>>()
+        .join(", ")
+}
+
+/// Parses a comma separated list of `label=email`, rejecting duplicate labels.
+pub fn parse_emails(list: &str) -> Result<Vec<Email>, String> {
+    let mut emails = Vec::<Email>::new();
+    for email in list.split(',').filter(|e| !e.trim().is_empty()) {
+        let email = email.parse::<Email>()?;
+        if emails.iter().any(|e| e.label == email.label) {
+            return Err(format!(
+                "The label `{}` is used more than once.",
+                email.label
+            ));
+        }
+        emails.push(email);
+    }
+    Ok(emails)
+}
+
+/// The inverse of [`parse_emails`].
+pub fn join_emails(emails: &[Email]) -> String {
+    emails
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+impl FromStr for KeyFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "openpgp" => Ok(Self::OpenPgp),
+            "x509" => Ok(Self::X509),
+            "ssh" => Ok(Self::Ssh),
+            otherwise => Err(format!(
+                "Unknown key format `{}`, expected one of `openpgp`, `x509`, or `ssh`.",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl FromStr for NavigatorOrder {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "recent" => Ok(Self::Recent),
+            "frequent" => Ok(Self::Frequent),
+            "alpha" => Ok(Self::Alpha),
+            "config-order" => Ok(Self::ConfigOrder),
+            otherwise => Err(format!(
+                "Unknown order `{}`, expected `recent`, `frequent`, `alpha`, or `config-order`.",
+                otherwise
+            )),
+        }
+    }
+}
+
+impl fmt::Display for KeyFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::OpenPgp => f.pad("openpgp"),
+            Self::X509 => f.pad("x509"),
+            Self::Ssh => f.pad("ssh"),
+        }
+    }
+}
+
+impl fmt::Display for Hook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::CommitMsg => f.pad("commit-msg"),
+        }
+    }
+}
+
+impl FromStr for Trailer {
+    type Err = String;
+
+    /// Parses `Key` or `Key=navigators` for navigators, and `Key=driver` for the driver.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (key, target) = match s.split_once('=') {
+            Some((key, target)) => (key.trim(), target.trim()),
+            None => (s.trim(), "navigators"),
+        };
+
+        if key.is_empty() || !key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(format!(
+                "The trailer key `{}` must only contain letters, digits, and `-`.",
+                key
+            ));
+        }
+
+        let target = match target {
+            "navigators" | "navigator" => TrailerTarget::Navigators,
+            "driver" => TrailerTarget::Driver,
+            otherwise => {
+                return Err(format!(
+                    "Unknown trailer target `{}`, expected either `navigators` or `driver`.",
+                    otherwise
+                ))
+            }
+        };
+
+        Ok(Self {
+            key: String::from(key),
+            target,
+        })
+    }
+}
+
+impl FromStr for DriverRule {
+    type Err = String;
+
+    /// Parses `remote:<pattern>=<alias>` and `path:<pattern>=<alias>`.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (rule, driver) = s
+            .rsplit_once('=')
+            .ok_or_else(|| format!("The driver rule `{}` is missing the `=<alias>`.", s))?;
+        let (target, pattern) = match rule.split_once(':') {
+            Some(("remote", pattern)) => (RuleTarget::Remote, pattern.trim()),
+            Some(("path", pattern)) => (RuleTarget::Path, pattern.trim()),
+            _ => {
+                return Err(format!(
+                    "The driver rule `{}` must start with either `remote:` or `path:`.",
+                    s
+                ))
+            }
+        };
+
+        let driver = driver.trim();
+        if pattern.is_empty() || driver.is_empty() {
+            return Err(format!(
+                "The driver rule `{}` needs a pattern and an alias.",
+                s
+            ));
+        }
+
+        Ok(Self {
+            target,
+            pattern: String::from(pattern),
+            driver: Id::from(driver),
+        })
+    }
+}
+
+impl fmt::Display for DriverRule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let target = match self.target {
+            RuleTarget::Remote => "remote",
+            RuleTarget::Path => "path",
+        };
+        write!(f, "{}:{}={}", target, self.pattern, &*self.driver)
+    }
+}
+
+impl fmt::Display for Trailer {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.target {
+            TrailerTarget::Navigators => f.write_str(&self.key),
+            TrailerTarget::Driver => write!(f, "{}=driver", self.key),
+        }
+    }
+}
+
 impl fmt::Display for Kind {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
@@ -188,6 +728,11 @@ impl fmt::Display for Kind {
 
 pub trait IdRef {
     fn id(&self) -> &Id;
+
+    /// Whether the entry is known by the id, which might be a secondary alias.
+    fn has_alias(&self, id: &str) -> bool {
+        &**self.id() == id
+    }
 }
 
 impl IdRef for Id {
@@ -196,9 +741,13 @@ impl IdRef for Id {
     }
 }
 
-impl IdRef for &Id {
+impl<T: IdRef + ?Sized> IdRef for &T {
     fn id(&self) -> &Id {
-        self
+        T::id(self)
+    }
+
+    fn has_alias(&self, id: &str) -> bool {
+        T::has_alias(self, id)
     }
 }
 
@@ -206,12 +755,20 @@ impl IdRef for Navigator {
     fn id(&self) -> &Id {
         &self.alias
     }
+
+    fn has_alias(&self, id: &str) -> bool {
+        Self::has_alias(self, id)
+    }
 }
 
 impl IdRef for Driver {
     fn id(&self) -> &Id {
         &self.navigator.alias
     }
+
+    fn has_alias(&self, id: &str) -> bool {
+        Self::has_alias(self, id)
+    }
 }
 
 impl Borrow<str> for &Navigator {
@@ -239,16 +796,22 @@ pub mod tests {
     pub fn nav1() -> Navigator {
         Navigator {
             alias: Id::from("nav1"),
+            aliases: Vec::new(),
             name: String::from("bernd"),
             email: String::from("foo@bar.org"),
+            emails: Vec::new(),
+            archived: false,
         }
     }
 
     pub fn nav2() -> Navigator {
         Navigator {
             alias: Id::from("nav2"),
+            aliases: Vec::new(),
             name: String::from("ronny"),
             email: String::from("baz@bar.org"),
+            emails: Vec::new(),
+            archived: false,
         }
     }
 
@@ -256,10 +819,151 @@ pub mod tests {
         Driver {
             navigator: Navigator {
                 alias: Id::from("drv1"),
+                aliases: Vec::new(),
                 name: String::from("ralle"),
                 email: String::from("qux@bar.org"),
+                emails: Vec::new(),
+                archived: false,
             },
             key: key.into().map(String::from),
+            key_format: KeyFormat::OpenPgp,
+        }
+    }
+
+    #[test]
+    fn parse_emails_list() {
+        let emails = parse_emails("oss=bernd@example.org, home = bernd@home.net,").unwrap();
+        assert_eq!(
+            emails,
+            [
+                Email {
+                    label: String::from("oss"),
+                    address: String::from("bernd@example.org"),
+                },
+                Email {
+                    label: String::from("home"),
+                    address: String::from("bernd@home.net"),
+                },
+            ]
+        );
+        assert_eq!(
+            join_emails(&emails),
+            "oss=bernd@example.org, home=bernd@home.net"
+        );
+        assert_eq!(parse_emails(" ").unwrap(), []);
+
+        assert_eq!(
+            parse_emails("oss=a@b.org, oss=c@d.org").unwrap_err(),
+            "The label `oss` is used more than once."
+        );
+        assert_eq!(
+            parse_emails("bernd@example.org").unwrap_err(),
+            "Expected `$label=$email`, but got `bernd@example.org`."
+        );
+        assert_eq!(
+            parse_emails("o:ss=bernd@example.org").unwrap_err(),
+            "Invalid label `o:ss`, expected only letters, digits, `-`, or `_`."
+        );
+    }
+
+    #[test]
+    fn parse_trailer() {
+        assert_eq!(
+            "Co-authored-by".parse::<Trailer>().unwrap(),
+            Trailer {
+                key: String::from("Co-authored-by"),
+                target: TrailerTarget::Navigators,
+            }
+        );
+        assert_eq!(
+            "Signed-off-by=driver".parse::<Trailer>().unwrap(),
+            Trailer {
+                key: String::from("Signed-off-by"),
+                target: TrailerTarget::Driver,
+            }
+        );
+        assert_eq!(
+            "Pair-With = navigators".parse::<Trailer>().unwrap(),
+            Trailer {
+                key: String::from("Pair-With"),
+                target: TrailerTarget::Navigators,
+            }
+        );
+    }
+
+    #[test]
+    fn parse_driver_rule() {
+        let rule = "remote:github.com/acme/*=work"
+            .parse::<DriverRule>()
+            .unwrap();
+        assert_eq!(
+            rule,
+            DriverRule {
+                target: RuleTarget::Remote,
+                pattern: String::from("github.com/acme/*"),
+                driver: Id::from("work"),
+            }
+        );
+        assert_eq!(rule.to_string(), "remote:github.com/acme/*=work");
+
+        assert_eq!(
+            "path: ~/private/** = me".parse::<DriverRule>().unwrap(),
+            DriverRule {
+                target: RuleTarget::Path,
+                pattern: String::from("~/private/**"),
+                driver: Id::from("me"),
+            }
+        );
+        assert_eq!(
+            "github.com/acme/*=work".parse::<DriverRule>().unwrap_err(),
+            "The driver rule `github.com/acme/*=work` must start with either `remote:` or `path:`."
+        );
+    }
+
+    #[test]
+    fn parse_navigator_order() {
+        assert_eq!("recent".parse(), Ok(NavigatorOrder::Recent));
+        assert_eq!("frequent".parse(), Ok(NavigatorOrder::Frequent));
+        assert_eq!("alpha".parse(), Ok(NavigatorOrder::Alpha));
+        assert_eq!("config-order".parse(), Ok(NavigatorOrder::ConfigOrder));
+        assert_eq!(
+            "random".parse::<NavigatorOrder>(),
+            Err(String::from(
+                "Unknown order `random`, expected `recent`, `frequent`, `alpha`, or `config-order`."
+            ))
+        );
+    }
+
+    #[test]
+    fn parse_durations() {
+        assert_eq!(parse_duration("2h"), Ok(Duration::from_secs(7_200)));
+        assert_eq!(parse_duration("90m"), Ok(Duration::from_secs(5_400)));
+        assert_eq!(parse_duration("1h30m"), Ok(Duration::from_secs(5_400)));
+        assert_eq!(parse_duration("1d"), Ok(Duration::from_secs(86_400)));
+    }
+
+    #[test]
+    fn parse_invalid_durations() {
+        for invalid in ["", "2", "h", "2x", "0m", "1h30"] {
+            assert_eq!(
+                parse_duration(invalid).unwrap_err(),
+                format!(
+                    "Invalid duration `{}`, expected e.g. `2h`, `90m`, or `1h30m`.",
+                    invalid
+                )
+            );
         }
     }
+
+    #[test]
+    fn parse_invalid_trailer() {
+        assert_eq!(
+            "Signed off by".parse::<Trailer>().unwrap_err(),
+            "The trailer key `Signed off by` must only contain letters, digits, and `-`."
+        );
+        assert_eq!(
+            "Signed-off-by=me".parse::<Trailer>().unwrap_err(),
+            "Unknown trailer target `me`, expected either `navigators` or `driver`."
+        );
+    }
 }
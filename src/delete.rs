@@ -3,6 +3,7 @@ use crate::{
     data::{IdRef, Kind, Modification},
     ui::{self, SelectMany},
 };
+use console::style;
 use eyre::Result;
 
 pub fn select(ui: impl SelectMany, kind: Kind, config: &mut Config) -> Result<Modification> {
@@ -12,9 +13,25 @@ pub fn select(ui: impl SelectMany, kind: Kind, config: &mut Config) -> Result<Mo
 }
 
 pub fn run<I: IdRef>(kind: Kind, config: &mut Config, ids: &[I]) -> Modification {
+    let ids = ids
+        .iter()
+        .filter(|id| {
+            let included = config.included_from(kind, id.id());
+            if let Some(file) = included {
+                eprintln!(
+                    "The {} {} is defined in the included file {} and cannot be deleted here.",
+                    kind,
+                    style(id.id().as_ref()).cyan(),
+                    file.display()
+                );
+            }
+            included.is_none()
+        })
+        .collect::<Vec<_>>();
+
     match kind {
-        Kind::Navigator => do_delete(&mut config.navigators, ids),
-        Kind::Driver => do_delete(&mut config.drivers, ids),
+        Kind::Navigator => do_delete(&mut config.navigators, &ids),
+        Kind::Driver => do_delete(&mut config.drivers, &ids),
     }
 }
 
@@ -22,7 +39,7 @@ fn do_delete<T: IdRef, I: IdRef>(data: &mut Vec<T>, ids: &[I]) -> Modification {
     let mut changed = Modification::Unchanged;
     let mut i = 0;
     while i != data.len() {
-        if ids.iter().any(|id| id.id() == data[i].id()) {
+        if ids.iter().any(|id| data[i].has_alias(id.id())) {
             drop(data.remove(i));
             changed = Modification::Changed;
         } else {
@@ -38,7 +55,7 @@ mod tests {
     use crate::{
         data::{
             tests::{drv1, nav1, nav2},
-            Id,
+            Id, Navigator,
         },
         ui::{
             util::{select_many, NoUi},
@@ -55,6 +72,18 @@ mod tests {
         assert_eq!(&config.drivers, &[drv1(None)]);
     }
 
+    #[test]
+    fn test_delete_by_secondary_alias() {
+        let nav = Navigator {
+            aliases: vec![Id::from("bernie")],
+            ..nav1()
+        };
+        let mut config = Config::from_iter([nav, nav2()]);
+        let modified = run(Kind::Navigator, &mut config, &[Id::from("bernie")]);
+        assert_eq!(modified, Modification::Changed);
+        assert_eq!(&config.navigators, &[nav2()]);
+    }
+
     #[test]
     fn test_delete_non_existing() {
         let mut config = Config::from_iter([nav1().ent(), nav2().ent(), drv1(None).ent()]);
@@ -82,10 +111,14 @@ mod tests {
                 &[
                     Selectable {
                         item: "nav1",
+                        name: "bernd",
+                        email: "foo@bar.org",
                         checked: false
                     },
                     Selectable {
                         item: "nav2",
+                        name: "ronny",
+                        email: "baz@bar.org",
                         checked: false
                     }
                 ]
@@ -108,10 +141,14 @@ mod tests {
                 &[
                     Selectable {
                         item: "nav1",
+                        name: "bernd",
+                        email: "foo@bar.org",
                         checked: false
                     },
                     Selectable {
                         item: "nav2",
+                        name: "ronny",
+                        email: "baz@bar.org",
                         checked: false
                     }
                 ]
@@ -133,6 +170,8 @@ mod tests {
                 items,
                 &[Selectable {
                     item: "drv1",
+                    name: "ralle",
+                    email: "qux@bar.org",
                     checked: false
                 },]
             );
@@ -154,10 +193,14 @@ mod tests {
                 &[
                     Selectable {
                         item: "nav1",
+                        name: "bernd",
+                        email: "foo@bar.org",
                         checked: false
                     },
                     Selectable {
                         item: "nav2",
+                        name: "ronny",
+                        email: "baz@bar.org",
                         checked: false
                     }
                 ]
@@ -180,10 +223,14 @@ mod tests {
                 &[
                     Selectable {
                         item: "nav1",
+                        name: "bernd",
+                        email: "foo@bar.org",
                         checked: false
                     },
                     Selectable {
                         item: "nav2",
+                        name: "ronny",
+                        email: "baz@bar.org",
                         checked: false
                     }
                 ]
@@ -1,16 +1,23 @@
 use crate::{
-    config::Config,
-    data::{Id, IdRef, Kind, Modification, Navigator, ShowNav},
-    ui::{self, SelectMany},
+    config::{self, Config},
+    data::{
+        Driver, Expiry, Id, IdRef, Kind, Modification, Navigator, NavigatorOrder, SaveGuests,
+        Scope, Session, ShowNav, Trailer, TrailerTarget,
+    },
+    hook,
+    repo::{self, ConfigFile, Git, Repository},
+    template::{Template, Values},
+    ui::{self, SelectMany, SelectOne},
 };
 use console::{style, Style};
-use eyre::{eyre, Result, WrapErr};
+use eyre::{bail, eyre, Result, WrapErr};
 use std::{
-    borrow::Borrow,
-    fs::File,
+    borrow::{Borrow, Cow},
+    cmp::Reverse,
+    fs::{File, OpenOptions},
     io::ErrorKind,
     path::{Path, PathBuf},
-    process::Command as Proc,
+    time::{SystemTime, UNIX_EPOCH},
 };
 
 pub fn current(
@@ -18,8 +25,9 @@ pub fn current(
         color,
         fail_if_empty,
     }: ShowNav,
+    session: &Session,
 ) -> Modification {
-    let current = current_fallible(&color);
+    let current = current_fallible(&color, session.scope);
     if fail_if_empty && matches!(current, Ok(false) | Err(_)) {
         std::process::exit(1);
     }
@@ -27,40 +35,248 @@ pub fn current(
     Modification::Unchanged
 }
 
-pub fn select(ui: impl SelectMany, config: &Config) -> Result<Option<Modification>> {
+pub fn select(
+    ui: impl SelectMany,
+    config: &Config,
+    session: &Session,
+) -> Result<Option<Modification>> {
     // TODO: proper error type
-    if config.navigators.is_empty() {
+    if config.navigators.iter().all(|n| n.archived) {
         Ok(None)
     } else {
-        select_navigators(ui, config).map(Some)
+        select_navigators(ui, config, session).map(Some)
     }
 }
 
-fn select_navigators(ui: impl SelectMany, config: &Config) -> Result<Modification> {
-    let currently = get_current().unwrap_or_default();
-    let ids = ui::select_ids_from(ui, Kind::Navigator, config, &currently)?;
-    run(&ids, config)
+fn select_navigators(
+    ui: impl SelectMany,
+    config: &Config,
+    session: &Session,
+) -> Result<Modification> {
+    // fail before prompting if there is no session to drive in
+    let scope = active_scope(session.scope)?;
+    let currently = get_current(Some(scope)).unwrap_or_default();
+    // archived navigators are not offered
+    let mut active = config
+        .navigators
+        .iter()
+        .filter(|n| !n.archived)
+        .cloned()
+        .collect::<Config>();
+    let order = navigator_order(Repository::discover().ok().as_ref())?;
+    sort_navigators(&mut active.navigators, order, &read_usage()?);
+    let ids = ui::select_ids_from(ui, Kind::Navigator, &active, &currently)?;
+    run(&ids, config, session)
 }
 
-pub fn run<I>(ids: &[I], config: &Config) -> Result<Modification>
+pub fn run<I>(ids: &[I], config: &Config, session: &Session) -> Result<Modification>
 where
     I: IdRef,
 {
+    let label = email_label(Repository::discover().ok().as_ref())?;
     let navigators = ids
         .iter()
-        .map(|id| match_navigator(id.id(), config))
+        .map(|id| match_navigator(id.id(), config, label.as_deref()))
         .collect::<Result<Vec<_>>>()?;
+    reject_archived(&navigators, config)?;
 
     if navigators.is_empty() {
-        return alone();
+        return alone(session);
     }
 
-    drive_with(navigators.into_iter())?;
+    drive_with(navigators.iter().map(|n| &**n), session)?;
+
+    let aliases = navigators
+        .iter()
+        .filter_map(|n| roster_alias(n, config))
+        .collect::<Vec<_>>();
+    record_usage(&aliases)?;
 
     Ok(Modification::Unchanged)
 }
 
-fn match_navigator<'config>(query: &Id, config: &'config Config) -> Result<&'config Navigator> {
+/// The alias of the navigator in the roster, without the label of `alias:label`,
+/// or `None` for guests.
+fn roster_alias<'a>(nav: &'a Navigator, config: &Config) -> Option<&'a str> {
+    if config.navigators.iter().any(|n| n.alias == nav.alias) {
+        return Some(&*nav.alias);
+    }
+    let (alias, _) = nav.alias.rsplit_once(':')?;
+    config
+        .navigators
+        .iter()
+        .any(|n| &*n.alias == alias)
+        .then_some(alias)
+}
+
+/// Archived navigators can still be resolved for past commits, but not be driven with.
+fn reject_archived(navigators: &[Cow<'_, Navigator>], config: &Config) -> Result<()> {
+    let nav = match navigators.iter().find(|n| n.archived) {
+        Some(nav) => nav,
+        None => return Ok(()),
+    };
+    let alias = roster_alias(nav, config).unwrap_or(&*nav.alias);
+    Err(eyre!(
+        "The navigator {} is archived, unarchive it with `git drive unarchive {}`.",
+        style(alias).cyan(),
+        alias
+    ))
+}
+
+/// Adds the guests among the navigators to the roster and starts driving with all of them.
+/// A guest whose email is already in the roster uses the existing entry instead.
+pub fn run_saving(
+    SaveGuests { ids, alias }: SaveGuests,
+    config: &mut Config,
+    session: &Session,
+) -> Result<Modification> {
+    let guests = ids.iter().filter(|id| guest(id).is_some()).count();
+    if alias.is_some() && guests != 1 {
+        bail!(
+            "{} needs exactly one guest to save, but got {}.",
+            style("--as").yellow(),
+            guests
+        );
+    }
+
+    let mut changed = Modification::Unchanged;
+    let mut aliases = Vec::with_capacity(ids.len());
+    for id in ids {
+        let mut guest = match guest(&id) {
+            Some(guest) => guest,
+            None => {
+                aliases.push(id);
+                continue;
+            }
+        };
+        if let Some(known) = config.navigators.iter().find(|n| n.email == guest.email) {
+            aliases.push(known.alias.clone());
+            continue;
+        }
+
+        guest.alias = alias.clone().unwrap_or_else(|| guest_alias(&guest.email));
+        if config.navigators.iter().any(|n| guest.alias.same_as_nav(n)) {
+            bail!("Alias {} already exists.", style(&*guest.alias).cyan());
+        }
+        println!(
+            "Saved {} <{}> as {}.",
+            guest.name,
+            guest.email,
+            style(&*guest.alias).cyan()
+        );
+        aliases.push(guest.alias.clone());
+        config.navigators.push(guest);
+        changed = Modification::Changed;
+    }
+
+    run(&aliases, config, session)?;
+
+    Ok(changed)
+}
+
+/// A navigator given as `Name <email>` instead of an alias, who does not need to be in the roster.
+/// The whole identity is used as the alias, so that the session can resolve it again.
+fn guest(query: &Id) -> Option<Navigator> {
+    let query = query.trim();
+    if !query.ends_with('>') {
+        return None;
+    }
+    let trailer = format!("Co-Authored-By: {}", query);
+    let co_author = co_authors::CoAuthor::try_from(trailer.as_str()).ok()?;
+    let email = co_author.mail.map(str::trim).filter(|m| !m.is_empty())?;
+
+    Some(Navigator {
+        alias: Id::from(query),
+        aliases: Vec::new(),
+        name: String::from(co_author.name.trim()),
+        email: String::from(email),
+        emails: Vec::new(),
+        archived: false,
+    })
+}
+
+/// The alias for a saved guest, the local part of the email.
+fn guest_alias(email: &str) -> Id {
+    let local = email.split_once('@').map_or(email, |(local, _)| local);
+    Id(local.to_lowercase())
+}
+
+/// The order of the navigators when selecting them, usually set globally.
+fn navigator_order(repo: Option<&Repository>) -> Result<NavigatorOrder> {
+    repo::config_value(repo, NAVIGATOR_ORDER)?.map_or_else(
+        || Ok(NavigatorOrder::default()),
+        |order| {
+            order
+                .parse()
+                .map_err(|e: String| eyre!(e).wrap_err("Invalid order in `drive.order`"))
+        },
+    )
+}
+
+/// Sorts the navigators by their usage, by alias, or leaves them in the order of the roster.
+/// Navigators that have never been driven with keep the order of the roster, after the others.
+fn sort_navigators(navigators: &mut [Navigator], order: NavigatorOrder, usage: &[Usage]) {
+    let usage_of = |nav: &Navigator| usage.iter().find(|u| u.alias == nav.alias);
+    match order {
+        NavigatorOrder::Recent => {
+            navigators.sort_by_key(|n| Reverse(usage_of(n).map(|u| (u.last_used, u.count))));
+        }
+        NavigatorOrder::Frequent => {
+            navigators.sort_by_key(|n| Reverse(usage_of(n).map(|u| (u.count, u.last_used))));
+        }
+        NavigatorOrder::Alpha => navigators.sort_by_cached_key(|n| n.alias.to_lowercase()),
+        NavigatorOrder::ConfigOrder => {}
+    }
+}
+
+/// The label of the emails that navigators use, usually set per repository.
+pub fn email_label(repo: Option<&Repository>) -> Result<Option<String>> {
+    repo::config_value(repo, EMAIL_LABEL)
+}
+
+/// Finds the navigator for an alias, `alias:label`, or a guest.
+/// The email with the `label` is used instead of the default one, if the navigator has one.
+pub fn match_navigator<'config>(
+    query: &Id,
+    config: &'config Config,
+    label: Option<&str>,
+) -> Result<Cow<'config, Navigator>> {
+    if let Some(guest) = guest(query) {
+        return Ok(Cow::Owned(guest));
+    }
+
+    let is_alias = config.navigators.iter().any(|n| query.same_as_nav(n));
+    if let Some((alias, label)) = query.rsplit_once(':').filter(|_| !is_alias) {
+        let nav = match_roster_navigator(&Id::from(alias), config)?;
+        let email = nav.email_for(label).ok_or_else(|| {
+            eyre!(
+                "The navigator {} has no email with the label `{}`.",
+                style(&*nav.alias).cyan(),
+                label
+            )
+        })?;
+        // the label is kept in the alias, so that the session uses the same email
+        return Ok(Cow::Owned(Navigator {
+            alias: Id(format!("{}:{}", nav.alias.as_ref(), label)),
+            email: String::from(email),
+            ..nav.clone()
+        }));
+    }
+
+    let nav = match_roster_navigator(query, config)?;
+    Ok(match label.and_then(|label| nav.email_for(label)) {
+        Some(email) => Cow::Owned(Navigator {
+            email: String::from(email),
+            ..nav.clone()
+        }),
+        None => Cow::Borrowed(nav),
+    })
+}
+
+fn match_roster_navigator<'config>(
+    query: &Id,
+    config: &'config Config,
+) -> Result<&'config Navigator> {
     let direct_matches = config.navigators.iter().filter(|n| query.same_as_nav(n));
     if let Some(direct) = validate_matches(query, direct_matches) {
         return direct;
@@ -130,58 +346,381 @@ fn match_caseless(query: &str, navigator: &str) -> bool {
     )
 }
 
-pub fn alone() -> Result<Modification> {
-    let sc = Proc::new("git")
-        .args(&["config", "--unset", "commit.template"])
-        .spawn()?
-        .wait()?;
+pub fn alone(session: &Session) -> Result<Modification> {
+    let scope = active_scope(session.scope)?;
+    end_session(scope)?;
+
+    Ok(Modification::Unchanged)
+}
+
+pub fn select_driver(
+    ui: impl SelectOne,
+    config: &Config,
+    session: &Session,
+) -> Result<Modification> {
+    match ui::select_id_from(ui, Kind::Driver, config)? {
+        Some(id) => switch_driver(id, config, session),
+        None => bail!("No drivers to drive as"),
+    }
+}
+
+/// Takes the driver seat in the active session.
+pub fn switch_driver(id: &Id, config: &Config, session: &Session) -> Result<Modification> {
+    let driver = match config.drivers.iter().find(|d| id.same_as_drv(d)) {
+        Some(driver) => driver,
+        None => bail!("Alias {} does not exist.", style(&**id).cyan()),
+    };
+
+    take_seat(active_scope(session.scope)?, driver)?;
+
+    println!("Driving as {} now.", style(&*driver.navigator.alias).cyan());
+    Ok(Modification::Unchanged)
+}
+
+/// Sets the identity and signing key of the driver in the git config of the scope.
+/// Commits are signed as well if `drive.gpgsign` is set to `true`.
+pub fn take_seat(scope: Scope, driver: &Driver) -> Result<()> {
+    let repo = match scope {
+        Scope::Global => None,
+        Scope::Worktree | Scope::Shared => Some(Repository::discover()?),
+    };
+    let sign = repo::config_value(repo.as_ref(), "drive.gpgsign")?.as_deref() == Some("true");
 
-    match sc.code() {
-        Some(0 | 5) => {}
-        Some(c) => std::process::exit(c),
-        None => std::process::exit(127),
+    if scope == Scope::Worktree {
+        enable_worktree_config()?;
     }
+    let mut git_config = git_config(scope)?;
+    set_driver(&mut git_config, driver, sign)?;
+    save(&git_config)
+}
+
+/// Sets `user.name`, `user.email`, `user.signingkey`, and `gpg.format` for the driver.
+/// A driver without a key unsets them, together with `commit.gpgsign`,
+/// so that the key of the previous driver is not used.
+fn set_driver(git_config: &mut ConfigFile, driver: &Driver, sign: bool) -> Result<()> {
+    git_config.set("user.name", &driver.navigator.name)?;
+    git_config.set("user.email", &driver.navigator.email)?;
+
+    match driver.key.as_deref() {
+        Some(key) => {
+            git_config.set("user.signingkey", key)?;
+            git_config.set("gpg.format", &driver.key_format.to_string())?;
+            if sign {
+                git_config.set("commit.gpgsign", "true")?;
+            }
+        }
+        None => {
+            git_config.unset("user.signingkey")?;
+            git_config.unset("gpg.format")?;
+            git_config.unset("commit.gpgsign")?;
+        }
+    }
+
+    Ok(())
+}
 
-    let git_dir = git_dir()?;
-    let mut current_navigators_file = git_dir;
-    current_navigators_file.push(concat!(".", env!("CARGO_PKG_NAME"), "_current_navigators"));
+fn end_session(scope: Scope) -> Result<()> {
+    let mut git_config = git_config(scope)?;
+    if git_config.unset("commit.template")? {
+        save(&git_config)?;
+    }
+
+    remove_file(&navigators_file(scope)?)?;
+    remove_file(&expiry_file(scope)?)?;
 
-    if let Err(e) = std::fs::remove_file(&current_navigators_file) {
+    record_history(scope, &[])
+}
+
+fn remove_file(file: &Path) -> Result<()> {
+    if let Err(e) = std::fs::remove_file(file) {
         if e.kind() != ErrorKind::NotFound {
-            return Err(eyre!(e).wrap_err(format!("File: {}", current_navigators_file.display())));
+            return Err(eyre!(e).wrap_err(format!("File: {}", file.display())));
         }
     }
+    Ok(())
+}
 
-    Ok(Modification::Unchanged)
+/// The scopes of the sessions that can be reached from here.
+/// Outside of a repository, that is only the global session.
+fn reachable_scopes() -> &'static [Scope] {
+    if Repository::discover().is_ok() {
+        &[Scope::Worktree, Scope::Shared, Scope::Global]
+    } else {
+        &[Scope::Global]
+    }
 }
 
+/// Ends the sessions that have expired, like `alone` would.
+/// Outside of a repository, only the global session is checked.
+pub fn end_expired_sessions() -> Result<()> {
+    for &scope in reachable_scopes() {
+        if navigators_file(scope)?.exists() && is_expired(scope)? {
+            end_session(scope)?;
+            eprintln!(
+                "The {} drive session has expired, driving alone now.",
+                scope
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the alias of a renamed navigator in the sessions and their history,
+/// for the current working tree, the repository, and the global session,
+/// as well as in the usage of the navigators.
+pub fn rename_navigator(old: &Id, new: &Id) -> Result<()> {
+    for &scope in reachable_scopes() {
+        let navigators_file = navigators_file(scope)?;
+        if navigators_file.exists() {
+            let data = read_data(&navigators_file)
+                .wrap_err_with(|| format!("File: {}", navigators_file.display()))?;
+            if let Some(renamed) = rename_alias(&data, old, new) {
+                write_data(&navigators_file, &renamed)
+                    .wrap_err_with(|| format!("File: {}", navigators_file.display()))?;
+            }
+        }
+
+        let history_file = history_file(scope)?;
+        if history_file.exists() {
+            let data = read_data(&history_file)
+                .wrap_err_with(|| format!("File: {}", history_file.display()))?;
+            if let Some(renamed) = rename_in_history(&data, old, new) {
+                write_data(&history_file, &renamed)
+                    .wrap_err_with(|| format!("File: {}", history_file.display()))?;
+            }
+        }
+    }
+
+    let mut usage = read_usage()?;
+    if usage.iter().any(|u| u.alias == *old) {
+        usage.retain(|u| u.alias != *new);
+        for used in usage.iter_mut().filter(|u| u.alias == *old) {
+            used.alias = new.clone();
+        }
+        let usage_file = usage_file()?;
+        write_data(&usage_file, &serialize_usage(&usage))
+            .wrap_err_with(|| format!("File: {}", usage_file.display()))?;
+    }
+    Ok(())
+}
+
+/// Renames the alias in the joined aliases, keeping the label of `alias:label`,
+/// returns `None` if it does not appear.
+fn rename_alias(aliases: &[u8], old: &str, new: &str) -> Option<Vec<u8>> {
+    let mut changed = false;
+    let renamed = aliases
+        .split(|b| *b == SEPARATOR)
+        .map(|alias| {
+            let label = alias
+                .strip_prefix(old.as_bytes())
+                .filter(|rest| rest.is_empty() || rest.starts_with(b":"));
+            match label {
+                Some(label) => {
+                    changed = true;
+                    [new.as_bytes(), label].concat()
+                }
+                None => alias.to_vec(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join([SEPARATOR].as_ref());
+
+    changed.then_some(renamed)
+}
+
+/// Renames the alias in each line of the history, after the start time.
+fn rename_in_history(data: &[u8], old: &str, new: &str) -> Option<Vec<u8>> {
+    let mut changed = false;
+    let renamed = data
+        .split(|b| *b == b'\n')
+        .map(|line| match line.iter().position(|b| *b == b' ') {
+            Some(pos) => {
+                let (started, aliases) = line.split_at(pos + 1);
+                match rename_alias(aliases, old, new) {
+                    Some(aliases) => {
+                        changed = true;
+                        [started, aliases.as_slice()].concat()
+                    }
+                    None => line.to_vec(),
+                }
+            }
+            None => line.to_vec(),
+        })
+        .collect::<Vec<_>>()
+        .join(&b'\n');
+
+    changed.then_some(renamed)
+}
+
+fn is_expired(scope: Scope) -> Result<bool> {
+    Ok(read_expiry(scope)?.map_or(false, |expires| expires <= now()))
+}
+
+/// The time at which the session of the scope expires, `None` if it does not expire.
+fn read_expiry(scope: Scope) -> Result<Option<u64>> {
+    let expiry_file = expiry_file(scope)?;
+    if !expiry_file.exists() {
+        return Ok(None);
+    }
+
+    let expires = std::fs::read_to_string(&expiry_file)
+        .wrap_err_with(|| format!("File: {}", expiry_file.display()))?;
+    let expires = expires
+        .trim()
+        .parse::<u64>()
+        .wrap_err_with(|| format!("File: {}", expiry_file.display()))?;
+    Ok(Some(expires))
+}
+
+/// Formats the remaining seconds like the durations of `--for`, e.g. `1h30m`.
+fn format_remaining(seconds: u64) -> String {
+    let (hours, minutes) = (seconds / 3_600, seconds % 3_600 / 60);
+    match (hours, minutes) {
+        (0, 0) => format!("{}s", seconds),
+        (0, minutes) => format!("{}m", minutes),
+        (hours, 0) => format!("{}h", hours),
+        (hours, minutes) => format!("{}h{}m", hours, minutes),
+    }
+}
+
+/// The time at which the session expires, in seconds since the unix epoch.
+fn expires_at(expiry: &Expiry) -> Result<u64> {
+    match expiry {
+        Expiry::After(duration) => Ok(now().saturating_add(duration.as_secs())),
+        Expiry::At(time) => {
+            // git understands the same times as for `git log --until`,
+            // and converts them to seconds since the epoch, also outside of a repository.
+            // Parsing them here would need the rules of the local time zone.
+            let parsed = Git::new(["-c"])
+                .arg(format!("drive.until={}", time))
+                .args(["config", "--type=expiry-date", "drive.until"])
+                .run()
+                .wrap_err_with(|| format!("Could not understand the time `{}`", time))?;
+            let expires = parsed
+                .trim()
+                .parse::<u64>()
+                .wrap_err_with(|| format!("Could not understand the time `{}`", time))?;
+
+            next_occurrence(time, expires, now())
+        }
+    }
+}
+
+/// A bare time of day that has already passed today means tomorrow,
+/// any other time in the past is an error.
+fn next_occurrence(time: &str, expires: u64, now: u64) -> Result<u64> {
+    if expires > now {
+        return Ok(expires);
+    }
+    if !is_time_of_day(time) {
+        bail!("The time `{}` is in the past.", time);
+    }
+
+    let days = (now - expires) / 86_400 + 1;
+    Ok(expires + days * 86_400)
+}
+
+/// Whether the time is only a time of day, e.g. `18:00`, `6:30:00`, or `6pm`.
+fn is_time_of_day(time: &str) -> bool {
+    let time = time.trim().to_ascii_lowercase();
+    let (time, meridiem) = match time.strip_suffix("am").or_else(|| time.strip_suffix("pm")) {
+        Some(time) => (time.trim_end(), true),
+        None => (time.as_str(), false),
+    };
+
+    let parts = time.split(':').collect::<Vec<_>>();
+    let numbers = parts
+        .iter()
+        .all(|p| (1..=2).contains(&p.len()) && p.chars().all(|c| c.is_ascii_digit()));
+    numbers
+        && match parts.len() {
+            1 => meridiem,
+            2 | 3 => true,
+            _ => false,
+        }
+}
+
+fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| d.as_secs())
+}
+
+const EMAIL_LABEL: &str = "drive.emailLabel";
+
+const NAVIGATOR_ORDER: &str = "drive.order";
+
 /// U+001F - Information Separator One
 const SEPARATOR: u8 = 0x1F_u8;
 
-fn drive_with<'a>(navigators: impl ExactSizeIterator<Item = &'a Navigator>) -> Result<()> {
-    let git_dir = git_dir()?;
+fn drive_with<'a>(
+    navigators: impl ExactSizeIterator<Item = &'a Navigator>,
+    session: &Session,
+) -> Result<()> {
+    let scope = active_scope(session.scope)?;
+    let session_dir = session_dir(scope)?;
+    // A global session applies to all repositories and only uses the global config.
+    let repo = match scope {
+        Scope::Global => None,
+        Scope::Worktree | Scope::Shared => Some(Repository::discover()?),
+    };
+    let repo = repo.as_ref();
+    let template = Template::load(repo)?;
+    let expires = session.expires.as_ref().map(expires_at).transpose()?;
+    let by_hook = match expires {
+        Some(_) => trailers_by_hook(repo)?,
+        None => false,
+    };
 
-    let (co_authored_lines, navigators): (Vec<_>, Vec<_>) = navigators
-        .map(|n| {
-            let co_authored_line = format!("Co-Authored-By: {} <{}>", n.name, n.email);
-            let navigator = n.alias.as_bytes().to_vec();
-            (co_authored_line, navigator)
-        })
-        .unzip();
+    let navigators = navigators.collect::<Vec<_>>();
 
-    let template_file = git_dir.join(concat!(env!("CARGO_PKG_NAME"), "_commit_template"));
-    write_template(&template_file, co_authored_lines.into_iter())
-        .wrap_err_with(|| format!("File: {}", template_file.display()))?;
+    let driver = match (
+        repo::config_value(repo, "user.name")?,
+        repo::config_value(repo, "user.email")?,
+    ) {
+        (Some(name), Some(email)) => Some(format!("{} <{}>", name, email)),
+        (name, _) => name,
+    };
+    let trailers = trailers(session, repo)?;
+    let trailer_lines = trailer_lines(&trailers, &navigators, driver.as_deref())?;
+    let values = Values {
+        co_authors: &trailer_lines,
+        driver,
+        branch: repo.map(Repository::branch).transpose()?.flatten(),
+    };
 
-    let navigators = navigators.join([SEPARATOR].as_ref());
-    let mut current_navigators_file = git_dir;
-    current_navigators_file.push(concat!(".", env!("CARGO_PKG_NAME"), "_current_navigators"));
+    std::fs::create_dir_all(&session_dir)
+        .wrap_err_with(|| format!("Directory: {}", session_dir.display()))?;
+    let template = template.render(&values)?;
+
+    let template_file = session_dir.join(template_file_name(scope));
+    if !by_hook {
+        write_data(&template_file, template.as_bytes())
+            .wrap_err_with(|| format!("File: {}", template_file.display()))?;
+    }
+
+    let expiry_file = session_dir.join(expiry_file_name(scope));
+    match expires {
+        Some(expires) => write_data(&expiry_file, expires.to_string().as_bytes())
+            .wrap_err_with(|| format!("File: {}", expiry_file.display()))?,
+        None => remove_file(&expiry_file)?,
+    }
+
+    record_history(scope, &navigators)?;
+
+    let navigators = join_aliases(&navigators);
+    let current_navigators_file = session_dir.join(navigators_file_name(scope));
     write_data(&current_navigators_file, &navigators)
         .wrap_err_with(|| format!("File: {}", current_navigators_file.display()))?;
-    println!(
-        "git-commit set template to {}.",
-        style(template_file.display()).cyan(),
-    );
+    if by_hook {
+        println!("The commit-msg hook adds the trailers until the drive expires.");
+    } else {
+        println!(
+            "git-commit set template to {}.",
+            style(template_file.display()).cyan(),
+        );
+    }
 
     let prog = (|| {
         let prog = std::env::args().next()?;
@@ -198,31 +737,209 @@ fn drive_with<'a>(navigators: impl ExactSizeIterator<Item = &'a Navigator>) -> R
         style("alone").yellow(),
     );
 
-    let sc = Proc::new("git")
-        .args(&["config", "commit.template"])
-        .arg(template_file)
-        .spawn()?
-        .wait()?;
+    if scope == Scope::Worktree {
+        enable_worktree_config()?;
+    }
+
+    let mut git_config = git_config(scope)?;
+    if by_hook {
+        // git would keep using the template after the drive expired,
+        // while the hook checks the expiry on every commit
+        if git_config.unset("commit.template")? {
+            save(&git_config)?;
+        }
+    } else {
+        let template_file = template_file
+            .to_str()
+            .ok_or_else(|| eyre!("The path {} is not valid UTF-8", template_file.display()))?;
+        git_config.set("commit.template", template_file)?;
+        save(&git_config)?;
+    }
+
+    // A session for the current working tree would hide the shared one.
+    if scope == Scope::Shared && navigators_file(Scope::Worktree)?.exists() {
+        end_session(Scope::Worktree)?;
+    }
+
+    Ok(())
+}
 
-    if !sc.success() {
-        std::process::exit(sc.code().unwrap_or_default())
+/// Whether the `commit-msg` hook adds the trailers of a drive that expires instead of
+/// `commit.template`, which git keeps using until the next `git drive` ends the session.
+/// A global drive cannot know whether all repositories have the hook, so it uses the template.
+fn trailers_by_hook(repo: Option<&Repository>) -> Result<bool> {
+    let installed = match repo {
+        Some(repo) => hook::adds_trailers(repo)?,
+        None => false,
+    };
+    if !installed {
+        eprintln!(
+            "git keeps adding the trailers after the drive expires, until the next {} ends it.\nInstall the commit-msg hook with {} to stop them in time.",
+            style("git drive").yellow(),
+            style("git drive hook install commit-msg --fix").yellow()
+        );
     }
+    Ok(installed)
+}
 
+/// Makes git read the config of the working tree, which holds its session.
+fn enable_worktree_config() -> Result<()> {
+    let mut git_config = git_config(Scope::Shared)?;
+    if git_config.get("extensions.worktreeConfig")? != Some("true") {
+        git_config.set("extensions.worktreeConfig", "true")?;
+        save(&git_config)?;
+    }
     Ok(())
 }
 
-fn write_template(file: &Path, data: impl Iterator<Item = String>) -> Result<()> {
+fn join_aliases(navigators: &[&Navigator]) -> Vec<u8> {
+    navigators
+        .iter()
+        .map(|n| n.alias.as_bytes())
+        .collect::<Vec<_>>()
+        .join([SEPARATOR].as_ref())
+}
+
+/// A drive session from the history, without navigators if it was driving alone.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HistoryEntry {
+    /// Seconds since the unix epoch.
+    pub started: u64,
+    pub navigators: Vec<Id>,
+}
+
+/// Appends the start of a session to the history, one line per session,
+/// with the start time followed by the aliases of the navigators.
+fn record_history(scope: Scope, navigators: &[&Navigator]) -> Result<()> {
     use std::io::Write;
 
-    let mut f = File::create(file)?;
-    writeln!(f)?;
-    writeln!(f)?;
-    for line in data {
-        writeln!(f, "{}", line)?;
+    let mut line = format!("{} ", now()).into_bytes();
+    line.extend(join_aliases(navigators));
+    line.push(b'\n');
+
+    let history_file = history_file(scope)?;
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&history_file)
+        .and_then(|mut f| f.write_all(&line))
+        .wrap_err_with(|| format!("File: {}", history_file.display()))
+}
+
+/// The history of the requested scope or, if none was requested, of the most specific
+/// of the current working tree, the repository, and the global session that has one.
+pub fn session_history(scope: Option<Scope>) -> Result<Vec<HistoryEntry>> {
+    let scope = match scope {
+        Some(scope) => scope,
+        None if history_file(Scope::Worktree)?.exists() => Scope::Worktree,
+        None if history_file(Scope::Shared)?.exists() => Scope::Shared,
+        None => Scope::Global,
+    };
+    let history_file = history_file(scope)?;
+    if !history_file.exists() {
+        return Ok(Vec::new());
     }
 
-    f.flush()?;
-    Ok(())
+    read_data(&history_file)
+        .and_then(|data| parse_history(&data))
+        .wrap_err_with(|| format!("File: {}", history_file.display()))
+}
+
+fn parse_history(data: &[u8]) -> Result<Vec<HistoryEntry>> {
+    data.split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| -> Result<HistoryEntry> {
+            let line = std::str::from_utf8(line)?;
+            let (started, navigators) = line.split_once(' ').unwrap_or((line, ""));
+            let navigators = navigators
+                .split(char::from(SEPARATOR))
+                .filter(|alias| !alias.is_empty())
+                .map(Id::from)
+                .collect();
+            Ok(HistoryEntry {
+                started: started.parse()?,
+                navigators,
+            })
+        })
+        .collect()
+}
+
+/// How often and when a navigator of the roster was last driven with, across all sessions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Usage {
+    alias: Id,
+    count: u64,
+    /// Seconds since the unix epoch.
+    last_used: u64,
+}
+
+fn read_usage() -> Result<Vec<Usage>> {
+    let usage_file = usage_file()?;
+    if !usage_file.exists() {
+        return Ok(Vec::new());
+    }
+
+    read_data(&usage_file)
+        .and_then(|data| parse_usage(&data))
+        .wrap_err_with(|| format!("File: {}", usage_file.display()))
+}
+
+/// Counts another drive with the navigators.
+fn record_usage(aliases: &[&str]) -> Result<()> {
+    if aliases.is_empty() {
+        return Ok(());
+    }
+
+    let mut usage = read_usage()?;
+    count_usage(&mut usage, aliases, now());
+
+    let usage_file = usage_file()?;
+    write_data(&usage_file, &serialize_usage(&usage))
+        .wrap_err_with(|| format!("File: {}", usage_file.display()))
+}
+
+fn count_usage(usage: &mut Vec<Usage>, aliases: &[&str], now: u64) {
+    for &alias in aliases {
+        match usage.iter_mut().find(|u| &*u.alias == alias) {
+            Some(used) => {
+                used.count += 1;
+                used.last_used = now;
+            }
+            None => usage.push(Usage {
+                alias: Id::from(alias),
+                count: 1,
+                last_used: now,
+            }),
+        }
+    }
+}
+
+/// One line per navigator, with the time of the last use and the count followed by the alias.
+fn parse_usage(data: &[u8]) -> Result<Vec<Usage>> {
+    data.split(|b| *b == b'\n')
+        .filter(|line| !line.is_empty())
+        .map(|line| -> Result<Usage> {
+            let line = std::str::from_utf8(line)?;
+            let mut parts = line.splitn(3, ' ');
+            let (last_used, count, alias) = match (parts.next(), parts.next(), parts.next()) {
+                (Some(last_used), Some(count), Some(alias)) => (last_used, count, alias),
+                _ => bail!("Expected `$last_used $count $alias`, but got `{}`.", line),
+            };
+            Ok(Usage {
+                alias: Id::from(alias),
+                count: count.parse()?,
+                last_used: last_used.parse()?,
+            })
+        })
+        .collect()
+}
+
+fn serialize_usage(usage: &[Usage]) -> Vec<u8> {
+    usage
+        .iter()
+        .map(|u| format!("{} {} {}\n", u.last_used, u.count, &*u.alias))
+        .collect::<String>()
+        .into_bytes()
 }
 
 fn write_data(file: &Path, data: &[u8]) -> Result<()> {
@@ -233,8 +950,8 @@ fn write_data(file: &Path, data: &[u8]) -> Result<()> {
     Ok(())
 }
 
-fn current_fallible(color: &str) -> Result<bool> {
-    let ids = get_current()?;
+fn current_fallible(color: &str, scope: Option<Scope>) -> Result<bool> {
+    let ids = get_current(scope)?;
     let style = Style::from_dotted_str(color);
     let has_current = !ids.is_empty();
     let s = ids
@@ -242,13 +959,34 @@ fn current_fallible(color: &str) -> Result<bool> {
         .map(|id| format!("{} ", style.apply_to(&*id)))
         .collect::<String>();
 
-    println!("{}", s.trim_end());
+    let expires = match read_expiry(active_scope(scope)?)? {
+        Some(expires) if has_current => format!(
+            " (expires in {})",
+            format_remaining(expires.saturating_sub(now()))
+        ),
+        _ => String::new(),
+    };
+
+    println!("{}{}", s.trim_end(), expires);
     Ok(has_current)
 }
 
-fn get_current() -> Result<Vec<Id>> {
-    let mut current_navigators_file = git_dir()?;
-    current_navigators_file.push(concat!(".", env!("CARGO_PKG_NAME"), "_current_navigators"));
+/// The navigators of the running session, empty if there is none.
+pub fn session_navigators(scope: Option<Scope>) -> Result<Vec<Id>> {
+    if navigators_file(active_scope(scope)?)?.exists() {
+        get_current(scope)
+    } else {
+        Ok(Vec::new())
+    }
+}
+
+/// The navigators of the session, empty if it has expired.
+fn get_current(scope: Option<Scope>) -> Result<Vec<Id>> {
+    let scope = active_scope(scope)?;
+    if is_expired(scope)? {
+        return Ok(Vec::new());
+    }
+    let current_navigators_file = navigators_file(scope)?;
 
     let data = read_data(&current_navigators_file)
         .wrap_err_with(|| format!("File: {}", current_navigators_file.display()))?;
@@ -262,28 +1000,165 @@ fn get_current() -> Result<Vec<Id>> {
     Ok(ids)
 }
 
-fn git_dir() -> Result<PathBuf> {
-    let git_dir = Proc::new("git")
-        .args(&["rev-parse", "--absolute-git-dir"])
-        .output()?;
-    if !git_dir.status.success() {
-        return Err(eyre!(
-            concat!(
-                "Could not get current git dir\n",
-                "Stderr: {}\n",
-                "\n",
-                "Try calling ",
-                env!("CARGO_PKG_NAME"),
-                " from a working directory of a git repository."
+/// The trailers given for this invocation, or else the ones configured in `drive.trailer`,
+/// where the repository config overrides the global one.
+pub fn trailers(session: &Session, repo: Option<&Repository>) -> Result<Vec<Trailer>> {
+    if !session.trailers.is_empty() {
+        return Ok(session.trailers.clone());
+    }
+
+    let configured = repo::config_values(repo, "drive.trailer")?;
+    if configured.is_empty() {
+        return Ok(vec![Trailer::co_authored_by()]);
+    }
+
+    configured
+        .iter()
+        .map(|trailer| {
+            trailer
+                .parse()
+                .map_err(|e: String| eyre!(e).wrap_err("Invalid trailer in `drive.trailer`"))
+        })
+        .collect()
+}
+
+/// The trailers that are added for every navigator, leaving out the ones for the driver.
+pub fn navigator_trailers(session: &Session, repo: &Repository) -> Result<Vec<Trailer>> {
+    Ok(trailers(session, Some(repo))?
+        .into_iter()
+        .filter(|t| t.target == TrailerTarget::Navigators)
+        .collect())
+}
+
+pub fn trailer_lines<N: Borrow<Navigator>>(
+    trailers: &[Trailer],
+    navigators: &[N],
+    driver: Option<&str>,
+) -> Result<Vec<String>> {
+    let mut lines = Vec::with_capacity(trailers.len() * navigators.len());
+    for trailer in trailers {
+        match trailer.target {
+            TrailerTarget::Navigators => lines.extend(
+                navigators
+                    .iter()
+                    .map(Borrow::borrow)
+                    .map(|n: &Navigator| format!("{}: {} <{}>", trailer.key, n.name, n.email)),
             ),
-            String::from_utf8_lossy(&git_dir.stdout[..])
-        ));
+            TrailerTarget::Driver => {
+                let driver = driver.ok_or_else(|| {
+                    eyre!(
+                        "The trailer `{}` needs the driver, but `user.name` or `user.email` are not set.",
+                        trailer.key
+                    )
+                })?;
+                lines.push(format!("{}: {}", trailer.key, driver));
+            }
+        }
+    }
+    Ok(lines)
+}
+
+/// Returns the requested scope, or, if none was requested, the scope of the running session.
+/// A session for the current working tree takes precedence over a shared one,
+/// which takes precedence over a global one.
+/// Outside of a repository, the global one must be requested explicitly.
+fn active_scope(requested: Option<Scope>) -> Result<Scope> {
+    if let Some(scope) = requested {
+        return Ok(scope);
+    }
+    if Repository::discover().is_err() {
+        bail!(
+            "Not in a git repository, pass {} to use the drive session of all repositories.",
+            style("--global").yellow()
+        );
+    }
+
+    for scope in [Scope::Worktree, Scope::Shared, Scope::Global] {
+        if navigators_file(scope)?.exists() {
+            return Ok(scope);
+        }
+    }
+    Ok(Scope::Shared)
+}
+
+const fn navigators_file_name(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Worktree => concat!(".", env!("CARGO_PKG_NAME"), "_worktree_navigators"),
+        Scope::Shared => concat!(".", env!("CARGO_PKG_NAME"), "_current_navigators"),
+        Scope::Global => concat!(".", env!("CARGO_PKG_NAME"), "_global_navigators"),
+    }
+}
+
+const fn history_file_name(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Worktree => concat!(".", env!("CARGO_PKG_NAME"), "_worktree_history"),
+        Scope::Shared => concat!(".", env!("CARGO_PKG_NAME"), "_history"),
+        Scope::Global => concat!(".", env!("CARGO_PKG_NAME"), "_global_history"),
+    }
+}
+
+const fn expiry_file_name(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Worktree => concat!(".", env!("CARGO_PKG_NAME"), "_worktree_expiry"),
+        Scope::Shared => concat!(".", env!("CARGO_PKG_NAME"), "_current_expiry"),
+        Scope::Global => concat!(".", env!("CARGO_PKG_NAME"), "_global_expiry"),
+    }
+}
+
+const fn template_file_name(scope: Scope) -> &'static str {
+    match scope {
+        Scope::Worktree => concat!(env!("CARGO_PKG_NAME"), "_worktree_commit_template"),
+        Scope::Shared => concat!(env!("CARGO_PKG_NAME"), "_commit_template"),
+        Scope::Global => concat!(env!("CARGO_PKG_NAME"), "_global_commit_template"),
     }
+}
+
+/// The usage is kept next to the config file, as it is about the roster, not a session.
+fn usage_file() -> Result<PathBuf> {
+    Ok(config::config_dir()?.join(concat!(".", env!("CARGO_PKG_NAME"), "_usage")))
+}
 
-    let git_dir = git_dir.stdout;
-    let git_dir = String::from_utf8(git_dir)?;
-    let git_dir = PathBuf::from(git_dir.trim());
-    Ok(git_dir)
+fn navigators_file(scope: Scope) -> Result<PathBuf> {
+    Ok(session_dir(scope)?.join(navigators_file_name(scope)))
+}
+
+fn expiry_file(scope: Scope) -> Result<PathBuf> {
+    Ok(session_dir(scope)?.join(expiry_file_name(scope)))
+}
+
+fn history_file(scope: Scope) -> Result<PathBuf> {
+    Ok(session_dir(scope)?.join(history_file_name(scope)))
+}
+
+/// Worktree sessions live in the git dir of the working tree,
+/// shared sessions in the common git dir of the repository,
+/// and global sessions next to the config file.
+fn session_dir(scope: Scope) -> Result<PathBuf> {
+    match scope {
+        Scope::Worktree => Ok(Repository::discover()?.git_dir().to_path_buf()),
+        Scope::Shared => Ok(Repository::discover()?.common_dir().to_path_buf()),
+        Scope::Global => config::config_dir(),
+    }
+}
+
+/// The config file that is changed by `git config --worktree`, `git config --local`,
+/// or `git config --global`.
+fn git_config(scope: Scope) -> Result<ConfigFile> {
+    let file = match scope {
+        Scope::Worktree => Repository::discover()?.worktree_config_file(),
+        Scope::Shared => Repository::discover()?.config_file(),
+        Scope::Global => repo::global_config_file()?,
+    };
+    ConfigFile::open(file)
+}
+
+fn save(git_config: &ConfigFile) -> Result<()> {
+    git_config.save().wrap_err_with(|| {
+        format!(
+            "Could not update the git config in {}",
+            git_config.path().display()
+        )
+    })
 }
 
 fn read_data(file: &Path) -> Result<Vec<u8>> {
@@ -294,3 +1169,340 @@ fn read_data(file: &Path) -> Result<Vec<u8>> {
     data.truncate(read);
     Ok(data)
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{
+            tests::{drv1, nav1, nav2},
+            KeyFormat,
+        },
+        ui::util::disable_colors,
+    };
+
+    #[test]
+    fn trailer_lines_for_navigators_and_driver() {
+        let trailers = [
+            "Co-authored-by".parse().unwrap(),
+            "Signed-off-by=driver".parse().unwrap(),
+        ];
+        let (nav1, nav2) = (nav1(), nav2());
+
+        let lines = trailer_lines(&trailers, &[&nav1, &nav2], Some("ralle <qux@bar.org>")).unwrap();
+        assert_eq!(
+            lines,
+            [
+                "Co-authored-by: bernd <foo@bar.org>",
+                "Co-authored-by: ronny <baz@bar.org>",
+                "Signed-off-by: ralle <qux@bar.org>",
+            ]
+        );
+    }
+
+    #[test]
+    fn match_guests_outside_of_the_roster() {
+        let config = Config::from_iter([nav1()]);
+
+        let guest =
+            match_navigator(&Id::from("Jane Doe <jane@example.com>"), &config, None).unwrap();
+        assert_eq!(&*guest.alias, "Jane Doe <jane@example.com>");
+        assert_eq!(guest.name, "Jane Doe");
+        assert_eq!(guest.email, "jane@example.com");
+
+        let known = match_navigator(&Id::from("nav1"), &config, None).unwrap();
+        assert_eq!(*known, nav1());
+
+        assert!(match_navigator(&Id::from("Jane Doe"), &config, None).is_err());
+        assert!(match_navigator(&Id::from("Jane Doe <>"), &config, None).is_err());
+    }
+
+    #[test]
+    fn match_navigators_with_other_emails() {
+        let _guard = disable_colors();
+        let nav = Navigator {
+            emails: vec!["oss=bernd@example.org".parse().unwrap()],
+            ..nav1()
+        };
+        let config = Config::from_iter([nav, nav2()]);
+
+        let oss = match_navigator(&Id::from("nav1:oss"), &config, None).unwrap();
+        assert_eq!(&*oss.alias, "nav1:oss");
+        assert_eq!(oss.email, "bernd@example.org");
+
+        let by_label = match_navigator(&Id::from("nav1"), &config, Some("oss")).unwrap();
+        assert_eq!(&*by_label.alias, "nav1");
+        assert_eq!(by_label.email, "bernd@example.org");
+
+        let without_label = match_navigator(&Id::from("nav2"), &config, Some("oss")).unwrap();
+        assert_eq!(*without_label, nav2());
+
+        let err = match_navigator(&Id::from("nav1:home"), &config, None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The navigator nav1 has no email with the label `home`."
+        );
+    }
+
+    #[test]
+    fn match_navigators_by_secondary_alias() {
+        let nav = Navigator {
+            aliases: vec![Id::from("bernie")],
+            emails: vec!["oss=bernd@example.org".parse().unwrap()],
+            ..nav1()
+        };
+        let config = Config::from_iter([nav.clone(), nav2()]);
+
+        let found = match_navigator(&Id::from("bernie"), &config, None).unwrap();
+        assert_eq!(*found, nav);
+
+        let oss = match_navigator(&Id::from("bernie:oss"), &config, None).unwrap();
+        assert_eq!(&*oss.alias, "nav1:oss");
+        assert_eq!(oss.email, "bernd@example.org");
+    }
+
+    #[test]
+    fn archived_navigators_are_matched_but_not_driven_with() {
+        let _guard = disable_colors();
+        let archived = Navigator {
+            archived: true,
+            emails: vec!["oss=bernd@example.org".parse().unwrap()],
+            ..nav1()
+        };
+        let config = Config::from_iter([archived, nav2()]);
+
+        let navigators = [Id::from("nav1"), Id::from("nav1:oss")]
+            .iter()
+            .map(|id| match_navigator(id, &config, None))
+            .collect::<Result<Vec<_>>>()
+            .unwrap();
+        assert!(navigators.iter().all(|n| n.archived));
+
+        for nav in navigators {
+            let err = reject_archived(&[nav], &config).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                "The navigator nav1 is archived, unarchive it with `git drive unarchive nav1`."
+            );
+        }
+
+        let active = match_navigator(&Id::from("nav2"), &config, None).unwrap();
+        assert!(reject_archived(&[active], &config).is_ok());
+    }
+
+    #[test]
+    fn count_and_parse_usage() {
+        let mut usage = Vec::new();
+        count_usage(&mut usage, &["nav1", "nav2"], 100);
+        count_usage(&mut usage, &["nav2"], 200);
+
+        let data = serialize_usage(&usage);
+        assert_eq!(data, b"100 1 nav1\n200 2 nav2\n");
+        assert_eq!(parse_usage(&data).unwrap(), usage);
+
+        let err = parse_usage(b"100 nav1\n").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Expected `$last_used $count $alias`, but got `100 nav1`."
+        );
+    }
+
+    #[test]
+    fn sort_navigators_by_usage_or_alias() {
+        let aliases = |navigators: &[Navigator]| {
+            navigators
+                .iter()
+                .map(|n| n.alias.0.clone())
+                .collect::<Vec<_>>()
+        };
+        let drv = drv1(None).navigator;
+        let roster = [nav1(), nav2(), drv];
+        let usage = [
+            Usage {
+                alias: Id::from("nav2"),
+                count: 1,
+                last_used: 200,
+            },
+            Usage {
+                alias: Id::from("drv1"),
+                count: 5,
+                last_used: 100,
+            },
+        ];
+
+        let mut navigators = roster.clone();
+        sort_navigators(&mut navigators, NavigatorOrder::Recent, &usage);
+        assert_eq!(aliases(&navigators), ["nav2", "drv1", "nav1"]);
+
+        let mut navigators = roster.clone();
+        sort_navigators(&mut navigators, NavigatorOrder::Frequent, &usage);
+        assert_eq!(aliases(&navigators), ["drv1", "nav2", "nav1"]);
+
+        let mut navigators = roster.clone();
+        sort_navigators(&mut navigators, NavigatorOrder::Alpha, &usage);
+        assert_eq!(aliases(&navigators), ["drv1", "nav1", "nav2"]);
+
+        let mut navigators = roster;
+        sort_navigators(&mut navigators, NavigatorOrder::ConfigOrder, &usage);
+        assert_eq!(aliases(&navigators), ["nav1", "nav2", "drv1"]);
+    }
+
+    #[test]
+    fn usage_is_recorded_for_the_roster_alias() {
+        let nav = Navigator {
+            emails: vec!["oss=bernd@example.org".parse().unwrap()],
+            ..nav1()
+        };
+        let config = Config::from_iter([nav]);
+
+        let labeled = match_navigator(&Id::from("nav1:oss"), &config, None).unwrap();
+        assert_eq!(roster_alias(&labeled, &config), Some("nav1"));
+
+        let guest = match_navigator(&Id::from("Jane <jane@example.com>"), &config, None).unwrap();
+        assert_eq!(roster_alias(&guest, &config), None);
+    }
+
+    #[test]
+    fn guest_alias_is_the_local_part_of_the_email() {
+        assert_eq!(guest_alias("Jane.Doe@example.com"), Id::from("jane.doe"));
+        assert_eq!(guest_alias("jane"), Id::from("jane"));
+    }
+
+    #[test]
+    fn set_driver_with_ssh_key() {
+        let mut git_config = ConfigFile::open("does-not-exist/config").unwrap();
+        let driver = Driver {
+            key_format: KeyFormat::Ssh,
+            ..drv1("~/.ssh/id_ed25519.pub")
+        };
+        set_driver(&mut git_config, &driver, true).unwrap();
+
+        assert_eq!(git_config.get("user.name").unwrap(), Some("ralle"));
+        assert_eq!(git_config.get("user.email").unwrap(), Some("qux@bar.org"));
+        assert_eq!(
+            git_config.get("user.signingkey").unwrap(),
+            Some("~/.ssh/id_ed25519.pub")
+        );
+        assert_eq!(git_config.get("gpg.format").unwrap(), Some("ssh"));
+        assert_eq!(git_config.get("commit.gpgsign").unwrap(), Some("true"));
+    }
+
+    #[test]
+    fn set_driver_without_key_unsets_signing() {
+        let mut git_config = ConfigFile::open("does-not-exist/config").unwrap();
+        set_driver(&mut git_config, &drv1("my-key"), true).unwrap();
+        set_driver(&mut git_config, &drv1(None), false).unwrap();
+
+        assert_eq!(git_config.get("user.name").unwrap(), Some("ralle"));
+        assert_eq!(git_config.get("user.signingkey").unwrap(), None);
+        assert_eq!(git_config.get("gpg.format").unwrap(), None);
+        assert_eq!(git_config.get("commit.gpgsign").unwrap(), None);
+    }
+
+    #[test]
+    fn rename_in_navigators_and_history() {
+        let navigators = b"nav1\x1Fnav2";
+        assert_eq!(
+            rename_alias(navigators, "nav2", "ronny").unwrap(),
+            b"nav1\x1Fronny"
+        );
+        assert_eq!(rename_alias(navigators, "nav", "ronny"), None);
+
+        let navigators = b"nav1:oss\x1Fnav2";
+        assert_eq!(
+            rename_alias(navigators, "nav1", "bernd").unwrap(),
+            b"bernd:oss\x1Fnav2"
+        );
+
+        let history = b"100 nav1\x1Fnav2\n200 \n300 nav2:oss\n";
+        assert_eq!(
+            rename_in_history(history, "nav2", "ronny").unwrap(),
+            b"100 nav1\x1Fronny\n200 \n300 ronny:oss\n"
+        );
+    }
+
+    #[test]
+    fn parse_session_history() {
+        let history =
+            parse_history(b"1700000000 nav1\x1fnav2\n1700000100 \n1700000200 nav2\n").unwrap();
+        assert_eq!(
+            history,
+            [
+                HistoryEntry {
+                    started: 1_700_000_000,
+                    navigators: vec![Id::from("nav1"), Id::from("nav2")],
+                },
+                HistoryEntry {
+                    started: 1_700_000_100,
+                    navigators: Vec::new(),
+                },
+                HistoryEntry {
+                    started: 1_700_000_200,
+                    navigators: vec![Id::from("nav2")],
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn time_of_day_rolls_over_to_tomorrow() {
+        let now = 1_700_000_000;
+        assert_eq!(next_occurrence("18:00", now + 60, now).unwrap(), now + 60);
+        assert_eq!(
+            next_occurrence("18:00", now - 60, now).unwrap(),
+            now - 60 + 86_400
+        );
+        assert_eq!(next_occurrence("6pm", now, now).unwrap(), now + 86_400);
+        assert_eq!(
+            next_occurrence("6:30:00 am", now - 60, now).unwrap(),
+            now - 60 + 86_400
+        );
+    }
+
+    #[test]
+    fn absolute_time_in_the_past_is_an_error() {
+        let now = 1_700_000_000;
+        for time in ["2023-11-13 18:00", "yesterday 18:00", "6"] {
+            let err = next_occurrence(time, now - 60, now).unwrap_err();
+            assert_eq!(
+                err.to_string(),
+                format!("The time `{}` is in the past.", time)
+            );
+        }
+        assert_eq!(
+            next_occurrence("tomorrow 9:00", now + 60, now).unwrap(),
+            now + 60
+        );
+    }
+
+    #[test]
+    fn expiry_is_parsed_by_git() {
+        let expires = expires_at(&Expiry::At(String::from("18:00"))).unwrap();
+        assert!(expires > now() && expires <= now() + 86_400);
+
+        let err = expires_at(&Expiry::At(String::from("2001-01-01 12:00"))).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The time `2001-01-01 12:00` is in the past."
+        );
+    }
+
+    #[test]
+    fn format_remaining_time() {
+        assert_eq!(format_remaining(30), "30s");
+        assert_eq!(format_remaining(45 * 60 + 10), "45m");
+        assert_eq!(format_remaining(2 * 3_600), "2h");
+        assert_eq!(format_remaining(3_600 + 30 * 60), "1h30m");
+    }
+
+    #[test]
+    fn driver_trailer_requires_a_driver() {
+        let trailers = ["Signed-off-by=driver".parse().unwrap()];
+
+        let err = trailer_lines(&trailers, &[&nav1()], None).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The trailer `Signed-off-by` needs the driver, but `user.name` or `user.email` are not set."
+        );
+    }
+}
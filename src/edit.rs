@@ -76,7 +76,7 @@ mod tests {
     use crate::{
         data::{
             tests::{drv1, nav1, nav2},
-            Driver, Field, Id, Navigator,
+            Driver, Email, Field, Id, KeyFormat, Navigator,
         },
         ui::{
             util::{disable_colors, prompt_text, select_one, AssertPromptText, Initial, NoUi},
@@ -107,10 +107,14 @@ mod tests {
                 &[
                     Selectable {
                         item: "nav1",
+                        name: "bernd",
+                        email: "foo@bar.org",
                         checked: false
                     },
                     Selectable {
                         item: "nav2",
+                        name: "ronny",
+                        email: "baz@bar.org",
                         checked: false
                     }
                 ]
@@ -130,6 +134,8 @@ mod tests {
                 items,
                 &[Selectable {
                     item: "drv1",
+                    name: "ralle",
+                    email: "qux@bar.org",
                     checked: false
                 }]
             );
@@ -157,6 +163,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value("foo@bar.org")
             .returns("new email")
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .done();
 
         let mut config = Config::from_iter([nav1().ent(), nav2().ent(), drv1(None).ent()]);
@@ -175,8 +184,11 @@ mod tests {
             &[
                 Navigator {
                     alias: Id(String::from("nav1")),
+                    aliases: Vec::new(),
                     name: String::from("new name"),
                     email: String::from("new email"),
+                    emails: Vec::new(),
+                    archived: false,
                 },
                 nav2()
             ]
@@ -231,6 +243,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value(partial.email.as_deref().unwrap())
             .returns(Initial)
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .done();
 
         let mut config = Config::from_iter([nav1()]);
@@ -242,12 +257,104 @@ mod tests {
             config,
             Config::from_iter([Navigator {
                 alias: Id::from("nav1"),
+                aliases: Vec::new(),
                 name: partial.name.unwrap(),
-                email: partial.email.unwrap()
+                email: partial.email.unwrap(),
+                emails: Vec::new(),
+                archived: false,
             }])
         );
     }
 
+    #[test]
+    fn test_edit_other_emails_of_existing_navigator() {
+        let mut text = AssertPromptText::start("nav1")
+            .expect(Field::Name)
+            .with_initial_value("bernd")
+            .returns(Initial)
+            .expect(Field::Email)
+            .with_initial_value("foo@bar.org")
+            .returns("oss")
+            .expect(Field::Emails)
+            .with_initial_value("work=foo@bar.org")
+            .returns("work=foo@bar.org, oss=bernd@example.org")
+            .done();
+
+        let nav = Navigator {
+            emails: vec![Email {
+                label: String::from("work"),
+                address: String::from("foo@bar.org"),
+            }],
+            ..nav1()
+        };
+        let mut config = Config::from_iter([nav]);
+        let modified = edit(
+            text.as_ui(),
+            Kind::Navigator,
+            &mut config,
+            PartialIdNav::new("nav1"),
+        )
+        .unwrap();
+
+        text.expect_done();
+        assert_eq!(modified, Modification::Changed);
+        let nav = &config.navigators[0];
+        assert_eq!(nav.email, "bernd@example.org");
+        assert_eq!(nav.email_for("work"), Some("foo@bar.org"));
+        assert_eq!(nav.email_for("oss"), Some("bernd@example.org"));
+    }
+
+    #[test]
+    fn test_edit_existing_navigator_with_invalid_emails() {
+        let text = prompt_text(|_field, _id, initial| Ok(initial.unwrap()));
+        let partial = PartialIdNav::new("nav1").with_emails("foo@bar.org");
+
+        let mut config = Config::from_iter([nav1()]);
+        let err = edit(text, Kind::Navigator, &mut config, partial).unwrap_err();
+
+        assert_eq!(
+            err.to_string(),
+            "Expected `$label=$email`, but got `foo@bar.org`."
+        );
+        assert_eq!(config, Config::from_iter([nav1()]));
+    }
+
+    #[test]
+    fn test_edit_navigator_by_secondary_alias() {
+        let text = prompt_text(|_field, _id, initial| Ok(initial.unwrap()));
+        let nav = Navigator {
+            aliases: vec![Id::from("bernie")],
+            ..nav1()
+        };
+        let partial = PartialIdNav::new("bernie").with_name("Bernd");
+
+        let mut config = Config::from_iter([nav.clone()]);
+        let modified = edit(text, Kind::Navigator, &mut config, partial).unwrap();
+
+        assert_eq!(modified, Modification::Changed);
+        assert_eq!(
+            &config.navigators,
+            &[Navigator {
+                name: String::from("Bernd"),
+                ..nav
+            }]
+        );
+    }
+
+    #[test]
+    fn test_edit_navigator_with_secondary_alias_of_another() {
+        let _guard = disable_colors();
+        let text = prompt_text(|_field, _id, initial| Ok(initial.unwrap()));
+        let partial = PartialIdNav::new("nav1")
+            .merge(PartialNav::default().with_aliases(String::from("bernie, nav2")));
+
+        let mut config = Config::from_iter([nav1(), nav2()]);
+        let err = edit(text, Kind::Navigator, &mut config, partial).unwrap_err();
+
+        assert_eq!(err.to_string(), "Alias nav2 already exists.");
+        assert_eq!(config, Config::from_iter([nav1(), nav2()]));
+    }
+
     #[test]
     fn test_edit_existing_driver() {
         let mut text = AssertPromptText::start("drv1")
@@ -257,6 +364,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value("qux@bar.org")
             .returns("new email")
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .expect(Field::Key)
             .with_initial_value("initial key")
             .returns("new key")
@@ -279,10 +389,14 @@ mod tests {
             &[Driver {
                 navigator: Navigator {
                     alias: Id(String::from("drv1")),
+                    aliases: Vec::new(),
                     name: String::from("new name"),
                     email: String::from("new email"),
+                    emails: Vec::new(),
+                    archived: false,
                 },
-                key: Some(String::from("new key"))
+                key: Some(String::from("new key")),
+                key_format: KeyFormat::OpenPgp,
             }]
         );
     }
@@ -329,6 +443,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value(partial.email.as_deref().unwrap())
             .returns(Initial)
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .expect(Field::Key)
             .with_initial_value(partial.key.as_deref().unwrap())
             .returns(Initial)
@@ -344,11 +461,32 @@ mod tests {
             Config::from_iter([Driver {
                 navigator: Navigator {
                     alias: Id::from("drv1"),
+                    aliases: Vec::new(),
                     name: partial.name.unwrap(),
-                    email: partial.email.unwrap()
+                    email: partial.email.unwrap(),
+                    emails: Vec::new(),
+                    archived: false,
                 },
-                key: partial.key
+                key: partial.key,
+                key_format: KeyFormat::OpenPgp,
             }])
         );
     }
+
+    #[test]
+    fn test_edit_key_format_of_existing_driver() {
+        let text = prompt_text(|_field, _id, initial| Ok(initial.unwrap()));
+        let mut partial = PartialIdNav::new("drv1").with_key("key::ssh-ed25519 AAAAC3Nza");
+        partial.key_format = Some(KeyFormat::Ssh);
+
+        let mut config = Config::from_iter([drv1("key")]);
+        let modified = edit(text, Kind::Driver, &mut config, partial).unwrap();
+
+        assert_eq!(modified, Modification::Changed);
+        assert_eq!(
+            config.drivers[0].key.as_deref(),
+            Some("key::ssh-ed25519 AAAAC3Nza")
+        );
+        assert_eq!(config.drivers[0].key_format, KeyFormat::Ssh);
+    }
 }
@@ -0,0 +1,242 @@
+use crate::{
+    config::Config,
+    data::{Hook, InstallHook, Modification, RunHook, Session},
+    drive, message,
+    repo::Repository,
+};
+use console::style;
+use eyre::{bail, Result, WrapErr};
+use std::path::Path;
+
+/// Marks the hooks that we installed and are allowed to replace.
+const MARKER: &str = concat!("# Installed by ", env!("CARGO_PKG_NAME"));
+
+pub fn install(install: &InstallHook) -> Result<Modification> {
+    let repo = Repository::discover()?;
+    let hooks_dir = repo.hooks_dir()?;
+    let hook_file = hooks_dir.join(install.hook.to_string());
+
+    if !install.force {
+        if let Ok(existing) = std::fs::read_to_string(&hook_file) {
+            if !existing.contains(MARKER) {
+                bail!(
+                    "There already is a {} hook at {}, use {} to replace it.",
+                    install.hook,
+                    style(hook_file.display()).cyan(),
+                    style("--force").yellow()
+                );
+            }
+        }
+    }
+
+    std::fs::create_dir_all(&hooks_dir)
+        .wrap_err_with(|| format!("Directory: {}", hooks_dir.display()))?;
+    std::fs::write(&hook_file, script(install))
+        .wrap_err_with(|| format!("File: {}", hook_file.display()))?;
+    make_executable(&hook_file)?;
+
+    println!(
+        "Installed the {} hook to {}.",
+        install.hook,
+        style(hook_file.display()).cyan()
+    );
+
+    Ok(Modification::Unchanged)
+}
+
+pub fn run(hook: &RunHook, config: &Config, session: &Session) -> Result<Modification> {
+    match hook.hook {
+        Hook::CommitMsg => commit_msg(&hook.message_file, hook.fix, config, session)?,
+    }
+
+    Ok(Modification::Unchanged)
+}
+
+/// Whether our `commit-msg` hook is installed with `--fix`,
+/// so that it adds the trailers to every commit message.
+pub fn adds_trailers(repo: &Repository) -> Result<bool> {
+    let hook_file = repo.hooks_dir()?.join(Hook::CommitMsg.to_string());
+    Ok(std::fs::read_to_string(hook_file).map_or(false, |script| {
+        script.contains(MARKER) && script.contains(" --fix ")
+    }))
+}
+
+fn script(install: &InstallHook) -> String {
+    format!(
+        "#!/bin/sh\n{}\nexec {} hook run {}{} \"$@\"\n",
+        MARKER,
+        env!("CARGO_PKG_NAME"),
+        install.hook,
+        if install.fix { " --fix" } else { "" }
+    )
+}
+
+/// Rejects the commit message if it is missing trailers of the current navigators,
+/// or adds them if `fix` is set. Does nothing when there is no drive session.
+fn commit_msg(file: &Path, fix: bool, config: &Config, session: &Session) -> Result<()> {
+    let ids = drive::session_navigators(session.scope)?;
+    if ids.is_empty() {
+        return Ok(());
+    }
+
+    let repo = Repository::discover()?;
+    let label = drive::email_label(Some(&repo))?;
+    let navigators = ids
+        .iter()
+        .map(|id| drive::match_navigator(id, config, label.as_deref()))
+        .collect::<Result<Vec<_>>>()?;
+    let trailers = drive::navigator_trailers(session, &repo)?;
+    let expected = drive::trailer_lines(&trailers, &navigators, None)?;
+
+    let content =
+        std::fs::read_to_string(file).wrap_err_with(|| format!("File: {}", file.display()))?;
+    let comment = comment_prefix(&repo)?;
+    match check_message(&content, &comment, &expected, fix)? {
+        Some(fixed) => {
+            std::fs::write(file, fixed).wrap_err_with(|| format!("File: {}", file.display()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Checks the message for the expected trailers, and returns the fixed message if `fix` is set
+/// and trailers are missing.
+/// An empty message is left alone, so that git still aborts the commit.
+fn check_message(
+    content: &str,
+    comment: &str,
+    expected: &[String],
+    fix: bool,
+) -> Result<Option<String>> {
+    let (message, comments) = message::split_comments(content, comment);
+    let stripped = message::strip_comments(message, comment);
+    if stripped.trim().is_empty() {
+        return Ok(None);
+    }
+
+    let missing = message::missing_trailers(&stripped, expected);
+    if missing.is_empty() {
+        return Ok(None);
+    }
+
+    if fix {
+        let fixed = message::add_trailers(message, &missing, Some(comment)) + comments;
+        return Ok(Some(fixed));
+    }
+
+    for line in &missing {
+        eprintln!("    missing {}", style(line).yellow());
+    }
+    bail!(
+        "The commit message is missing the trailers of the navigators, add them or drive {} first.",
+        style("alone").yellow()
+    )
+}
+
+/// The prefix of comment lines in the commit message,
+/// from `core.commentString` or `core.commentChar`.
+fn comment_prefix(repo: &Repository) -> Result<String> {
+    let configured = match repo.config_value("core.commentString")? {
+        Some(prefix) => Some(prefix),
+        None => repo.config_value("core.commentChar")?,
+    };
+    // with `auto`, git picks a prefix that the message does not use, which we cannot know here
+    Ok(match configured.as_deref() {
+        None | Some("" | "auto") => String::from("#"),
+        Some(prefix) => prefix.to_string(),
+    })
+}
+
+#[cfg(unix)]
+fn make_executable(file: &Path) -> Result<()> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let mut permissions = std::fs::metadata(file)?.permissions();
+    permissions.set_mode(permissions.mode() | 0o111);
+    std::fs::set_permissions(file, permissions)
+        .wrap_err_with(|| format!("File: {}", file.display()))
+}
+
+#[cfg(not(unix))]
+fn make_executable(_file: &Path) -> Result<()> {
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const TRAILER: &str = "Co-Authored-By: bernd <bernd@bar.org>";
+
+    const EDIT_MESSAGE: &str = concat!(
+        "Subject\n",
+        "\n",
+        "# Please enter the commit message for your changes.\n",
+        "# ------------------------ >8 ------------------------\n",
+        "diff --git a/src/main.rs b/src/main.rs\n",
+    );
+
+    fn expected() -> Vec<String> {
+        vec![String::from(TRAILER)]
+    }
+
+    #[test]
+    fn empty_message_stays_empty() {
+        let content = "\n# Please enter the commit message for your changes.\n";
+        assert_eq!(
+            check_message(content, "#", &expected(), true).unwrap(),
+            None
+        );
+        assert_eq!(
+            check_message(content, "#", &expected(), false).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn fix_adds_the_trailers_above_the_comments() {
+        let fixed = check_message(EDIT_MESSAGE, "#", &expected(), true).unwrap();
+        assert_eq!(
+            fixed.as_deref(),
+            Some(concat!(
+                "Subject\n",
+                "\n",
+                "Co-Authored-By: bernd <bernd@bar.org>\n",
+                "\n",
+                "# Please enter the commit message for your changes.\n",
+                "# ------------------------ >8 ------------------------\n",
+                "diff --git a/src/main.rs b/src/main.rs\n",
+            ))
+        );
+    }
+
+    #[test]
+    fn missing_trailers_are_rejected() {
+        assert!(check_message(EDIT_MESSAGE, "#", &expected(), false).is_err());
+
+        let content = format!("Subject\n\n{}\n", TRAILER);
+        assert_eq!(
+            check_message(&content, "#", &expected(), false).unwrap(),
+            None
+        );
+    }
+
+    #[test]
+    fn no_change_without_a_session() {
+        assert_eq!(check_message(EDIT_MESSAGE, "#", &[], true).unwrap(), None);
+        assert_eq!(check_message(EDIT_MESSAGE, "#", &[], false).unwrap(), None);
+    }
+
+    #[test]
+    fn hook_script() {
+        let install = InstallHook {
+            hook: Hook::CommitMsg,
+            fix: true,
+            force: false,
+        };
+        assert_eq!(
+            script(&install),
+            "#!/bin/sh\n# Installed by git-drive\nexec git-drive hook run commit-msg --fix \"$@\"\n"
+        );
+    }
+}
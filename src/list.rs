@@ -3,28 +3,32 @@ use crate::{
     data::{Kind, Modification, Navigator},
 };
 
-pub fn run(kind: Kind, config: &Config) -> Modification {
-    for line in list(kind, config) {
+/// Prints the entries, including the archived ones if `all` is set.
+pub fn run(kind: Kind, config: &Config, all: bool) -> Modification {
+    for line in list(kind, config, all) {
         println!("{}", line);
     }
     Modification::Unchanged
 }
 
-fn list(kind: Kind, config: &Config) -> Box<dyn Iterator<Item = String> + '_> {
-    match kind {
-        Kind::Navigator => Box::new(config.navigators.iter().map(format_nav)),
-        Kind::Driver => Box::new(
-            config
-                .drivers
-                .iter()
-                .map(|drv| &drv.navigator)
-                .map(format_nav),
-        ),
-    }
+fn list(kind: Kind, config: &Config, all: bool) -> Box<dyn Iterator<Item = String> + '_> {
+    let navigators: Box<dyn Iterator<Item = &Navigator> + '_> = match kind {
+        Kind::Navigator => Box::new(config.navigators.iter()),
+        Kind::Driver => Box::new(config.drivers.iter().map(|drv| &drv.navigator)),
+    };
+    Box::new(
+        navigators
+            .filter(move |nav| all || !nav.archived)
+            .map(format_nav),
+    )
 }
 
 fn format_nav(nav: &Navigator) -> String {
-    format!("{}: {} <{}>", &*nav.alias, nav.name, nav.email)
+    if nav.archived {
+        format!("{}: {} <{}> (archived)", &*nav.alias, nav.name, nav.email)
+    } else {
+        format!("{}: {} <{}>", &*nav.alias, nav.name, nav.email)
+    }
 }
 
 #[cfg(test)]
@@ -36,7 +40,7 @@ mod tests {
     #[test]
     fn list_navigators() {
         let config = Config::from_iter([nav1().ent(), nav2().ent(), drv1(None).ent()]);
-        let lines = list(Kind::Navigator, &config).collect::<Vec<_>>();
+        let lines = list(Kind::Navigator, &config, false).collect::<Vec<_>>();
         assert_eq!(
             lines,
             vec!["nav1: bernd <foo@bar.org>", "nav2: ronny <baz@bar.org>"]
@@ -46,7 +50,28 @@ mod tests {
     #[test]
     fn list_drivers() {
         let config = Config::from_iter([nav1().ent(), nav2().ent(), drv1(None).ent()]);
-        let lines = list(Kind::Driver, &config).collect::<Vec<_>>();
+        let lines = list(Kind::Driver, &config, false).collect::<Vec<_>>();
         assert_eq!(lines, vec!["drv1: ralle <qux@bar.org>"]);
     }
+
+    #[test]
+    fn list_archived_navigators_only_with_all() {
+        let archived = Navigator {
+            archived: true,
+            ..nav1()
+        };
+        let config = Config::from_iter([archived, nav2()]);
+
+        let lines = list(Kind::Navigator, &config, false).collect::<Vec<_>>();
+        assert_eq!(lines, vec!["nav2: ronny <baz@bar.org>"]);
+
+        let lines = list(Kind::Navigator, &config, true).collect::<Vec<_>>();
+        assert_eq!(
+            lines,
+            vec![
+                "nav1: bernd <foo@bar.org> (archived)",
+                "nav2: ronny <baz@bar.org>"
+            ]
+        );
+    }
 }
@@ -9,7 +9,10 @@ Support for switching git authors and co-authors
 git drive
 
 # Start driving with the specified navigator(s)
-git drive with user1 [user2...]
+git drive with user1 [user2...] [--for 2h | --until 18:00]
+
+# Start driving with a guest who is not a known navigator, and optionally add them
+git drive with "Jane Doe <jane@example.com>" [user2...] [--save [--as jane]]
 
 # Start driving alone
 git drive alone
@@ -17,18 +20,28 @@ git drive alone
 # Show current navigators
 git drive show [--color[=<color>]]
 
-# List known navigators
-git drive list
+# List known navigators, including the archived ones with --all
+git drive list [--all]
 
 # Edit navigator(s), either prompted for, or specified
 git drive edit [user1 [user2...]]
 
 # Add new navigator, either prompted for, or specified
-git drive new [[--as] user --name User --email Email]
+git drive new [[--as] user --name User --email Email [--emails label=Email,...] [--aliases nick,...]]
 
 # Delets navigator(s), either prompted for, or specified
 git drive delete [user1 [user2...]]
 
+# Rename a navigator, either prompted for, or specified
+git drive rename [old [new]]
+
+# Turn a navigator into a driver, either prompted for, or specified
+git drive promote [user] [--key GPGSigningKey] [--keep]
+
+# Archive navigator(s) who left, or make them available again
+git drive archive user1 [user2...]
+git drive unarchive user1 [user2...]
+
 # List known aliases for the driver
 git drive me list
 
@@ -36,15 +49,247 @@ git drive me list
 git drive me edit [user1 [user2...]]
 
 # Add new driver, either prompted for, or specified
-git drive me new [[--as] user --name User --email Email --key GPGSigningKey]
+git drive me new [[--as] user --name User --email Email --key GPGSigningKey [--key-format ssh]]
 
 # Delets a driver, either prompted for, or specified
 git drive me delete [user1 [user2...]]
 
+# Rename a driver, either prompted for, or specified
+git drive me rename [old [new]]
+
+# Turn a driver into a navigator, either prompted for, or specified
+git drive me demote [user] [--keep]
+
 # Change identity while driving
 git drive as alias
+
+# Change identity to the driver that the rules select for this repository
+git drive me auto
+
+# Add the navigators to commits made before starting the drive
+git drive amend [<range>] [--with user1,user2] [--force]
+
+# Check that the commits made while driving have the navigators' trailers
+git drive verify <range> [--with user1,user2]
+
+# Install a commit-msg hook that rejects commits without the navigators' trailers
+git drive hook install commit-msg [--fix]
+
+# Edit all navigators and drivers at once in $VISUAL or $EDITOR
+git drive config edit
+
+# Report all problems in the roster file, and fix those that can be fixed
+git drive config check [--fix]
+```
+
+# Sessions
+
+A drive session applies to all working trees of a repository.
+Pass `--worktree` to `git drive`, `with`, `alone`, `show`, or `as` to drive only in the current working tree,
+or `--shared` to explicitly address the session of the whole repository.
+Pass `--global` to drive in all repositories, which sets `commit.template` in the global git config
+and keeps the session next to the config file of the navigators.
+Without any of these flags, a session of the current working tree takes precedence over a shared one,
+which takes precedence over a global one.
+Outside of a repository, `--global` is required.
+
+To end a drive by itself, pass `--for <duration>` (e.g. `2h` or `1h30m`) or `--until <time>` (e.g. `18:00`)
+to `git drive` or `git drive with`.
+`--until` is parsed by git and accepts the same times as `git log --until`.
+A time of day that has already passed means tomorrow, any other time must be in the future.
+`git drive show` prints how long the drive has left, e.g. `bernd (expires in 1h30m)`.
+An expired drive shows no navigators and is ended the next time `git drive` runs,
+which includes the `commit-msg` hook.
+As git would keep using `commit.template` after the drive expired, a drive that expires
+adds the trailers with the `commit-msg` hook instead, if it is installed with `git drive hook install commit-msg --fix`.
+Otherwise, or for a global drive, the template stays until the next `git drive` ends the expired drive.
+
+# Guests
+
+A navigator can also be given as `"Name <email>"` to `git drive with`,
+which drives with them without adding them to the known navigators.
+The guest stays part of the session, so `amend`, `verify`, and the `commit-msg` hook use them as well.
+Pass `--save` to add the guests to the navigators, using the local part of their email as the alias,
+or the alias given with `--as` if there is only one guest.
+A guest with the email of a known navigator uses that navigator instead.
+
+# Archived navigators
+
+Instead of deleting navigators who left the team, archive them with `git drive archive`.
+Archived navigators are not listed, unless `git drive list --all` is used,
+are not offered when selecting navigators, and cannot be driven with.
+They are still known to `amend`, `verify`, and the `commit-msg` hook,
+so sessions and commits that already name them keep working.
+`git drive unarchive` makes them available again.
+
+# Secondary aliases
+
+Next to its alias, an entry can have secondary aliases, given as `--aliases bob,bobby`
+to `new` or `edit`.
+They are accepted wherever the alias is, for example `git drive with bobby`,
+but the entry is always shown and remembered under its alias.
+An alias can belong to only one entry.
+
+# Multiple emails
+
+A navigator or driver can have other emails next to the default one, each with a label,
+given as `--emails oss=alice@example.org,home=alice@example.net` to `new` or `edit`.
+Editing an existing entry also prompts for them; enter the label of one of them as the email
+to make it the default, the previous default stays as another email labeled `previous`.
+Pass `alias:label` to `git drive with` to use the email with that label in the trailer,
+or set `drive.emailLabel` to use the emails with that label whenever a navigator has one:
+
+```bash
+git config drive.emailLabel oss
+```
+
+# Trailers
+
+By default, every navigator is added as a `Co-Authored-By` trailer.
+Configure other trailers with `drive.trailer`, which can be given multiple times.
+A trailer is either just the key, which adds one trailer per navigator,
+or `<key>=driver`, which adds one trailer for the current `user.name` and `user.email`.
+Trailers configured in the repository replace the global ones.
+
+```bash
+git config --global --add drive.trailer Co-authored-by
+git config --add drive.trailer Signed-off-by=driver
+```
+
+To use other trailers for a single drive, pass `--trailer` to `git drive` or `git drive with`.
+
+# Verifying commits
+
+Every drive session is recorded in a history next to the session.
+`git drive verify <range>` checks that each commit in the range that was committed during a drive
+has the trailers for the navigators of that drive, and fails with a report otherwise.
+This catches commits from tools that ignore `commit.template`, e.g. in a `pre-push` hook:
+
+```bash
+git drive verify @{upstream}..
+```
+
+Pass `--with` to expect the given navigators on all commits instead.
+
+# Hooks
+
+`git drive hook install commit-msg` installs a `commit-msg` hook into the hooks directory
+of the repository, or into `core.hooksPath` if that is set.
+While driving, the hook rejects commit messages that are missing the trailers of the navigators.
+Install it with `--fix` to add the missing trailers instead.
+Comment lines and everything below the scissors line are ignored, honoring `core.commentChar`,
+and `--fix` adds the trailers above them.
+Without a drive session, the hook accepts every commit.
+
+# Signing commits
+
+A driver's signing key is a GPG key id by default.
+For SSH or X.509 signing, pass `--key-format ssh` or `--key-format x509` to `git drive me new` or `git drive me edit`.
+An SSH key is either the path to a key file, which must be readable, or a public key prefixed with `key::`.
+
+```bash
+git drive me new ralle --name Ralle --email ralle@example.org --key ~/.ssh/id_ed25519.pub --key-format ssh
 ```
 
+`git drive as <alias>` sets `user.name`, `user.email`, `user.signingkey`, and `gpg.format`
+in the git config of the current session.
+To also turn on `commit.gpgsign` for drivers with a key, set `drive.gpgsign`:
+
+```bash
+git config --global drive.gpgsign true
+```
+
+*/
+// the patterns of the driver rules contain `/*`, which would open a nested block comment
+//! # Driver rules
+//!
+//! To switch between e.g. a work and a personal driver automatically, map repositories to drivers
+//! with `drive.driverRule`, which can be given multiple times and is checked in order.
+//! A rule is either `remote:<pattern>=<alias>`, matched against the URLs of the remotes,
+//! or `path:<pattern>=<alias>`, matched against the root of the working tree.
+//! In a pattern, `*` matches within a path segment and `**` across segments.
+//!
+//! ```bash
+//! git config --global --add drive.driverRule 'remote:github.com/acme/*=work'
+//! git config --global --add drive.driverRule 'path:~/private/**=me'
+//! ```
+//!
+//! `git drive`, `git drive with`, and `git drive me auto` take the seat of the selected driver,
+//! and warn if the repository used another `user.email` before.
+//! Renaming a driver does not change the rules, `git drive me rename` lists those that still select the old alias.
+//!
+/*!
+# Commit template
+
+By default, the commit template contains the trailers for the navigators.
+To use your own template, point `drive.template` to a file,
+either globally or per repository. Relative paths are resolved against the working tree.
+
+```bash
+git config drive.template .github/commit-template
+```
+
+The template may contain the following placeholders:
+
+- `{{co_authors}}`: the configured trailers
+- `{{driver}}`: the current `user.name` and `user.email`
+- `{{branch}}`: the checked out branch
+- `{{ticket}}`: the ticket id in the branch name, e.g. `ABC-123`
+
+The ticket id is found with the regular expression in `drive.ticketPattern`.
+If the pattern has a capture group, the first group is used.
+
+# Prompts
+
+The lists of navigators and drivers show each alias with its name and email,
+and mark the members of the current session.
+Type to narrow the interactive lists down by alias, name, or email,
+and press [escape] to clear the filter.
+
+When selecting navigators to drive with, the ones you drove with most recently come first.
+Set `drive.order` to `frequent` to put the ones you drove with most often first,
+to `alpha` to sort them by alias, or to `config-order` to keep the order of the roster:
+
+```bash
+git config --global drive.order frequent
+```
+
+If the interactive prompts do not work in your terminal, e.g. in an Emacs shell,
+switch to plain prompts that print a numbered list and read a line of numbers or aliases:
+
+```bash
+git config --global drive.prompt plain
+```
+
+Plain prompts are the default when `TERM` is `dumb`. Set `drive.prompt` to `fancy` to always use the interactive prompts.
+In plain prompts, press [return] to keep the value in brackets, or enter `-` to clear an optional one.
+
+# Scripting
+
+When stdin is not a terminal, or with `--no-input`, `git drive` never prompts.
+Missing input is an error that names the argument to pass instead, e.g. `--name` or `--email`.
+
+# Configuration
+
+The navigators and drivers are stored in a file in the user's config directory.
+That file can pull in shared rosters with `include: <path>` lines,
+which are resolved relative to the including file.
+Included entries are never written back to the including file.
+
+To change many entries at once, `git drive config edit` opens the roster in `$VISUAL` or `$EDITOR`.
+If the edited roster cannot be read, the editor opens again with the error above the offending line.
+`git drive config check` reports all problems of the roster file with their line and column,
+and `--fix` repairs those with an unambiguous fix, e.g. a missing `key:` line.
+
+To store the roster in the global git config instead, run
+
+```bash
+git config --global drive.backend git
+```
+
+The roster is then kept in `~/.gitconfig`, or in the XDG git config if only that exists.
+Entries in other config files, including included ones, are not part of it.
+
 */
 #![warn(clippy::all, clippy::nursery)]
 #![warn(clippy::cargo, clippy::pedantic)]
@@ -88,50 +333,105 @@ git drive as alias
 )]
 
 use crate::{
-    args::Action,
+    args::{Action, Args},
     config::Config,
-    data::{Kind, Modification},
+    data::{Kind, Modification, Session},
+    ui::SelectMany,
 };
 use console::style;
-use eyre::{bail, Result};
+use eyre::Result;
 use std::slice::from_ref;
 
+mod amend;
+mod archive;
 mod args;
+mod auto;
 mod config;
 mod data;
 mod delete;
 mod drive;
 mod edit;
+mod hook;
 mod list;
+mod message;
 mod new;
+mod promote;
+mod rename;
+mod repo;
+mod template;
 mod ui;
+mod verify;
 
 fn main() -> Result<()> {
-    let action = args::action();
+    let Args {
+        action,
+        session,
+        no_input,
+    } = args::args();
+
+    // checking must work even if the config cannot be loaded
+    if let Action::CheckConfig(check) = &action {
+        return config::check(check);
+    }
+
     let mut config = config::load()?;
-    let ui = ui::ui();
+    let ui = ui::ui(no_input);
+
+    drive::end_expired_sessions()?;
+
+    if matches!(
+        action,
+        Action::DriveFromSelection
+            | Action::DriveWith(_)
+            | Action::DriveWithAll(_)
+            | Action::DriveWithSaving(_)
+    ) {
+        auto::apply(&config, &session)?;
+    }
 
     let changed = match action {
-        Action::DriveFromSelection => select_drive(&config)?,
-        Action::DriveWith(id) => drive::run(from_ref(&id), &config)?,
-        Action::DriveWithAll(ids) => drive::run(&ids, &config)?,
-        Action::DriveAlone => drive::alone()?,
-        Action::ListNavigators => list::run(Kind::Navigator, &config),
-        Action::ListDrivers => list::run(Kind::Driver, &config),
-        Action::ShowCurrentNavigator(show) => drive::current(show),
+        Action::DriveFromSelection => select_drive(ui, &config, &session)?,
+        Action::DriveWith(id) => drive::run(from_ref(&id), &config, &session)?,
+        Action::DriveWithAll(ids) => drive::run(&ids, &config, &session)?,
+        Action::DriveWithSaving(save) => drive::run_saving(save, &mut config, &session)?,
+        Action::DriveAlone => drive::alone(&session)?,
+        Action::ListNavigators => list::run(Kind::Navigator, &config, false),
+        Action::ListAllNavigators => list::run(Kind::Navigator, &config, true),
+        Action::ListDrivers => list::run(Kind::Driver, &config, false),
+        Action::ShowCurrentNavigator(show) => drive::current(show, &session),
         Action::NewNavigator(partial) => new::run(ui, Kind::Navigator, &mut config, partial)?,
         Action::EditNavigator(partial) => edit::run(ui, Kind::Navigator, &mut config, partial)?,
         Action::DeleteNavigatorFromSelection => delete::select(ui, Kind::Navigator, &mut config)?,
         Action::DeleteNavigator(id) => delete::run(Kind::Navigator, &mut config, from_ref(&id)),
         Action::DeleteAllNavigators(ids) => delete::run(Kind::Navigator, &mut config, &ids),
+        Action::RenameNavigator(rename) => {
+            let renamed = rename::run(ui, Kind::Navigator, &mut config, rename)?;
+            config::store(&config)?;
+            renamed.finish()?
+        }
+        Action::PromoteNavigator(change) => promote::promote(ui, &mut config, change)?,
+        Action::ArchiveNavigators(ids) => archive::archive(&mut config, &ids)?,
+        Action::UnarchiveNavigators(ids) => archive::unarchive(&mut config, &ids)?,
         Action::NewDriver(partial) => new::run(ui, Kind::Driver, &mut config, partial)?,
         Action::EditDriver(partial) => edit::run(ui, Kind::Driver, &mut config, partial)?,
         Action::DeleteDriverFromSelection => delete::select(ui, Kind::Driver, &mut config)?,
         Action::DeleteDriver(id) => delete::run(Kind::Driver, &mut config, from_ref(&id)),
         Action::DeleteAllDrivers(ids) => delete::run(Kind::Driver, &mut config, &ids),
-        Action::DriveAsFromSelection | Action::DriveAs(_) => {
-            bail!("Switching seats not yet implemented")
+        Action::RenameDriver(rename) => {
+            let renamed = rename::run(ui, Kind::Driver, &mut config, rename)?;
+            config::store(&config)?;
+            renamed.finish()?
         }
+        Action::DemoteDriver(change) => promote::demote(ui, &mut config, change)?,
+        Action::Amend(amend) => amend::run(amend, &config, &session)?,
+        Action::Verify(verify) => verify::run(verify, &config, &session)?,
+        Action::InstallHook(install) => hook::install(&install)?,
+        Action::RunHook(hook) => hook::run(&hook, &config, &session)?,
+        Action::EditConfig => config::edit(&mut config)?,
+        Action::CheckConfig(_) => unreachable!("checked before loading the config"),
+        Action::DriveAsFromSelection => drive::select_driver(ui, &config, &session)?,
+        Action::DriveAs(id) => drive::switch_driver(&id, &config, &session)?,
+        Action::DriveAuto => auto::run(&config, &session)?,
     };
 
     if changed == Modification::Changed {
@@ -141,9 +441,18 @@ fn main() -> Result<()> {
     Ok(())
 }
 
-fn select_drive(config: &Config) -> Result<Modification> {
-    if let Some(changed) = drive::select(ui::ui(), config)? {
+fn select_drive(ui: impl SelectMany, config: &Config, session: &Session) -> Result<Modification> {
+    if let Some(changed) = drive::select(ui, config, session)? {
         Ok(changed)
+    } else if !config.navigators.is_empty() {
+        eprintln!("{}", style("All navigators are archived").yellow());
+        eprintln!();
+        eprintln!(
+            "List them with {} and make them available again with {}",
+            style(concat!(env!("CARGO_PKG_NAME"), " list --all")).green(),
+            style(concat!(env!("CARGO_PKG_NAME"), " unarchive <alias>")).green()
+        );
+        Ok(Modification::Unchanged)
     } else {
         use std::fmt::Write;
         let mut pre_help = String::with_capacity(128);
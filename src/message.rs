@@ -0,0 +1,344 @@
+/// The line below which git drops everything from the message, after the comment prefix.
+const SCISSORS: &str = "------------------------ >8 ------------------------";
+
+/// A trailer line in a commit message, e.g. `Co-Authored-By: Bernd <bernd@example.com>`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TrailerLine<'a> {
+    pub key: &'a str,
+    pub value: &'a str,
+}
+
+impl<'a> TrailerLine<'a> {
+    pub fn parse(line: &'a str) -> Option<Self> {
+        let (key, value) = line.split_once(':')?;
+        let valid_key = !key.is_empty()
+            && key.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+            && !key.starts_with('-');
+        valid_key.then(|| Self {
+            key,
+            value: value.trim(),
+        })
+    }
+
+    /// The email of the identity in the value, if there is one.
+    pub fn email(&self) -> Option<&'a str> {
+        let (_, email) = self.value.rsplit_once('<')?;
+        let (email, _) = email.split_once('>')?;
+        Some(email.trim())
+    }
+
+    /// Trailers are the same if their keys match ignoring case,
+    /// and either their emails match ignoring case, or their values match exactly.
+    pub fn same_as(&self, other: &Self) -> bool {
+        self.key.eq_ignore_ascii_case(other.key)
+            && match (self.email(), other.email()) {
+                (Some(this), Some(other)) => this.eq_ignore_ascii_case(other),
+                _ => self.value == other.value,
+            }
+    }
+}
+
+/// Splits the message into the part before the trailers and the trailer block.
+///
+/// The trailer block is the last paragraph of the message, if all of its lines are trailers
+/// or continuations of a trailer. The first paragraph is the subject and never a trailer block.
+/// Lines starting with the `comment` prefix, if there is one, are skipped.
+pub fn split_trailers<'a>(message: &'a str, comment: Option<&str>) -> (&'a str, &'a str) {
+    let message = message.trim_end();
+    let paragraph_start = message.rfind("\n\n").map(|idx| idx + 2);
+
+    let is_comment = |line: &str| comment.map_or(false, |prefix| line.starts_with(prefix));
+    let is_trailer_block = |block: &str| {
+        let mut lines = block.lines().filter(|l| !is_comment(l));
+        lines.next().and_then(TrailerLine::parse).is_some()
+            && lines.all(|l| l.starts_with(char::is_whitespace) || TrailerLine::parse(l).is_some())
+    };
+
+    match paragraph_start {
+        Some(start) if message[..start].trim().is_empty() => (message, ""),
+        Some(start) if is_trailer_block(&message[start..]) => {
+            (message[..start].trim_end(), &message[start..])
+        }
+        _ => (message, ""),
+    }
+}
+
+/// Adds the trailers to the message, unless it already has them.
+/// Duplicate trailers for the same identity are removed.
+pub fn add_trailers(message: &str, new_trailers: &[String], comment: Option<&str>) -> String {
+    let (body, block) = split_trailers(message, comment);
+    let new_trailers = new_trailers
+        .iter()
+        .filter_map(|t| TrailerLine::parse(t))
+        .collect::<Vec<_>>();
+
+    let mut lines = Vec::new();
+    let mut seen = Vec::<TrailerLine<'_>>::new();
+    for line in block.lines() {
+        match TrailerLine::parse(line) {
+            Some(trailer)
+                if trailer.email().is_some()
+                    || new_trailers.iter().any(|t| t.same_as(&trailer)) =>
+            {
+                if !seen.iter().any(|t| t.same_as(&trailer)) {
+                    seen.push(trailer);
+                    lines.push(line.to_string());
+                }
+            }
+            _ => lines.push(line.to_string()),
+        }
+    }
+    for trailer in new_trailers {
+        if !seen.iter().any(|t| t.same_as(&trailer)) {
+            seen.push(trailer);
+            lines.push(format!("{}: {}", trailer.key, trailer.value));
+        }
+    }
+
+    let mut message = String::with_capacity(body.len() + lines.len() * 64);
+    message.push_str(body);
+    if !lines.is_empty() {
+        if !body.is_empty() {
+            message.push_str("\n\n");
+        }
+        message.push_str(&lines.join("\n"));
+    }
+    message.push('\n');
+    message
+}
+
+/// The expected trailers that are not in the trailer block of the message.
+pub fn missing_trailers(message: &str, expected: &[String]) -> Vec<String> {
+    let (_, block) = split_trailers(message, None);
+    let present = block
+        .lines()
+        .filter_map(TrailerLine::parse)
+        .collect::<Vec<_>>();
+
+    expected
+        .iter()
+        .filter(|line| {
+            TrailerLine::parse(line).map_or(false, |trailer| {
+                !present.iter().any(|p| p.same_as(&trailer))
+            })
+        })
+        .cloned()
+        .collect()
+}
+
+/// Splits a message as git gives it to the editor and the `commit-msg` hook
+/// into the message and the part at the end that git strips,
+/// i.e. the trailing comment lines and everything below the scissors line.
+pub fn split_comments<'a>(message: &'a str, comment: &str) -> (&'a str, &'a str) {
+    let mut end = 0;
+    for line in message.split_inclusive('\n') {
+        let scissors = line
+            .strip_prefix(comment)
+            .map_or(false, |rest| rest.trim() == SCISSORS);
+        if scissors {
+            break;
+        }
+        end += line.len();
+    }
+
+    let mut start = end;
+    for line in message[..end].split_inclusive('\n').rev() {
+        if !(line.trim().is_empty() || line.starts_with(comment)) {
+            break;
+        }
+        start -= line.len();
+    }
+
+    message.split_at(start)
+}
+
+/// Removes the comment lines, like `git commit --cleanup=strip` does.
+pub fn strip_comments(message: &str, comment: &str) -> String {
+    message
+        .split_inclusive('\n')
+        .filter(|line| !line.starts_with(comment))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn co_author(name: &str) -> String {
+        format!("Co-Authored-By: {} <{}@bar.org>", name, name)
+    }
+
+    #[test]
+    fn subject_is_no_trailer_block() {
+        assert_eq!(
+            split_trailers("Fix: the thing\n", None),
+            ("Fix: the thing", "")
+        );
+    }
+
+    #[test]
+    fn split_trailer_block() {
+        let message = "Subject\n\nBody: not a trailer\nreally\n\nCo-Authored-By: bernd <foo@bar.org>\nSigned-off-by: ralle <qux@bar.org>\n";
+        let (body, block) = split_trailers(message, None);
+        assert_eq!(body, "Subject\n\nBody: not a trailer\nreally");
+        assert_eq!(
+            block,
+            "Co-Authored-By: bernd <foo@bar.org>\nSigned-off-by: ralle <qux@bar.org>"
+        );
+        assert_eq!(
+            block
+                .lines()
+                .filter_map(TrailerLine::parse)
+                .map(|t| t.email())
+                .collect::<Vec<_>>(),
+            [Some("foo@bar.org"), Some("qux@bar.org")]
+        );
+    }
+
+    #[test]
+    fn add_trailers_to_message_without_trailers() {
+        let message = add_trailers("Subject\n\nBody\n", &[co_author("bernd")], None);
+        assert_eq!(
+            message,
+            "Subject\n\nBody\n\nCo-Authored-By: bernd <bernd@bar.org>\n"
+        );
+    }
+
+    #[test]
+    fn add_trailers_to_existing_block() {
+        let message = add_trailers(
+            "Subject\n\nSigned-off-by: ralle <ralle@bar.org>\n",
+            &[co_author("bernd")],
+            None,
+        );
+        assert_eq!(
+            message,
+            "Subject\n\nSigned-off-by: ralle <ralle@bar.org>\nCo-Authored-By: bernd <bernd@bar.org>\n"
+        );
+    }
+
+    #[test]
+    fn add_trailers_deduplicates() {
+        let message = add_trailers(
+            "Subject\n\nCo-authored-by: Bernd <BERND@bar.org>\nCo-Authored-By: bernd <bernd@bar.org>\n",
+            &[co_author("bernd"), co_author("ronny")],
+            None,
+        );
+        assert_eq!(
+            message,
+            "Subject\n\nCo-authored-by: Bernd <BERND@bar.org>\nCo-Authored-By: ronny <ronny@bar.org>\n"
+        );
+    }
+
+    #[test]
+    fn missing_trailers_match_by_email() {
+        let expected = [co_author("bernd"), co_author("ronny")];
+        let message = "Subject\n\nco-authored-by: Bernd B. <BERND@bar.org>\n";
+
+        assert_eq!(
+            missing_trailers(message, &expected),
+            ["Co-Authored-By: ronny <ronny@bar.org>"]
+        );
+    }
+
+    const EDIT_MESSAGE: &str = concat!(
+        "Subject\n",
+        "\n",
+        "Body\n",
+        "\n",
+        "Co-Authored-By: bernd <bernd@bar.org>\n",
+        "\n",
+        "# Please enter the commit message for your changes. Lines starting\n",
+        "# with '#' will be ignored, and an empty message aborts the commit.\n",
+        "#\n",
+        "# On branch main\n",
+        "# Changes to be committed:\n",
+        "#\tmodified:   src/main.rs\n",
+        "#\n",
+        "# ------------------------ >8 ------------------------\n",
+        "# Do not modify or remove the line above.\n",
+        "# Everything below it will be ignored.\n",
+        "diff --git a/src/main.rs b/src/main.rs\n",
+        "\n",
+        "Not-A-Trailer: in the diff\n",
+    );
+
+    #[test]
+    fn split_comments_of_the_edit_message() {
+        let (message, comments) = split_comments(EDIT_MESSAGE, "#");
+        assert_eq!(
+            message,
+            "Subject\n\nBody\n\nCo-Authored-By: bernd <bernd@bar.org>\n"
+        );
+        assert!(comments.starts_with("\n# Please enter the commit message"));
+        assert!(comments.ends_with("Not-A-Trailer: in the diff\n"));
+
+        let expected = [co_author("bernd"), co_author("ronny")];
+        assert_eq!(
+            missing_trailers(&strip_comments(message, "#"), &expected),
+            ["Co-Authored-By: ronny <ronny@bar.org>"]
+        );
+    }
+
+    #[test]
+    fn add_trailers_above_the_comments() {
+        let (message, comments) = split_comments(EDIT_MESSAGE, "#");
+        let fixed = add_trailers(message, &[co_author("ronny")], Some("#")) + comments;
+
+        let expected = EDIT_MESSAGE.replace(
+            "<bernd@bar.org>\n",
+            "<bernd@bar.org>\nCo-Authored-By: ronny <ronny@bar.org>\n",
+        );
+        assert_eq!(fixed, expected);
+    }
+
+    #[test]
+    fn split_comments_with_other_comment_prefix() {
+        let message = concat!(
+            "Subject\n",
+            "# not a comment\n",
+            "\n",
+            "; a comment\n",
+            "; ------------------------ >8 ------------------------\n",
+            "diff\n",
+        );
+        let (message, comments) = split_comments(message, ";");
+        assert_eq!(message, "Subject\n# not a comment\n");
+        assert_eq!(
+            comments,
+            "\n; a comment\n; ------------------------ >8 ------------------------\ndiff\n"
+        );
+    }
+
+    #[test]
+    fn strip_comments_within_the_message() {
+        let message = "Subject\n# a comment\n\nCo-Authored-By: bernd <bernd@bar.org>\n";
+        assert_eq!(
+            strip_comments(message, "#"),
+            "Subject\n\nCo-Authored-By: bernd <bernd@bar.org>\n"
+        );
+    }
+
+    #[test]
+    fn trailer_block_skips_lines_with_the_comment_prefix() {
+        let message = "Subject\n\nCo-Authored-By: bernd <bernd@bar.org>\n; a comment\n";
+        assert_eq!(
+            split_trailers(message, Some(";")).1,
+            "Co-Authored-By: bernd <bernd@bar.org>\n; a comment"
+        );
+        assert_eq!(split_trailers(message, Some("#")).1, "");
+    }
+
+    #[test]
+    fn add_trailers_keeps_other_duplicates() {
+        let message = add_trailers(
+            "Subject\n\nAcked-by: a\nAcked-by: a\n",
+            &[co_author("bernd")],
+            None,
+        );
+        assert_eq!(
+            message,
+            "Subject\n\nAcked-by: a\nAcked-by: a\nCo-Authored-By: bernd <bernd@bar.org>\n"
+        );
+    }
+}
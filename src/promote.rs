@@ -0,0 +1,201 @@
+use crate::{
+    config::Config,
+    data::{ChangeSeat, Driver, Id, KeyFormat, Kind, Modification, Navigator},
+    ui::{self, PromptText, SelectOne},
+};
+use console::style;
+use eyre::{bail, Result};
+
+/// Turns a navigator into a driver, prompting for the signing key.
+pub fn promote(
+    mut ui: impl SelectOne + PromptText,
+    config: &mut Config,
+    change: ChangeSeat,
+) -> Result<Modification> {
+    let id = match change.id {
+        Some(id) => id,
+        None => select_id(&mut ui, Kind::Navigator, config, "promote")?,
+    };
+    let key_format = change.key_format.unwrap_or_default();
+    do_promote(ui, config, &id, (change.key, key_format), change.keep)?;
+
+    println!("The navigator {} is now a driver.", style(&*id).cyan());
+    Ok(Modification::Changed)
+}
+
+/// Turns a driver into a navigator, dropping the signing key.
+pub fn demote(
+    mut ui: impl SelectOne,
+    config: &mut Config,
+    change: ChangeSeat,
+) -> Result<Modification> {
+    let id = match change.id {
+        Some(id) => id,
+        None => select_id(&mut ui, Kind::Driver, config, "demote")?,
+    };
+    do_demote(config, &id, change.keep)?;
+
+    println!("The driver {} is now a navigator.", style(&*id).cyan());
+    Ok(Modification::Changed)
+}
+
+fn select_id(ui: impl SelectOne, kind: Kind, config: &Config, verb: &str) -> Result<Id> {
+    match ui::select_id_from(ui, kind, config)? {
+        Some(id) => Ok(id.clone()),
+        None => bail!("No {}s to {}", kind, verb),
+    }
+}
+
+fn do_promote(
+    ui: impl PromptText,
+    config: &mut Config,
+    id: &Id,
+    (key, key_format): (Option<String>, KeyFormat),
+    keep: bool,
+) -> Result<()> {
+    // only navigators are archived, drivers are always active
+    let navigator = match config.navigators.iter().find(|n| id.same_as_nav(n)) {
+        Some(navigator) => Navigator {
+            archived: false,
+            ..navigator.clone()
+        },
+        None => bail!("Alias {} does not exist.", style(&**id).cyan()),
+    };
+    if config.drivers.iter().any(|d| id.same_as_drv(d)) {
+        bail!("Alias {} already exists.", style(&**id).cyan());
+    }
+    check_removable(config, Kind::Navigator, id, keep)?;
+
+    let key = ui::complete_key(ui, id, key, key_format)?;
+    if !keep {
+        config.navigators.retain(|n| !id.same_as_nav(n));
+    }
+    config.drivers.push(Driver {
+        navigator,
+        key,
+        key_format,
+    });
+
+    Ok(())
+}
+
+fn do_demote(config: &mut Config, id: &Id, keep: bool) -> Result<()> {
+    let navigator = match config.drivers.iter().find(|d| id.same_as_drv(d)) {
+        Some(driver) => driver.navigator.clone(),
+        None => bail!("Alias {} does not exist.", style(&**id).cyan()),
+    };
+    if config.navigators.iter().any(|n| id.same_as_nav(n)) {
+        bail!("Alias {} already exists.", style(&**id).cyan());
+    }
+    check_removable(config, Kind::Driver, id, keep)?;
+
+    if !keep {
+        config.drivers.retain(|d| !id.same_as_drv(d));
+    }
+    config.navigators.push(navigator);
+
+    Ok(())
+}
+
+fn check_removable(config: &Config, kind: Kind, id: &Id, keep: bool) -> Result<()> {
+    if keep {
+        return Ok(());
+    }
+    if let Some(file) = config.included_from(kind, id) {
+        bail!(
+            "The {} {} is defined in the included file {}, pass {} to copy it instead.",
+            kind,
+            style(&**id).cyan(),
+            file.display(),
+            style("--keep").yellow()
+        );
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{
+            tests::{drv1, nav1, nav2},
+            Field,
+        },
+        ui::util::{disable_colors, prompt_text, NoUi},
+    };
+
+    #[test]
+    fn test_promote_navigator() {
+        let text = prompt_text(|field, id, initial| {
+            assert_eq!(field, Field::Key);
+            assert_eq!(id, "nav1");
+            assert_eq!(initial, None);
+            Ok(String::from("key"))
+        });
+
+        let mut config = Config::from_iter([nav1(), nav2()]);
+        let key = (None, KeyFormat::OpenPgp);
+        do_promote(text, &mut config, &Id::from("nav1"), key, false).unwrap();
+
+        assert_eq!(&config.navigators, &[nav2()]);
+        assert_eq!(
+            &config.drivers,
+            &[Driver {
+                navigator: nav1(),
+                key: Some(String::from("key")),
+                key_format: KeyFormat::OpenPgp,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_promote_and_keep_navigator() {
+        let text = prompt_text(|_field, _id, initial| Ok(initial.unwrap_or_default()));
+
+        let mut config = Config::from_iter([nav1()]);
+        let key = (None, KeyFormat::OpenPgp);
+        do_promote(text, &mut config, &Id::from("nav1"), key, true).unwrap();
+
+        assert_eq!(&config.navigators, &[nav1()]);
+        assert_eq!(
+            &config.drivers,
+            &[Driver {
+                navigator: nav1(),
+                key: None,
+                key_format: KeyFormat::OpenPgp,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_promote_to_existing_driver() {
+        let _guard = disable_colors();
+
+        let mut config = Config::from_iter([nav1().ent(), drv1(None).ent()]);
+        let key = (None, KeyFormat::OpenPgp);
+        let err = do_promote(NoUi, &mut config, &Id::from("drv1"), key.clone(), false).unwrap_err();
+        assert_eq!(err.to_string(), "Alias drv1 does not exist.");
+
+        config.navigators.push(drv1(None).navigator);
+        let err = do_promote(NoUi, &mut config, &Id::from("drv1"), key, false).unwrap_err();
+        assert_eq!(err.to_string(), "Alias drv1 already exists.");
+    }
+
+    #[test]
+    fn test_demote_driver() {
+        let mut config = Config::from_iter([nav1().ent(), drv1("key").ent()]);
+        do_demote(&mut config, &Id::from("drv1"), false).unwrap();
+
+        assert_eq!(&config.navigators, &[nav1(), drv1(None).navigator]);
+        assert_eq!(&config.drivers, &[]);
+    }
+
+    #[test]
+    fn test_demote_and_keep_driver() {
+        let mut config = Config::from_iter([drv1("key")]);
+        do_demote(&mut config, &Id::from("drv1"), true).unwrap();
+
+        assert_eq!(&config.navigators, &[drv1(None).navigator]);
+        assert_eq!(&config.drivers, &[drv1("key")]);
+    }
+}
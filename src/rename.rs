@@ -0,0 +1,244 @@
+use crate::{
+    auto,
+    config::Config,
+    data::{Id, Kind, Modification, RenameAlias},
+    drive,
+    ui::{self, PromptAlias, PromptText, SelectOne},
+};
+use console::style;
+use eyre::{bail, Result};
+
+/// An alias that is renamed in the roster, but not yet in the sessions.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Renamed {
+    kind: Kind,
+    old: Id,
+    new: Id,
+}
+
+/// Renames the alias in the roster, the caller stores it and then finishes the rename,
+/// so that the sessions never name an alias that the stored roster does not have.
+pub fn run(
+    mut ui: impl SelectOne + PromptText + PromptAlias,
+    kind: Kind,
+    config: &mut Config,
+    rename: RenameAlias,
+) -> Result<Renamed> {
+    let old = match rename.old {
+        Some(old) => old,
+        None => match ui::select_id_from(&mut ui, kind, config)? {
+            Some(old) => old.clone(),
+            None => bail!("No {}s to rename", kind),
+        },
+    };
+    let old = primary_alias(kind, config, old);
+    let new = do_rename(ui, kind, config, &old, rename.new)?;
+
+    Ok(Renamed { kind, old, new })
+}
+
+impl Renamed {
+    /// Renames the alias in the sessions, once the renamed roster is stored.
+    pub fn finish(self) -> Result<Modification> {
+        let Self { kind, old, new } = self;
+
+        // sessions only remember the navigators
+        if kind == Kind::Navigator {
+            drive::rename_navigator(&old, &new)?;
+        }
+
+        println!(
+            "Renamed the {} {} to {}.",
+            kind,
+            style(&*old).cyan(),
+            style(&*new).cyan()
+        );
+
+        if kind == Kind::Driver {
+            for rule in auto::rules_selecting(&old)? {
+                eprintln!(
+                    "The rule `{}` in {} still selects {}, change it to select {}.",
+                    rule,
+                    style("drive.driverRule").yellow(),
+                    style(&*old).cyan(),
+                    style(&*new).cyan()
+                );
+            }
+        }
+
+        Ok(Modification::Unchanged)
+    }
+}
+
+/// Resolves a secondary alias to the alias that is shown and remembered by sessions.
+fn primary_alias(kind: Kind, config: &Config, id: Id) -> Id {
+    let primary = match kind {
+        Kind::Navigator => config
+            .navigators
+            .iter()
+            .find(|n| id.same_as_nav(n))
+            .map(|n| n.alias.clone()),
+        Kind::Driver => config
+            .drivers
+            .iter()
+            .find(|d| id.same_as_drv(d))
+            .map(|d| d.navigator.alias.clone()),
+    };
+    primary.unwrap_or(id)
+}
+
+fn do_rename(
+    ui: impl PromptText + PromptAlias,
+    kind: Kind,
+    config: &mut Config,
+    old: &Id,
+    new: Option<Id>,
+) -> Result<Id> {
+    let exists = match kind {
+        Kind::Navigator => config.navigators.iter().any(|n| old.same_as_nav(n)),
+        Kind::Driver => config.drivers.iter().any(|d| old.same_as_drv(d)),
+    };
+    if !exists {
+        bail!("Alias {} does not exist.", style(&**old).cyan());
+    }
+    if let Some(file) = config.included_from(kind, old) {
+        bail!(
+            "The {} {} is defined in the included file {} and cannot be renamed here.",
+            kind,
+            style(&**old).cyan(),
+            file.display()
+        );
+    }
+
+    let new = ui::complete_new_alias(ui, kind, config, new.map(|id| id.0))?;
+
+    let alias = match kind {
+        Kind::Navigator => config
+            .navigators
+            .iter_mut()
+            .find(|n| old.same_as_nav(n))
+            .map(|n| &mut n.alias),
+        Kind::Driver => config
+            .drivers
+            .iter_mut()
+            .find(|d| old.same_as_drv(d))
+            .map(|d| &mut d.navigator.alias),
+    };
+    *alias.expect("checked above") = new.clone();
+
+    Ok(new)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        data::{
+            tests::{drv1, nav1, nav2},
+            Navigator,
+        },
+        ui::util::{disable_colors, prompt_alias, NoUi},
+    };
+
+    #[test]
+    fn test_rename_navigator() {
+        let mut config = Config::from_iter([nav1().ent(), nav2().ent(), drv1(None).ent()]);
+        let old = Id::from("nav1");
+        let new = do_rename(
+            NoUi,
+            Kind::Navigator,
+            &mut config,
+            &old,
+            Some(Id::from("bernd")),
+        )
+        .unwrap();
+
+        assert_eq!(new, Id::from("bernd"));
+        assert_eq!(
+            &config.navigators,
+            &[
+                Navigator {
+                    alias: Id::from("bernd"),
+                    ..nav1()
+                },
+                nav2()
+            ]
+        );
+        assert_eq!(&config.drivers, &[drv1(None)]);
+    }
+
+    #[test]
+    fn test_rename_by_secondary_alias() {
+        let nav = Navigator {
+            aliases: vec![Id::from("bernie")],
+            ..nav1()
+        };
+        let mut config = Config::from_iter([nav]);
+        let old = primary_alias(Kind::Navigator, &config, Id::from("bernie"));
+        assert_eq!(old, Id::from("nav1"));
+
+        do_rename(
+            NoUi,
+            Kind::Navigator,
+            &mut config,
+            &old,
+            Some(Id::from("bernd")),
+        )
+        .unwrap();
+        assert_eq!(&*config.navigators[0].alias, "bernd");
+        assert_eq!(config.navigators[0].aliases, [Id::from("bernie")]);
+    }
+
+    #[test]
+    fn test_rename_driver_to_prompted_alias() {
+        let ui = (
+            NoUi,
+            prompt_alias(|kind| {
+                assert_eq!(kind, Kind::Driver);
+                Ok(String::from("ralle"))
+            }),
+        );
+        let mut config = Config::from_iter([nav1().ent(), drv1(None).ent()]);
+        do_rename(ui, Kind::Driver, &mut config, &Id::from("drv1"), None).unwrap();
+
+        assert_eq!(&config.navigators, &[nav1()]);
+        assert_eq!(&*config.drivers[0].navigator.alias, "ralle");
+    }
+
+    #[test]
+    fn test_rename_to_existing_alias() {
+        let _guard = disable_colors();
+
+        let mut config = Config::from_iter([nav1(), nav2()]);
+        let old = Id::from("nav1");
+        let err = do_rename(
+            NoUi,
+            Kind::Navigator,
+            &mut config,
+            &old,
+            Some(Id::from("nav2")),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "Alias nav2 already exists.");
+        assert_eq!(config, Config::from_iter([nav1(), nav2()]));
+    }
+
+    #[test]
+    fn test_rename_non_existing_alias() {
+        let _guard = disable_colors();
+
+        let mut config = Config::from_iter([nav1()]);
+        let old = Id::from("foo");
+        let err = do_rename(
+            NoUi,
+            Kind::Navigator,
+            &mut config,
+            &old,
+            Some(Id::from("bar")),
+        )
+        .unwrap_err();
+
+        assert_eq!(err.to_string(), "Alias foo does not exist.");
+    }
+}
@@ -0,0 +1,580 @@
+use crate::auto::glob_matches;
+use directories::BaseDirs;
+use eyre::{bail, eyre, Result, WrapErr};
+use std::path::{Component, Path, PathBuf};
+
+mod config;
+mod git;
+
+pub use config::ConfigFile;
+use config::Lookup;
+pub use git::Git;
+
+/// The git directories of the repository we are running in.
+///
+/// For the main working tree, both directories are the same.
+/// For a linked working tree, `git_dir` is the private directory of that working tree
+/// and `common_dir` is the directory shared by all working trees.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Repository {
+    git_dir: PathBuf,
+    common_dir: PathBuf,
+    work_tree: Option<PathBuf>,
+}
+
+impl Repository {
+    /// Finds the repository of the current working directory, like `git rev-parse` does.
+    pub fn discover() -> Result<Self> {
+        let cwd = std::env::current_dir()?;
+        std::env::var_os("GIT_DIR").map_or_else(
+            || Self::discover_from(&cwd),
+            |git_dir| {
+                let work_tree = std::env::var_os("GIT_WORK_TREE")
+                    .map_or_else(|| cwd.clone(), |work_tree| normalize(&cwd.join(work_tree)));
+                Self::from_git_dir(normalize(&cwd.join(git_dir)), Some(work_tree))
+            },
+        )
+    }
+
+    fn discover_from(dir: &Path) -> Result<Self> {
+        for dir in dir.ancestors() {
+            let dot_git = dir.join(".git");
+            let work_tree = Some(dir.to_path_buf());
+            if dot_git.is_dir() {
+                return Self::from_git_dir(dot_git, work_tree);
+            }
+            if dot_git.is_file() {
+                let git_dir = read_link_file(&dot_git, "gitdir:")?;
+                return Self::from_git_dir(normalize(&dir.join(git_dir)), work_tree);
+            }
+            if is_git_dir(dir) {
+                return Self::from_git_dir(dir.to_path_buf(), None);
+            }
+        }
+
+        Err(eyre!(
+            concat!(
+                "Could not find a git repository in {} or any of its parents.\n",
+                "\n",
+                "Try calling ",
+                env!("CARGO_PKG_NAME"),
+                " from a working directory of a git repository."
+            ),
+            dir.display()
+        ))
+    }
+
+    fn from_git_dir(git_dir: PathBuf, work_tree: Option<PathBuf>) -> Result<Self> {
+        let common_dir = git_dir.join("commondir");
+        let common_dir = if common_dir.is_file() {
+            let common_dir = std::fs::read_to_string(&common_dir)
+                .wrap_err_with(|| format!("File: {}", common_dir.display()))?;
+            normalize(&git_dir.join(common_dir.trim()))
+        } else {
+            git_dir.clone()
+        };
+
+        Ok(Self {
+            git_dir,
+            common_dir,
+            work_tree,
+        })
+    }
+
+    /// The git dir of the current working tree.
+    pub fn git_dir(&self) -> &Path {
+        &self.git_dir
+    }
+
+    /// The git dir that is shared by all working trees.
+    pub fn common_dir(&self) -> &Path {
+        &self.common_dir
+    }
+
+    /// The root of the current working tree, `None` for bare repositories.
+    pub fn work_tree(&self) -> Option<&Path> {
+        self.work_tree.as_deref()
+    }
+
+    /// The repository config file, as used by `git config --local`.
+    pub fn config_file(&self) -> PathBuf {
+        self.common_dir.join("config")
+    }
+
+    /// The config file of the current working tree, as used by `git config --worktree`.
+    pub fn worktree_config_file(&self) -> PathBuf {
+        self.git_dir.join("config.worktree")
+    }
+
+    /// The directory that git runs hooks from, either `core.hooksPath`
+    /// relative to the working tree, or the `hooks` in the common git dir.
+    pub fn hooks_dir(&self) -> Result<PathBuf> {
+        Ok(match self.config_value("core.hooksPath")? {
+            Some(path) => self.work_tree().unwrap_or(&self.git_dir).join(path),
+            None => self.common_dir.join("hooks"),
+        })
+    }
+
+    /// The name of the checked out branch, `None` if `HEAD` is detached.
+    pub fn branch(&self) -> Result<Option<String>> {
+        let head = self.git_dir.join("HEAD");
+        let head =
+            std::fs::read_to_string(&head).wrap_err_with(|| format!("File: {}", head.display()))?;
+        Ok(head
+            .trim()
+            .strip_prefix("ref: refs/heads/")
+            .map(String::from))
+    }
+
+    /// The remote-tracking ref of the upstream of the checked out branch,
+    /// as configured in `branch.<name>.remote` and `branch.<name>.merge`.
+    pub fn upstream(&self) -> Result<Option<String>> {
+        let branch = match self.branch()? {
+            Some(branch) => branch,
+            None => return Ok(None),
+        };
+        let remote = self.config_value(&format!("branch.{}.remote", branch))?;
+        let merge = self.config_value(&format!("branch.{}.merge", branch))?;
+
+        Ok(match (remote, merge) {
+            (Some(remote), Some(merge)) if remote == "." => Some(merge),
+            (Some(remote), Some(merge)) => Some(format!(
+                "refs/remotes/{}/{}",
+                remote,
+                merge.strip_prefix("refs/heads/").unwrap_or(&merge)
+            )),
+            _ => None,
+        })
+    }
+
+    /// The URLs of all remotes, as configured in `remote.<name>.url`.
+    pub fn remote_urls(&self) -> Result<Vec<String>> {
+        let config = ConfigFile::open(self.config_file())?;
+        Ok(config
+            .subsection_values("remote", "url")
+            .into_iter()
+            .map(String::from)
+            .collect())
+    }
+
+    /// Returns the value for the key, looking at the system config, the global config files,
+    /// the repository config, and the worktree config, in that order,
+    /// and following their includes, like `git config --get` does.
+    pub fn config_value(&self, key: &str) -> Result<Option<String>> {
+        config_value(Some(self), key)
+    }
+
+    /// Returns all values for a multi-valued key from the most specific
+    /// of the system, global, repository, and worktree config that has any values,
+    /// so that a repository can override the global values.
+    pub fn config_values(&self, key: &str) -> Result<Vec<String>> {
+        config_values(Some(self), key)
+    }
+}
+
+/// Like [`Repository::config_value`], but only looks at the system and global config
+/// without a repository.
+pub fn config_value(repo: Option<&Repository>, key: &str) -> Result<Option<String>> {
+    let mut values = Vec::new();
+    for file in config_levels(repo)?.iter().flatten() {
+        collect_values(file, key, repo, 0, &mut values)?;
+    }
+    Ok(values.pop())
+}
+
+/// Like [`Repository::config_values`], but only looks at the system and global config
+/// without a repository.
+pub fn config_values(repo: Option<&Repository>, key: &str) -> Result<Vec<String>> {
+    let mut values = Vec::new();
+    for level in config_levels(repo)? {
+        let mut level_values = Vec::new();
+        for file in &level {
+            collect_values(file, key, repo, 0, &mut level_values)?;
+        }
+        if !level_values.is_empty() {
+            values = level_values;
+        }
+    }
+    Ok(values)
+}
+
+fn config_levels(repo: Option<&Repository>) -> Result<Vec<Vec<ConfigFile>>> {
+    let system = system_config_file()
+        .map(ConfigFile::open)
+        .into_iter()
+        .collect::<Result<Vec<_>>>()?;
+    let global = global_config_files()
+        .into_iter()
+        .map(ConfigFile::open)
+        .collect::<Result<Vec<_>>>()?;
+    let repo = match repo {
+        Some(repo) => repo,
+        None => return Ok(vec![system, global]),
+    };
+
+    let repo_config = ConfigFile::open(repo.config_file())?;
+    let worktree_config = if repo_config.get("extensions.worktreeConfig")? == Some("true") {
+        vec![ConfigFile::open(repo.worktree_config_file())?]
+    } else {
+        Vec::new()
+    };
+
+    Ok(vec![system, global, vec![repo_config], worktree_config])
+}
+
+/// Collects the values for the key from the file and the files that it includes,
+/// in the order that git reads them.
+fn collect_values(
+    file: &ConfigFile,
+    key: &str,
+    repo: Option<&Repository>,
+    depth: usize,
+    values: &mut Vec<String>,
+) -> Result<()> {
+    const MAX_INCLUDE_DEPTH: usize = 10;
+
+    for lookup in file.lookup(key)? {
+        match lookup {
+            Lookup::Value(value) => values.push(value.to_string()),
+            Lookup::Include { condition, path } => {
+                if let Some(condition) = condition {
+                    if !include_condition_holds(condition, file.path(), repo)? {
+                        continue;
+                    }
+                }
+                if depth >= MAX_INCLUDE_DEPTH {
+                    bail!(
+                        "Exceeded the maximum include depth of {} while including {} from {}.",
+                        MAX_INCLUDE_DEPTH,
+                        path,
+                        file.path().display()
+                    );
+                }
+                // like git, includes of missing files are ignored
+                let included = ConfigFile::open(include_path(file.path(), path))?;
+                collect_values(&included, key, repo, depth + 1, values)?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Resolves an included path relative to the directory of the including file,
+/// expanding a leading `~/`.
+fn include_path(from: &Path, path: &str) -> PathBuf {
+    match (path.strip_prefix("~/"), BaseDirs::new()) {
+        (Some(rest), Some(dirs)) => dirs.home_dir().join(rest),
+        _ => from
+            .parent()
+            .map_or_else(|| PathBuf::from(path), |dir| dir.join(path)),
+    }
+}
+
+/// Whether the condition of an `includeIf` holds.
+/// Supports `gitdir:`, `gitdir/i:`, and `onbranch:`, other conditions never hold.
+fn include_condition_holds(
+    condition: &str,
+    from: &Path,
+    repo: Option<&Repository>,
+) -> Result<bool> {
+    let repo = match repo {
+        Some(repo) => repo,
+        None => return Ok(false),
+    };
+
+    if let Some(pattern) = condition.strip_prefix("gitdir:") {
+        Ok(gitdir_matches(pattern, from, repo, false))
+    } else if let Some(pattern) = condition.strip_prefix("gitdir/i:") {
+        Ok(gitdir_matches(pattern, from, repo, true))
+    } else if let Some(pattern) = condition.strip_prefix("onbranch:") {
+        let pattern = match pattern.strip_suffix('/') {
+            Some(prefix) => format!("{}/**", prefix),
+            None => pattern.to_string(),
+        };
+        Ok(repo.branch()?.map_or(false, |branch| {
+            glob_matches(pattern.as_bytes(), branch.as_bytes())
+        }))
+    } else {
+        Ok(false)
+    }
+}
+
+/// Matches the git dir against the pattern of a `gitdir:` condition, like git does:
+/// `~/` and `./` are expanded, relative patterns match anywhere,
+/// and a trailing `/` matches everything below.
+fn gitdir_matches(pattern: &str, from: &Path, repo: &Repository, ignore_case: bool) -> bool {
+    let mut pattern = if let Some(rest) = pattern.strip_prefix("./") {
+        from.parent()
+            .map_or_else(|| PathBuf::from(rest), |dir| dir.join(rest))
+            .display()
+            .to_string()
+    } else {
+        include_path(Path::new(""), pattern).display().to_string()
+    };
+    if !(Path::new(&pattern).is_absolute() || pattern.starts_with("**/")) {
+        pattern.insert_str(0, "**/");
+    }
+    if pattern.ends_with('/') {
+        pattern.push_str("**");
+    }
+
+    let git_dirs = [
+        Some(repo.git_dir().to_path_buf()),
+        std::fs::canonicalize(repo.git_dir()).ok(),
+    ];
+    git_dirs.iter().flatten().any(|git_dir| {
+        let git_dir = git_dir.display().to_string();
+        if ignore_case {
+            glob_matches(
+                pattern.to_lowercase().as_bytes(),
+                git_dir.to_lowercase().as_bytes(),
+            )
+        } else {
+            glob_matches(pattern.as_bytes(), git_dir.as_bytes())
+        }
+    })
+}
+
+/// The system config file, as read by `git config --system`,
+/// unless it is disabled with `GIT_CONFIG_NOSYSTEM`.
+fn system_config_file() -> Option<PathBuf> {
+    let disabled = std::env::var("GIT_CONFIG_NOSYSTEM").map_or(false, |v| {
+        matches!(v.to_ascii_lowercase().as_str(), "1" | "true" | "yes" | "on")
+    });
+    if disabled {
+        return None;
+    }
+    if let Some(file) = std::env::var_os("GIT_CONFIG_SYSTEM").filter(|f| !f.is_empty()) {
+        return Some(PathBuf::from(file));
+    }
+    if cfg!(windows) {
+        std::env::var_os("PROGRAMFILES")
+            .map(|dir| Path::new(&dir).join("Git").join("etc").join("gitconfig"))
+    } else {
+        Some(PathBuf::from("/etc/gitconfig"))
+    }
+}
+
+/// The global config files, as read by `git config --global`.
+pub fn global_config_files() -> Vec<PathBuf> {
+    let mut files = Vec::with_capacity(2);
+    if let Some(config_home) = std::env::var_os("XDG_CONFIG_HOME").filter(|d| !d.is_empty()) {
+        files.push(Path::new(&config_home).join("git").join("config"));
+    } else if let Some(dirs) = BaseDirs::new() {
+        files.push(dirs.home_dir().join(".config").join("git").join("config"));
+    }
+    if let Some(dirs) = BaseDirs::new() {
+        files.push(dirs.home_dir().join(".gitconfig"));
+    }
+    files
+}
+
+/// The global config file that `git config --global` writes to:
+/// `~/.gitconfig`, unless only the XDG config file exists.
+pub fn global_config_file() -> Result<PathBuf> {
+    let files = global_config_files();
+    files
+        .iter()
+        .rev()
+        .find(|file| file.exists())
+        .or_else(|| files.last())
+        .cloned()
+        .ok_or_else(|| eyre!("Could not find the home directory for the global git config"))
+}
+
+fn is_git_dir(dir: &Path) -> bool {
+    dir.join("HEAD").is_file() && dir.join("objects").is_dir() && dir.join("refs").is_dir()
+}
+
+/// Reads a `.git` file of a linked working tree or submodule, e.g. `gitdir: ../.git/worktrees/foo`.
+fn read_link_file(file: &Path, prefix: &str) -> Result<PathBuf> {
+    let content =
+        std::fs::read_to_string(file).wrap_err_with(|| format!("File: {}", file.display()))?;
+
+    content
+        .trim()
+        .strip_prefix(prefix)
+        .map(|path| PathBuf::from(path.trim()))
+        .ok_or_else(|| {
+            eyre!(
+                "Invalid git file {}: expected it to start with `{}`.",
+                file.display(),
+                prefix
+            )
+        })
+}
+
+/// Resolves `.` and `..` components without touching the file system.
+fn normalize(path: &Path) -> PathBuf {
+    let mut normalized = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if !normalized.pop() {
+                    normalized.push(component);
+                }
+            }
+            component => normalized.push(component),
+        }
+    }
+    normalized
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{
+        prelude::{FileTouch, FileWriteStr, PathChild, PathCreateDir},
+        TempDir,
+    };
+
+    #[test]
+    fn discover_main_worktree() {
+        let dir = TempDir::new().unwrap();
+        let git_dir = dir.child(".git");
+        git_dir.create_dir_all().unwrap();
+        let sub = dir.child("src");
+        sub.create_dir_all().unwrap();
+
+        let repo = Repository::discover_from(sub.path()).unwrap();
+        assert_eq!(repo.git_dir(), git_dir.path());
+        assert_eq!(repo.common_dir(), git_dir.path());
+        assert_eq!(repo.config_file(), git_dir.child("config").path());
+        assert_eq!(repo.work_tree(), Some(dir.path()));
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn discover_linked_worktree() {
+        let dir = TempDir::new().unwrap();
+        let common_dir = dir.child("main/.git");
+        let git_dir = common_dir.child("worktrees/other");
+        git_dir.create_dir_all().unwrap();
+        git_dir.child("commondir").write_str("../..\n").unwrap();
+        let worktree = dir.child("other");
+        worktree.create_dir_all().unwrap();
+        worktree
+            .child(".git")
+            .write_str("gitdir: ../main/.git/worktrees/other\n")
+            .unwrap();
+
+        let repo = Repository::discover_from(worktree.path()).unwrap();
+        assert_eq!(repo.git_dir(), git_dir.path());
+        assert_eq!(repo.common_dir(), common_dir.path());
+        assert_eq!(repo.config_file(), common_dir.child("config").path());
+        assert_eq!(
+            repo.worktree_config_file(),
+            git_dir.child("config.worktree").path()
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn discover_bare_repository() {
+        let dir = TempDir::new().unwrap();
+        dir.child("HEAD").touch().unwrap();
+        dir.child("objects").create_dir_all().unwrap();
+        dir.child("refs").create_dir_all().unwrap();
+
+        let repo = Repository::discover_from(dir.path()).unwrap();
+        assert_eq!(repo.git_dir(), dir.path());
+        assert_eq!(repo.work_tree(), None);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn checked_out_branch() {
+        let dir = TempDir::new().unwrap();
+        let head = dir.child(".git/HEAD");
+        head.write_str("ref: refs/heads/feature/ABC-123\n").unwrap();
+        let repo = Repository::discover_from(dir.path()).unwrap();
+
+        assert_eq!(repo.branch().unwrap().as_deref(), Some("feature/ABC-123"));
+
+        head.write_str("8d6b1b5bd6e0e1c3ba6a3c0ab5d4d9c8fb2fc9a3\n")
+            .unwrap();
+        assert_eq!(repo.branch().unwrap(), None);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn values_of_includes_take_the_place_of_the_include() {
+        let dir = TempDir::new().unwrap();
+        let git_dir = dir.child("work/repo/.git");
+        git_dir.create_dir_all().unwrap();
+        git_dir
+            .child("HEAD")
+            .write_str("ref: refs/heads/main\n")
+            .unwrap();
+        let repo = Repository::discover_from(git_dir.path()).unwrap();
+
+        dir.child("base.inc")
+            .write_str("[drive]\n\ttrailer = base\n[include]\n\tpath = nested.inc\n")
+            .unwrap();
+        dir.child("nested.inc")
+            .write_str("[drive]\n\ttrailer = nested\n")
+            .unwrap();
+        dir.child("work.inc")
+            .write_str("[drive]\n\ttrailer = work\n")
+            .unwrap();
+        dir.child("branch.inc")
+            .write_str("[drive]\n\ttrailer = branch\n")
+            .unwrap();
+        let config = dir.child("config");
+        config
+            .write_str(concat!(
+                "[drive]\n\ttrailer = first\n",
+                "[include]\n\tpath = base.inc\n",
+                "[includeIf \"gitdir:work/\"]\n\tpath = work.inc\n",
+                "[includeIf \"gitdir:private/\"]\n\tpath = private.inc\n",
+                "[includeIf \"onbranch:main\"]\n\tpath = branch.inc\n",
+                "[include]\n\tpath = missing.inc\n",
+                "[drive]\n\ttrailer = last\n",
+            ))
+            .unwrap();
+        let config = ConfigFile::open(config.path()).unwrap();
+
+        let mut values = Vec::new();
+        collect_values(&config, "drive.trailer", Some(&repo), 0, &mut values).unwrap();
+        assert_eq!(
+            values,
+            ["first", "base", "nested", "work", "branch", "last"]
+        );
+
+        let mut values = Vec::new();
+        collect_values(&config, "drive.trailer", None, 0, &mut values).unwrap();
+        assert_eq!(values, ["first", "base", "nested", "last"]);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn include_cycles_are_an_error() {
+        let dir = TempDir::new().unwrap();
+        let config = dir.child("config");
+        config.write_str("[include]\n\tpath = config\n").unwrap();
+        let config = ConfigFile::open(config.path()).unwrap();
+
+        let err = collect_values(&config, "drive.trailer", None, 0, &mut Vec::new()).unwrap_err();
+        assert!(
+            err.to_string()
+                .starts_with("Exceeded the maximum include depth of 10"),
+            "{}",
+            err
+        );
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn normalize_parent_components() {
+        let path = normalize(Path::new("/repo/.git/worktrees/other/../.."));
+        assert_eq!(path, Path::new("/repo/.git"));
+    }
+}
@@ -0,0 +1,1017 @@
+use eyre::{bail, eyre, Result, WrapErr};
+use std::{
+    fs::OpenOptions,
+    io::{ErrorKind, Write},
+    path::{Path, PathBuf},
+};
+
+/// A git config file that can be changed without calling `git config`.
+///
+/// Only the lines of the changed entries are touched,
+/// the formatting and comments of everything else is kept as is.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ConfigFile {
+    path: PathBuf,
+    lines: Vec<Line>,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Line {
+    /// The original text, including the line break.
+    text: String,
+    kind: LineKind,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum LineKind {
+    Section(Section),
+    Entry {
+        name: String,
+        value: Option<String>,
+    },
+    /// Blank lines, comments, and anything we do not understand.
+    Other,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Section {
+    name: String,
+    subsection: Option<String>,
+    /// Subsections of the deprecated `[section.subsection]` form are case-insensitive.
+    legacy: bool,
+}
+
+/// What is relevant for reading a key, in the order of the config file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Lookup<'a> {
+    /// A value of the key.
+    Value(&'a str),
+    /// An `include.path`, or an `includeIf.<condition>.path` with its condition.
+    Include {
+        condition: Option<&'a str>,
+        path: &'a str,
+    },
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Key<'a> {
+    section: &'a str,
+    subsection: Option<&'a str>,
+    name: &'a str,
+}
+
+impl ConfigFile {
+    /// Reads the config file, a missing file is treated as an empty one.
+    pub fn open(path: impl Into<PathBuf>) -> Result<Self> {
+        let path = path.into();
+        let content = match std::fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == ErrorKind::NotFound => String::new(),
+            Err(e) => return Err(eyre!(e).wrap_err(format!("File: {}", path.display()))),
+        };
+
+        let lines = parse(&content);
+        Ok(Self { path, lines })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the last value for the key, like `git config --get` does.
+    /// Entries without a value, i.e. a plain `key` line, are returned as `true`.
+    pub fn get(&self, key: &str) -> Result<Option<&str>> {
+        Ok(self.get_all(key)?.pop())
+    }
+
+    /// Returns all values for the key, like `git config --get-all` does.
+    pub fn get_all(&self, key: &str) -> Result<Vec<&str>> {
+        let key = Key::parse(key)?;
+        let values = self
+            .entries(key)
+            .map(|idx| match &self.lines[idx].kind {
+                LineKind::Entry { value, .. } => value.as_deref().unwrap_or("true"),
+                _ => unreachable!("entries only returns entry lines"),
+            })
+            .collect();
+        Ok(values)
+    }
+
+    /// Returns the values of the key together with the includes of the file, in file order,
+    /// so that the values of an included file can take the place of its include, like git does.
+    pub fn lookup(&self, key: &str) -> Result<Vec<Lookup<'_>>> {
+        let key = Key::parse(key)?;
+        let mut section = None;
+        let lookups = self
+            .lines
+            .iter()
+            .filter_map(|line| match &line.kind {
+                LineKind::Section(s) => {
+                    section = Some(s);
+                    None
+                }
+                LineKind::Entry { name, value } => {
+                    let s = section?;
+                    let value = value.as_deref().unwrap_or("true");
+                    if s.matches(key) && name.eq_ignore_ascii_case(key.name) {
+                        Some(Lookup::Value(value))
+                    } else if name == "path" && s.name.eq_ignore_ascii_case("include") {
+                        s.subsection.is_none().then_some(Lookup::Include {
+                            condition: None,
+                            path: value,
+                        })
+                    } else if name == "path" && s.name.eq_ignore_ascii_case("includeIf") {
+                        Some(Lookup::Include {
+                            condition: Some(s.subsection.as_deref()?),
+                            path: value,
+                        })
+                    } else {
+                        None
+                    }
+                }
+                LineKind::Other => None,
+            })
+            .collect();
+        Ok(lookups)
+    }
+
+    /// Returns the values of `<section>.<subsection>.<name>` of all subsections,
+    /// e.g. the URLs of all remotes.
+    pub fn subsection_values(&self, section: &str, name: &str) -> Vec<&str> {
+        let mut in_section = false;
+        self.lines
+            .iter()
+            .filter_map(|line| match &line.kind {
+                LineKind::Section(s) => {
+                    in_section = s.subsection.is_some() && s.name.eq_ignore_ascii_case(section);
+                    None
+                }
+                LineKind::Entry { name: n, value }
+                    if in_section && n.eq_ignore_ascii_case(name) =>
+                {
+                    Some(value.as_deref().unwrap_or("true"))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    /// Returns the keys and values of the section and all its subsections, in file order,
+    /// with the keys as `git config --get-regexp` prints them, e.g. `remote.origin.url`.
+    pub fn section_entries(&self, section: &str) -> Vec<(String, &str)> {
+        let mut current = None;
+        self.lines
+            .iter()
+            .filter_map(|line| match &line.kind {
+                LineKind::Section(s) => {
+                    current = s.name.eq_ignore_ascii_case(section).then_some(s);
+                    None
+                }
+                LineKind::Entry { name, value } => {
+                    let s = current?;
+                    // only subsections keep their case, unless they are of the legacy form
+                    let mut key = s.name.to_ascii_lowercase();
+                    match s.subsection.as_deref() {
+                        Some(sub) if s.legacy => key = format!("{}.{}", key, sub.to_lowercase()),
+                        Some(sub) => key = format!("{}.{}", key, sub),
+                        None => {}
+                    }
+                    let key = format!("{}.{}", key, name.to_ascii_lowercase());
+                    Some((key, value.as_deref().unwrap_or("true")))
+                }
+                LineKind::Other => None,
+            })
+            .collect()
+    }
+
+    /// Sets the value for the key, like `git config <key> <value>` does.
+    /// If the key has multiple values, only the last one is replaced.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        let key = Key::parse(key)?;
+        let entry = Line::entry(key, value);
+
+        if let Some(idx) = self.entries(key).last() {
+            self.lines[idx] = entry;
+            if idx > 0 {
+                // the entry might have been on the same line as the section header
+                ensure_line_break(&mut self.lines[idx - 1].text);
+            }
+            return Ok(());
+        }
+
+        self.insert(key, entry);
+        Ok(())
+    }
+
+    /// Adds another value for the key, like `git config --add <key> <value>` does.
+    pub fn add(&mut self, key: &str, value: &str) -> Result<()> {
+        let key = Key::parse(key)?;
+        self.insert(key, Line::entry(key, value));
+        Ok(())
+    }
+
+    /// Inserts the entry at the end of the last matching section,
+    /// or in a new section at the end of the file.
+    fn insert(&mut self, key: Key<'_>, entry: Line) {
+        let section_end = self
+            .lines
+            .iter()
+            .enumerate()
+            .rev()
+            .find(|(_, line)| matches!(&line.kind, LineKind::Section(s) if s.matches(key)))
+            .map(|(idx, _)| {
+                let entries = self.lines[idx + 1..]
+                    .iter()
+                    .take_while(|line| !matches!(line.kind, LineKind::Section(_)))
+                    .enumerate()
+                    .filter(|(_, line)| matches!(line.kind, LineKind::Entry { .. }))
+                    .map(|(offset, _)| offset + 1)
+                    .last()
+                    .unwrap_or(0);
+                idx + entries
+            });
+
+        if let Some(idx) = section_end {
+            ensure_line_break(&mut self.lines[idx].text);
+            self.lines.insert(idx + 1, entry);
+        } else {
+            if let Some(last) = self.lines.last_mut() {
+                ensure_line_break(&mut last.text);
+            }
+            let section = Section::from(key);
+            self.lines.push(Line {
+                text: format!("{}\n", section),
+                kind: LineKind::Section(section),
+            });
+            self.lines.push(entry);
+        }
+    }
+
+    /// Returns the names of all subsections of the section, e.g. the names of all remotes.
+    pub fn subsections(&self, section: &str) -> Vec<&str> {
+        let mut subsections = Vec::new();
+        for line in &self.lines {
+            if let LineKind::Section(s) = &line.kind {
+                if let Some(sub) = s.subsection.as_deref() {
+                    if s.name.eq_ignore_ascii_case(section) && !subsections.contains(&sub) {
+                        subsections.push(sub);
+                    }
+                }
+            }
+        }
+        subsections
+    }
+
+    /// Removes the section with all its entries, like `git config --remove-section` does.
+    /// Returns whether anything was removed.
+    pub fn remove_section(&mut self, name: &str) -> bool {
+        let (section, subsection) = match name.split_once('.') {
+            Some((section, subsection)) => (section, Some(subsection)),
+            None => (name, None),
+        };
+        let key = Key {
+            section,
+            subsection,
+            name: "",
+        };
+
+        let before = self.lines.len();
+        let mut in_section = false;
+        self.lines.retain(|line| {
+            if let LineKind::Section(s) = &line.kind {
+                in_section = s.matches(key);
+            }
+            !in_section
+        });
+        self.lines.len() != before
+    }
+
+    /// Removes all values of the key, like `git config --unset-all <key>` does.
+    /// Returns whether anything was removed.
+    pub fn unset(&mut self, key: &str) -> Result<bool> {
+        let key = Key::parse(key)?;
+        let entries = self.entries(key).collect::<Vec<_>>();
+        for &idx in entries.iter().rev() {
+            self.lines.remove(idx);
+            if idx > 0 {
+                ensure_line_break(&mut self.lines[idx - 1].text);
+            }
+        }
+        Ok(!entries.is_empty())
+    }
+
+    /// Writes the config file, using a `.lock` file like git does.
+    /// A symlinked config file is written through to its target and stays a symlink,
+    /// and the file keeps its permissions.
+    pub fn save(&self) -> Result<()> {
+        let target = resolve_symlinks(&self.path)?;
+        let mut lock_file = target.clone().into_os_string();
+        lock_file.push(".lock");
+        let lock_file = PathBuf::from(lock_file);
+
+        let mut file = match OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&lock_file)
+        {
+            Ok(file) => file,
+            Err(e) if e.kind() == ErrorKind::AlreadyExists => bail!(
+                concat!(
+                    "Could not lock the config file {}, because {} already exists.\n",
+                    "Another git process seems to be running. ",
+                    "If that is not the case, remove the lock file and try again."
+                ),
+                target.display(),
+                lock_file.display()
+            ),
+            Err(e) => return Err(eyre!(e).wrap_err(format!("File: {}", lock_file.display()))),
+        };
+
+        // the lock file is created with the default permissions, but config files may be private
+        let permissions = match std::fs::metadata(&target) {
+            Ok(metadata) => file.set_permissions(metadata.permissions()),
+            Err(e) if e.kind() == ErrorKind::NotFound => Ok(()),
+            Err(e) => Err(e),
+        };
+        let written = permissions
+            .and_then(|()| {
+                self.lines
+                    .iter()
+                    .try_for_each(|line| file.write_all(line.text.as_bytes()))
+            })
+            .and_then(|()| file.flush())
+            .and_then(|()| file.sync_all())
+            .and_then(|()| {
+                drop(file);
+                std::fs::rename(&lock_file, &target)
+            })
+            .wrap_err_with(|| format!("File: {}", target.display()));
+
+        if written.is_err() {
+            let _ = std::fs::remove_file(&lock_file);
+        }
+
+        written
+    }
+
+    fn entries<'a>(&'a self, key: Key<'a>) -> impl Iterator<Item = usize> + 'a {
+        let mut in_section = false;
+        self.lines
+            .iter()
+            .enumerate()
+            .filter_map(move |(idx, line)| match &line.kind {
+                LineKind::Section(section) => {
+                    in_section = section.matches(key);
+                    None
+                }
+                LineKind::Entry { name, .. }
+                    if in_section && name.eq_ignore_ascii_case(key.name) =>
+                {
+                    Some(idx)
+                }
+                _ => None,
+            })
+    }
+}
+
+impl Line {
+    fn entry(key: Key<'_>, value: &str) -> Self {
+        Self {
+            text: format!("\t{} = {}\n", key.name, quote(value)),
+            kind: LineKind::Entry {
+                name: key.name.to_ascii_lowercase(),
+                value: Some(value.to_string()),
+            },
+        }
+    }
+}
+
+impl<'a> Key<'a> {
+    /// Splits `section.subsection.name`, where the subsection may contain dots.
+    fn parse(key: &'a str) -> Result<Self> {
+        let (section, rest) = key
+            .split_once('.')
+            .ok_or_else(|| eyre!("The config key `{}` does not contain a section.", key))?;
+        let (subsection, name) = match rest.rsplit_once('.') {
+            Some((subsection, name)) => (Some(subsection), name),
+            None => (None, rest),
+        };
+        if section.is_empty() || name.is_empty() {
+            bail!("The config key `{}` is invalid.", key);
+        }
+        Ok(Self {
+            section,
+            subsection,
+            name,
+        })
+    }
+}
+
+impl Section {
+    fn matches(&self, key: Key<'_>) -> bool {
+        self.name.eq_ignore_ascii_case(key.section)
+            && match (self.subsection.as_deref(), key.subsection) {
+                (None, None) => true,
+                (Some(sub), Some(key)) if self.legacy => sub.eq_ignore_ascii_case(key),
+                (Some(sub), Some(key)) => sub == key,
+                _ => false,
+            }
+    }
+}
+
+impl From<Key<'_>> for Section {
+    fn from(key: Key<'_>) -> Self {
+        Self {
+            name: key.section.to_string(),
+            subsection: key.subsection.map(String::from),
+            legacy: false,
+        }
+    }
+}
+
+impl std::fmt::Display for Section {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.subsection {
+            Some(sub) => {
+                let sub = sub.replace('\\', r"\\").replace('"', "\\\"");
+                write!(f, "[{} \"{}\"]", self.name, sub)
+            }
+            None => write!(f, "[{}]", self.name),
+        }
+    }
+}
+
+/// Follows the symlinks of the path, like git does before locking a file.
+/// The target does not need to exist.
+fn resolve_symlinks(path: &Path) -> Result<PathBuf> {
+    const MAX_DEPTH: usize = 5;
+
+    let mut path = path.to_path_buf();
+    for _ in 0..MAX_DEPTH {
+        match std::fs::symlink_metadata(&path) {
+            Ok(meta) if meta.file_type().is_symlink() => {
+                let link = std::fs::read_link(&path)
+                    .wrap_err_with(|| format!("File: {}", path.display()))?;
+                path = match path.parent() {
+                    Some(dir) => dir.join(link),
+                    None => link,
+                };
+            }
+            Ok(_) => return Ok(path),
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(path),
+            Err(e) => return Err(eyre!(e).wrap_err(format!("File: {}", path.display()))),
+        }
+    }
+
+    bail!(
+        "Could not resolve the config file {}, it has more than {} levels of symlinks.",
+        path.display(),
+        MAX_DEPTH
+    )
+}
+
+fn ensure_line_break(text: &mut String) {
+    if !text.ends_with('\n') {
+        text.push('\n');
+    }
+}
+
+fn quote(value: &str) -> String {
+    let needs_quotes = value.starts_with(char::is_whitespace)
+        || value.ends_with(char::is_whitespace)
+        || value.contains(['#', ';']);
+
+    let mut quoted = String::with_capacity(value.len() + 2);
+    if needs_quotes {
+        quoted.push('"');
+    }
+    for c in value.chars() {
+        match c {
+            '\\' => quoted.push_str(r"\\"),
+            '"' => quoted.push_str("\\\""),
+            '\n' => quoted.push_str(r"\n"),
+            '\t' => quoted.push_str(r"\t"),
+            c => quoted.push(c),
+        }
+    }
+    if needs_quotes {
+        quoted.push('"');
+    }
+    quoted
+}
+
+fn parse(content: &str) -> Vec<Line> {
+    let mut lines = Vec::new();
+    let mut physical = content.split_inclusive('\n');
+
+    while let Some(text) = physical.next() {
+        let trimmed = text.trim_start();
+        if trimmed.starts_with('[') {
+            match parse_section(trimmed) {
+                Some((section, rest)) => {
+                    let header_len = text.len() - rest.len();
+                    lines.push(Line {
+                        text: text[..header_len].to_string(),
+                        kind: LineKind::Section(section),
+                    });
+                    if rest.trim().is_empty() || rest.trim_start().starts_with(['#', ';']) {
+                        lines.last_mut().unwrap().text.push_str(rest);
+                    } else {
+                        // an entry on the same line as the section header
+                        lines.push(parse_entry(rest, &mut physical));
+                    }
+                }
+                None => lines.push(Line {
+                    text: text.to_string(),
+                    kind: LineKind::Other,
+                }),
+            }
+        } else if trimmed.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            lines.push(parse_entry(text, &mut physical));
+        } else {
+            lines.push(Line {
+                text: text.to_string(),
+                kind: LineKind::Other,
+            });
+        }
+    }
+
+    lines
+}
+
+/// Parses `[section]`, `[section "subsection"]`, or `[section.subsection]`,
+/// returning the section and the rest of the line after the closing bracket.
+fn parse_section(line: &str) -> Option<(Section, &str)> {
+    let line = line.strip_prefix('[')?;
+    let name_end = line.find(|c: char| !(c.is_ascii_alphanumeric() || c == '-' || c == '.'))?;
+    let (name, rest) = line.split_at(name_end);
+
+    if let Some(rest) = rest.strip_prefix(']') {
+        let (name, subsection) = match name.split_once('.') {
+            Some((name, sub)) => (name, Some(sub.to_string())),
+            None => (name, None),
+        };
+        let section = Section {
+            name: name.to_string(),
+            legacy: subsection.is_some(),
+            subsection,
+        };
+        return Some((section, rest));
+    }
+
+    let rest = rest.trim_start().strip_prefix('"')?;
+    let mut subsection = String::new();
+    let mut chars = rest.char_indices();
+    while let Some((_, c)) = chars.next() {
+        match c {
+            '\\' => subsection.push(chars.next()?.1),
+            '"' => break,
+            '\n' => return None,
+            c => subsection.push(c),
+        }
+    }
+    let rest = chars.as_str().strip_prefix(']')?;
+    let section = Section {
+        name: name.to_string(),
+        subsection: Some(subsection),
+        legacy: false,
+    };
+    Some((section, rest))
+}
+
+/// Parses `name = value`, consuming more lines if the value continues with a trailing `\`.
+fn parse_entry<'a>(first: &'a str, physical: &mut impl Iterator<Item = &'a str>) -> Line {
+    let mut text = first.to_string();
+    let trimmed = first.trim_start();
+    let name_end = trimmed
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+        .unwrap_or(trimmed.len());
+    let (name, rest) = trimmed.split_at(name_end);
+    let name = name.to_ascii_lowercase();
+
+    let rest = rest.trim_start_matches([' ', '\t']);
+    let mut rest = match rest.strip_prefix('=') {
+        Some(rest) => rest.to_string(),
+        None => {
+            return Line {
+                text,
+                kind: LineKind::Entry { name, value: None },
+            }
+        }
+    };
+
+    let mut value = ValueParser::default();
+    while value.parse_line(&rest) {
+        match physical.next() {
+            Some(next) => {
+                text.push_str(next);
+                rest = next.to_string();
+            }
+            None => break,
+        }
+    }
+
+    Line {
+        text,
+        kind: LineKind::Entry {
+            name,
+            value: Some(value.value),
+        },
+    }
+}
+
+#[derive(Debug, Default)]
+struct ValueParser {
+    value: String,
+    quoted: bool,
+    /// Unquoted whitespace is only kept if something follows it.
+    pending_space: String,
+}
+
+impl ValueParser {
+    /// Appends the value of one physical line, handling quotes, escapes, and comments.
+    /// Returns whether the value continues on the next line.
+    fn parse_line(&mut self, line: &str) -> bool {
+        let mut chars = line.chars();
+        while let Some(c) = chars.next() {
+            match c {
+                '\n' | '\r' | '#' | ';' if !self.quoted => break,
+                '"' => self.quoted = !self.quoted,
+                '\\' => {
+                    let escaped = match chars.next() {
+                        None | Some('\n') => return true,
+                        Some('\r') if chars.as_str() == "\n" => return true,
+                        Some('n') => '\n',
+                        Some('t') => '\t',
+                        Some('b') => '\u{8}',
+                        Some(c) => c,
+                    };
+                    self.push(escaped);
+                }
+                c if c.is_whitespace() && !self.quoted => {
+                    if !self.value.is_empty() {
+                        self.pending_space.push(c);
+                    }
+                }
+                c => self.push(c),
+            }
+        }
+
+        false
+    }
+
+    fn push(&mut self, c: char) {
+        self.value.push_str(&self.pending_space);
+        self.pending_space.clear();
+        self.value.push(c);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use assert_fs::{
+        prelude::{FileWriteStr, PathChild},
+        TempDir,
+    };
+
+    fn config(content: &str) -> ConfigFile {
+        ConfigFile {
+            path: PathBuf::new(),
+            lines: parse(content),
+        }
+    }
+
+    fn content(config: &ConfigFile) -> String {
+        config.lines.iter().map(|l| l.text.as_str()).collect()
+    }
+
+    const CONFIG: &str = r#"# a comment
+[core]
+	repositoryformatversion = 0
+	bare = false ; trailing comment
+[remote "origin"]
+	url = git@github.com:knutwalker/git-drive.git
+	fetch = +refs/heads/*:refs/remotes/origin/*
+
+[alias]
+	lg = "log --oneline # not a comment"
+	long = one \
+two
+"#;
+
+    #[test]
+    fn parse_keeps_the_content() {
+        assert_eq!(content(&config(CONFIG)), CONFIG);
+    }
+
+    #[test]
+    fn get_values() {
+        let config = config(CONFIG);
+        assert_eq!(config.get("core.bare").unwrap(), Some("false"));
+        assert_eq!(
+            config.get("remote.origin.url").unwrap(),
+            Some("git@github.com:knutwalker/git-drive.git")
+        );
+        assert_eq!(
+            config.get("alias.lg").unwrap(),
+            Some("log --oneline # not a comment")
+        );
+        assert_eq!(config.get("alias.long").unwrap(), Some("one two"));
+        assert_eq!(
+            config.get("Core.RepositoryFormatVersion").unwrap(),
+            Some("0")
+        );
+        assert_eq!(config.get("REMOTE.origin.url").unwrap().is_some(), true);
+        assert_eq!(config.get("remote.ORIGIN.url").unwrap(), None);
+        assert_eq!(config.get("commit.template").unwrap(), None);
+    }
+
+    #[test]
+    fn get_subsection_values() {
+        let config = config(concat!(
+            "[remote \"origin\"]\n\turl = a\n",
+            "[remote]\n\turl = b\n",
+            "[remote \"upstream\"]\n\turl = c\n",
+        ));
+        assert_eq!(config.subsection_values("remote", "url"), ["a", "c"]);
+    }
+
+    #[test]
+    fn get_all_values() {
+        let config = config("[drive]\n\ttrailer = a\n[core]\n\tbare\n[drive]\n\ttrailer = b\n");
+        assert_eq!(config.get_all("drive.trailer").unwrap(), ["a", "b"]);
+        assert_eq!(config.get("drive.trailer").unwrap(), Some("b"));
+    }
+
+    #[test]
+    fn get_implicit_true() {
+        let config = config("[core]\n\tbare\n");
+        assert_eq!(config.get("core.bare").unwrap(), Some("true"));
+    }
+
+    #[test]
+    fn get_legacy_subsection() {
+        let config = config("[remote.Origin]\n\turl = foo\n");
+        assert_eq!(config.get("remote.origin.url").unwrap(), Some("foo"));
+    }
+
+    #[test]
+    fn get_inline_entry() {
+        let config = config("[core] bare = true\n");
+        assert_eq!(config.get("core.bare").unwrap(), Some("true"));
+    }
+
+    #[test]
+    fn get_requires_a_section() {
+        let err = config(CONFIG).get("template").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "The config key `template` does not contain a section."
+        );
+    }
+
+    #[test]
+    fn set_replaces_existing_value() {
+        let mut config = config(CONFIG);
+        config.set("core.bare", "true").unwrap();
+
+        let expected = CONFIG.replace("\tbare = false ; trailing comment\n", "\tbare = true\n");
+        assert_eq!(content(&config), expected);
+    }
+
+    #[test]
+    fn set_appends_to_existing_section() {
+        let mut config = config(CONFIG);
+        config.set("remote.origin.pushurl", "foo").unwrap();
+
+        let expected = CONFIG.replace("origin/*\n", "origin/*\n\tpushurl = foo\n");
+        assert_eq!(content(&config), expected);
+    }
+
+    #[test]
+    fn set_adds_a_new_section() {
+        let mut config = config(CONFIG);
+        config
+            .set("commit.template", r"C:\repo\.git\template")
+            .unwrap();
+
+        let expected = format!(
+            "{}[commit]\n\ttemplate = C:\\\\repo\\\\.git\\\\template\n",
+            CONFIG
+        );
+        assert_eq!(content(&config), expected);
+        assert_eq!(
+            config.get("commit.template").unwrap(),
+            Some(r"C:\repo\.git\template")
+        );
+    }
+
+    #[test]
+    fn set_adds_a_new_subsection() {
+        let mut config = config("[core]\n\tbare = false");
+        config
+            .set("drive.navigator.first.last.name", "Bernd")
+            .unwrap();
+
+        assert_eq!(
+            content(&config),
+            "[core]\n\tbare = false\n[drive \"navigator.first.last\"]\n\tname = Bernd\n"
+        );
+    }
+
+    #[test]
+    fn set_quotes_special_values() {
+        let mut config = config("");
+        config.set("alias.x", " # \"quoted\" ").unwrap();
+
+        assert_eq!(content(&config), "[alias]\n\tx = \" # \\\"quoted\\\" \"\n");
+        assert_eq!(config.get("alias.x").unwrap(), Some(" # \"quoted\" "));
+    }
+
+    #[test]
+    fn set_inline_entry() {
+        let mut config = config("[core] bare = false\n");
+        config.set("core.bare", "true").unwrap();
+
+        assert_eq!(content(&config), "[core]\n\tbare = true\n");
+    }
+
+    #[test]
+    fn section_entries_with_their_keys() {
+        let config = config(concat!(
+            "[Drive \"navigator.Nav1\"]\n\tName = bernd\n\tarchived\n",
+            "[user]\n\tname = ralle\n",
+            "[drive.Legacy]\n\tname = ronny\n",
+        ));
+        assert_eq!(
+            config.section_entries("drive"),
+            [
+                (String::from("drive.navigator.Nav1.name"), "bernd"),
+                (String::from("drive.navigator.Nav1.archived"), "true"),
+                (String::from("drive.legacy.name"), "ronny"),
+            ]
+        );
+    }
+
+    #[test]
+    fn unset_removes_all_values() {
+        let mut config =
+            config("[commit]\n\ttemplate = a\n[user]\n\tname = b\n[commit]\n\ttemplate = c\n");
+        assert!(config.unset("commit.template").unwrap());
+        assert!(!config.unset("commit.template").unwrap());
+
+        assert_eq!(content(&config), "[commit]\n[user]\n\tname = b\n[commit]\n");
+    }
+
+    #[test]
+    fn add_appends_another_value() {
+        let mut config = config("[drive]\n\ttrailer = a\n[core]\n\tbare = false\n");
+        config.add("drive.trailer", "b").unwrap();
+        config
+            .add("drive.navigator.nav1.aliases", "bernie")
+            .unwrap();
+
+        assert_eq!(
+            content(&config),
+            concat!(
+                "[drive]\n\ttrailer = a\n\ttrailer = b\n[core]\n\tbare = false\n",
+                "[drive \"navigator.nav1\"]\n\taliases = bernie\n",
+            )
+        );
+        assert_eq!(config.get_all("drive.trailer").unwrap(), ["a", "b"]);
+    }
+
+    #[test]
+    fn remove_section_and_its_entries() {
+        let mut config = config(concat!(
+            "[drive \"navigator.nav1\"]\n\tname = bernd\n",
+            "[core]\n\tbare = false\n",
+            "[drive \"navigator.nav1\"]\n\temail = foo@bar.org\n",
+            "[drive \"navigator.nav2\"]\n\tname = ronny\n",
+        ));
+        assert_eq!(
+            config.subsections("drive"),
+            ["navigator.nav1", "navigator.nav2"]
+        );
+
+        assert!(config.remove_section("drive.navigator.nav1"));
+        assert!(!config.remove_section("drive.navigator.nav1"));
+
+        assert_eq!(
+            content(&config),
+            "[core]\n\tbare = false\n[drive \"navigator.nav2\"]\n\tname = ronny\n"
+        );
+    }
+
+    #[test]
+    fn unset_continued_value() {
+        let mut config = config(CONFIG);
+        assert!(config.unset("alias.long").unwrap());
+
+        let expected = CONFIG.replace("\tlong = one \\\ntwo\n", "");
+        assert_eq!(content(&config), expected);
+    }
+
+    #[test]
+    fn open_missing_file_and_save() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("config");
+
+        let mut config = ConfigFile::open(file.path()).unwrap();
+        config.set("commit.template", "foo").unwrap();
+        config.save().unwrap();
+
+        let content = std::fs::read_to_string(file.path()).unwrap();
+        assert_eq!(content, "[commit]\n\ttemplate = foo\n");
+        assert!(!dir.child("config.lock").path().exists());
+
+        dir.close().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_keeps_a_symlinked_file() {
+        let dir = TempDir::new().unwrap();
+        let target = dir.child("dotfiles/gitconfig");
+        target.write_str("[user]\n\tname = bernd\n").unwrap();
+        let link = dir.child(".gitconfig");
+        std::os::unix::fs::symlink("dotfiles/gitconfig", link.path()).unwrap();
+
+        let mut config = ConfigFile::open(link.path()).unwrap();
+        config.set("commit.template", "foo").unwrap();
+        config.save().unwrap();
+
+        let meta = std::fs::symlink_metadata(link.path()).unwrap();
+        assert!(meta.file_type().is_symlink());
+        let content = std::fs::read_to_string(target.path()).unwrap();
+        assert_eq!(
+            content,
+            "[user]\n\tname = bernd\n[commit]\n\ttemplate = foo\n"
+        );
+        assert!(!dir.child("dotfiles/gitconfig.lock").path().exists());
+
+        dir.close().unwrap();
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn save_keeps_the_permissions() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = TempDir::new().unwrap();
+        let file = dir.child(".gitconfig");
+        file.write_str("[user]\n\tname = bernd\n").unwrap();
+        std::fs::set_permissions(file.path(), std::fs::Permissions::from_mode(0o600)).unwrap();
+
+        let mut config = ConfigFile::open(file.path()).unwrap();
+        config.set("commit.template", "foo").unwrap();
+        config.save().unwrap();
+
+        let mode = std::fs::metadata(file.path()).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o600);
+
+        dir.close().unwrap();
+    }
+
+    #[test]
+    fn lookup_returns_values_and_includes_in_order() {
+        let config = config(concat!(
+            "[drive]\n\ttrailer = a\n",
+            "[include]\n\tpath = shared\n",
+            "[includeIf \"gitdir:~/work/\"]\n\tpath = work\n",
+            "[drive]\n\ttrailer = b\n",
+        ));
+        assert_eq!(
+            config.lookup("drive.trailer").unwrap(),
+            [
+                Lookup::Value("a"),
+                Lookup::Include {
+                    condition: None,
+                    path: "shared"
+                },
+                Lookup::Include {
+                    condition: Some("gitdir:~/work/"),
+                    path: "work"
+                },
+                Lookup::Value("b"),
+            ]
+        );
+    }
+
+    #[test]
+    fn save_fails_if_locked() {
+        let dir = TempDir::new().unwrap();
+        let file = dir.child("config");
+        dir.child("config.lock").write_str("").unwrap();
+
+        let config = ConfigFile::open(file.path()).unwrap();
+        let err = config.save().unwrap_err();
+
+        assert!(err
+            .to_string()
+            .starts_with("Could not lock the config file"));
+
+        dir.close().unwrap();
+    }
+}
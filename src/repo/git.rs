@@ -0,0 +1,99 @@
+use eyre::{eyre, Result, WrapErr};
+use std::{
+    ffi::OsStr,
+    io::{ErrorKind, Write},
+    process::{Command as Proc, Stdio},
+};
+
+/// A call to `git` for the things that cannot reasonably be done in-process,
+/// such as walking the history or writing commits.
+#[derive(Debug)]
+pub struct Git {
+    cmd: Proc,
+    input: Option<String>,
+}
+
+impl Git {
+    pub fn new<I, S>(args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        let mut cmd = Proc::new("git");
+        cmd.args(args);
+        Self { cmd, input: None }
+    }
+
+    pub fn arg(mut self, arg: impl AsRef<OsStr>) -> Self {
+        self.cmd.arg(arg);
+        self
+    }
+
+    pub fn args<I, S>(mut self, args: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<OsStr>,
+    {
+        self.cmd.args(args);
+        self
+    }
+
+    pub fn env(mut self, key: &str, value: &str) -> Self {
+        self.cmd.env(key, value);
+        self
+    }
+
+    /// Writes the input to the stdin of git.
+    pub fn input(mut self, input: impl Into<String>) -> Self {
+        self.input = Some(input.into());
+        self
+    }
+
+    /// Runs git and returns its stdout, failing if git fails.
+    pub fn run(self) -> Result<String> {
+        self.run_allowing(&[0]).map(|(_, stdout)| stdout)
+    }
+
+    /// Runs git and returns its exit code and stdout,
+    /// failing if git exits with a code that is not allowed.
+    pub fn run_allowing(mut self, allowed: &[i32]) -> Result<(i32, String)> {
+        let description = format!("{:?}", self.cmd);
+        self.cmd
+            .stdin(if self.input.is_some() {
+                Stdio::piped()
+            } else {
+                Stdio::null()
+            })
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped());
+
+        let mut child = self.cmd.spawn().map_err(|e| match e.kind() {
+            ErrorKind::NotFound => {
+                eyre!("Could not find `git`, please make sure it is on the PATH.")
+            }
+            _ => e.into(),
+        })?;
+
+        if let (Some(input), Some(mut stdin)) = (self.input, child.stdin.take()) {
+            stdin
+                .write_all(input.as_bytes())
+                .wrap_err_with(|| format!("Could not write to {}", description))?;
+        }
+
+        let output = child.wait_with_output()?;
+        match output.status.code() {
+            Some(code) if allowed.contains(&code) => {
+                let stdout = String::from_utf8(output.stdout).wrap_err_with(|| {
+                    format!("The output of {} is not valid UTF-8", description)
+                })?;
+                Ok((code, stdout))
+            }
+            _ => Err(eyre!(
+                "Calling {} failed with {}\nStderr: {}",
+                description,
+                output.status,
+                String::from_utf8_lossy(&output.stderr).trim()
+            )),
+        }
+    }
+}
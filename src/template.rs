@@ -0,0 +1,238 @@
+use crate::repo::{self, Repository};
+use directories::BaseDirs;
+use eyre::{bail, eyre, Result, WrapErr};
+use regex::Regex;
+use std::path::{Path, PathBuf};
+
+/// The template that is used if `drive.template` is not configured.
+const DEFAULT_TEMPLATE: &str = "\n\n{{co_authors}}\n";
+
+/// Matches ticket ids like `ABC-123`, used if `drive.ticketPattern` is not configured.
+const DEFAULT_TICKET_PATTERN: &str = r"[A-Z][A-Z0-9]+-\d+";
+
+const PLACEHOLDERS: [&str; 4] = ["co_authors", "driver", "branch", "ticket"];
+
+/// A commit template with placeholders in the form of `{{name}}`.
+#[derive(Clone, Debug)]
+pub struct Template {
+    source: Option<PathBuf>,
+    content: String,
+    ticket_pattern: Regex,
+}
+
+/// The values for the placeholders.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Values<'a> {
+    /// The trailer lines for the navigators.
+    pub co_authors: &'a [String],
+    /// The current git user, as `Name <email>`.
+    pub driver: Option<String>,
+    /// The name of the checked out branch.
+    pub branch: Option<String>,
+}
+
+impl Template {
+    /// Loads the template configured in `drive.template` and
+    /// the pattern for ticket ids configured in `drive.ticketPattern`.
+    /// Without a repository, only the global config is used.
+    pub fn load(repo: Option<&Repository>) -> Result<Self> {
+        let ticket_pattern = repo::config_value(repo, "drive.ticketPattern")?
+            .unwrap_or_else(|| String::from(DEFAULT_TICKET_PATTERN));
+        let ticket_pattern = Regex::new(&ticket_pattern).wrap_err_with(|| {
+            format!(
+                "The ticket pattern `{}` in `drive.ticketPattern` is invalid.",
+                ticket_pattern
+            )
+        })?;
+
+        let source = repo::config_value(repo, "drive.template")?
+            .map(|file| template_path(&file, repo.and_then(Repository::work_tree)))
+            .transpose()?;
+
+        let content = source
+            .as_deref()
+            .map(|source| {
+                std::fs::read_to_string(source).wrap_err_with(|| {
+                    format!("Could not read the commit template {}", source.display())
+                })
+            })
+            .transpose()?
+            .unwrap_or_else(|| String::from(DEFAULT_TEMPLATE));
+
+        Ok(Self {
+            source,
+            content,
+            ticket_pattern,
+        })
+    }
+
+    /// Replaces all placeholders. Placeholders without a value are replaced with nothing.
+    pub fn render(&self, values: &Values<'_>) -> Result<String> {
+        let ticket = values.branch.as_deref().and_then(|b| self.ticket(b));
+
+        let mut rendered = String::with_capacity(self.content.len() + 64);
+        let mut rest = self.content.as_str();
+        while let Some(start) = rest.find("{{") {
+            rendered.push_str(&rest[..start]);
+            let placeholder = &rest[start + 2..];
+            let end = placeholder
+                .find("}}")
+                .ok_or_else(|| self.error("An opened placeholder `{{` is never closed"))?;
+            let name = placeholder[..end].trim();
+            match name {
+                "co_authors" => rendered.push_str(&values.co_authors.join("\n")),
+                "driver" => rendered.push_str(values.driver.as_deref().unwrap_or_default()),
+                "branch" => rendered.push_str(values.branch.as_deref().unwrap_or_default()),
+                "ticket" => rendered.push_str(ticket.unwrap_or_default()),
+                otherwise => {
+                    return Err(self.error(&format!(
+                        "Unknown placeholder `{{{{{}}}}}`, expected one of {}",
+                        otherwise,
+                        PLACEHOLDERS
+                            .iter()
+                            .map(|p| format!("`{{{{{}}}}}`", p))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    )))
+                }
+            }
+            rest = &placeholder[end + 2..];
+        }
+        rendered.push_str(rest);
+
+        Ok(rendered)
+    }
+
+    /// The first capture group of the ticket pattern, or the whole match if there is no group.
+    fn ticket<'b>(&self, branch: &'b str) -> Option<&'b str> {
+        let captures = self.ticket_pattern.captures(branch)?;
+        captures
+            .get(1)
+            .or_else(|| captures.get(0))
+            .map(|m| m.as_str())
+    }
+
+    fn error(&self, msg: &str) -> eyre::Report {
+        self.source.as_ref().map_or_else(
+            || eyre!("{}.", msg),
+            |source| eyre!("{} in the commit template {}.", msg, source.display()),
+        )
+    }
+}
+
+/// Expands `~` and resolves relative paths against the working tree,
+/// so that a template can be committed to the repository.
+fn template_path(file: &str, work_tree: Option<&Path>) -> Result<PathBuf> {
+    if let Some(rest) = file.strip_prefix('~') {
+        let dirs = BaseDirs::new().ok_or_else(|| eyre!("Could not find the home directory"))?;
+        return Ok(dirs.home_dir().join(rest.trim_start_matches(['/', '\\'])));
+    }
+
+    let path = PathBuf::from(file);
+    match work_tree {
+        _ if path.is_absolute() => Ok(path),
+        Some(work_tree) => Ok(work_tree.join(path)),
+        None => bail!(
+            "The commit template `{}` is a relative path, but there is no working tree.",
+            file
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(content: &str) -> Template {
+        Template {
+            source: None,
+            content: String::from(content),
+            ticket_pattern: Regex::new(DEFAULT_TICKET_PATTERN).unwrap(),
+        }
+    }
+
+    #[test]
+    fn default_template() {
+        let co_authors = [
+            String::from("Co-Authored-By: bernd <foo@bar.org>"),
+            String::from("Co-Authored-By: ralle <qux@bar.org>"),
+        ];
+        let values = Values {
+            co_authors: &co_authors,
+            ..Values::default()
+        };
+
+        let rendered = template(DEFAULT_TEMPLATE).render(&values).unwrap();
+        assert_eq!(
+            rendered,
+            "\n\nCo-Authored-By: bernd <foo@bar.org>\nCo-Authored-By: ralle <qux@bar.org>\n"
+        );
+    }
+
+    #[test]
+    fn render_all_placeholders() {
+        let co_authors = [String::from("Co-Authored-By: bernd <foo@bar.org>")];
+        let values = Values {
+            co_authors: &co_authors,
+            driver: Some(String::from("ronny <ronny@bar.org>")),
+            branch: Some(String::from("feature/ABC-123-fix-the-thing")),
+        };
+
+        let rendered =
+            template("[{{ ticket }}] \n\nOn {{branch}} by {{driver}}\n\n{{co_authors}}\n")
+                .render(&values)
+                .unwrap();
+        assert_eq!(
+            rendered,
+            concat!(
+                "[ABC-123] \n\n",
+                "On feature/ABC-123-fix-the-thing by ronny <ronny@bar.org>\n\n",
+                "Co-Authored-By: bernd <foo@bar.org>\n"
+            )
+        );
+    }
+
+    #[test]
+    fn missing_values_render_empty() {
+        let rendered = template("{{ticket}}|{{branch}}|{{driver}}|{{co_authors}}")
+            .render(&Values::default())
+            .unwrap();
+        assert_eq!(rendered, "|||");
+    }
+
+    #[test]
+    fn ticket_from_capture_group() {
+        let mut template = template("{{ticket}}");
+        template.ticket_pattern = Regex::new(r"^(?:feature|bugfix)/(\d+)").unwrap();
+
+        let values = Values {
+            branch: Some(String::from("feature/42-answer")),
+            ..Values::default()
+        };
+        assert_eq!(template.render(&values).unwrap(), "42");
+    }
+
+    #[test]
+    fn unknown_placeholder() {
+        let err = template("{{foo}}").render(&Values::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Unknown placeholder `{{foo}}`, expected one of `{{co_authors}}`, `{{driver}}`, `{{branch}}`, `{{ticket}}`."
+        );
+    }
+
+    #[test]
+    fn unclosed_placeholder() {
+        let err = template("{{ticket").render(&Values::default()).unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "An opened placeholder `{{` is never closed."
+        );
+    }
+
+    #[test]
+    fn relative_template_path() {
+        let path = template_path(".github/commit-template", Some(Path::new("/repo"))).unwrap();
+        assert_eq!(path, Path::new("/repo").join(".github/commit-template"));
+    }
+}
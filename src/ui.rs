@@ -2,12 +2,19 @@
 
 use crate::{
     config::Config,
-    data::{Driver, Field, Id, IdRef, Kind, Navigator, PartialIdNav, PartialNav},
+    data::{
+        join_emails, parse_aliases, parse_emails, Driver, Email, Field, Id, IdRef, KeyFormat, Kind,
+        Navigator, PartialIdNav, PartialNav,
+    },
+    repo::{self, Repository},
     ui::validation::AndThen,
 };
-use eyre::Result;
-use validation::{CheckForEmpty, Lookup, Validator};
+use console::style;
+use eyre::{bail, eyre, Result};
+use validation::{CheckForEmpty, EmailList, Lookup, SigningKey, Validator};
 
+mod no_input;
+mod plain;
 mod tui;
 mod validation;
 
@@ -35,10 +42,37 @@ pub trait PromptAlias {
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub struct Selectable<'a> {
+    /// The alias, which is also used to select the item by typing it.
     pub item: &'a str,
+    pub name: &'a str,
+    pub email: &'a str,
+    /// Checked items are the members of the current session.
     pub checked: bool,
 }
 
+impl Selectable<'_> {
+    /// The alias, padded to `width`, followed by the identity,
+    /// and marked if it is a member of the current session.
+    pub fn label(&self, width: usize) -> String {
+        format!(
+            "{:<width$} — {} <{}>{}",
+            self.item,
+            self.name,
+            self.email,
+            if self.checked { " (current)" } else { "" },
+        )
+    }
+
+    /// The width of the longest alias, so that the identities line up.
+    pub fn alias_width(items: &[Self]) -> usize {
+        items
+            .iter()
+            .map(|item| item.item.chars().count())
+            .max()
+            .unwrap_or_default()
+    }
+}
+
 pub fn complete_new_nav(
     mut ui: impl PromptAlias + PromptText,
     partial: PartialNav,
@@ -71,6 +105,29 @@ pub fn complete_existing_drv(
     ui.complete_existing_drv(partial, config)
 }
 
+/// Prompts for the signing key of a driver, empty if there is none.
+pub fn complete_key(
+    mut ui: impl PromptText,
+    id: &Id,
+    key: Option<String>,
+    key_format: KeyFormat,
+) -> Result<Option<String>> {
+    ui.prompt_for_key(id, key, key_format)
+}
+
+/// Validates the new alias for renaming an entry, or prompts for it if it is missing.
+pub fn complete_new_alias(
+    mut ui: impl PromptAlias + PromptText,
+    kind: Kind,
+    config: &Config,
+    alias: Option<String>,
+) -> Result<Id> {
+    Ok(match kind {
+        Kind::Navigator => ui.prompt_alias::<NavigatorSeat>(config, alias)?.0,
+        Kind::Driver => ui.prompt_alias::<DriverSeat>(config, alias)?.0,
+    })
+}
+
 pub fn select_id_from(
     mut ui: impl SelectOne,
     kind: Kind,
@@ -88,8 +145,145 @@ pub fn select_ids_from<'config>(
     ui.select_ids_from(kind, config, pre_selected)
 }
 
-pub fn ui() -> impl SelectOne + SelectMany + PromptText + PromptAlias + Sized {
-    tui::ConsoleUi
+/// Prompts on the console, unless `no_input` is set or stdin is not a terminal,
+/// in which case missing input is an error.
+///
+/// The prompts are line-based if `drive.prompt` is set to `plain`,
+/// or if it is not set and the terminal is dumb.
+/// The style is only looked up once something is prompted for.
+pub fn ui(no_input: bool) -> impl SelectOne + SelectMany + PromptText + PromptAlias + Sized {
+    if no_input || !stdin_is_terminal() {
+        Ui::NoInput(no_input::NoInputUi)
+    } else {
+        Ui::Unresolved
+    }
+}
+
+/// The interactive prompts in the style of `drive.prompt`.
+fn prompt_style() -> Result<Ui> {
+    let repo = Repository::discover().ok();
+    let dumb_terminal = || std::env::var_os("TERM").map_or(false, |term| term == "dumb");
+    // an invalid style must not stop the commands that never prompt
+    let plain = match repo::config_value(repo.as_ref(), "drive.prompt")?.as_deref() {
+        Some("plain") => true,
+        Some("fancy") => false,
+        Some(other) => {
+            eprintln!(
+                "The prompt style `{}` in `drive.prompt` is invalid, expected `plain` or `fancy`.",
+                other
+            );
+            dumb_terminal()
+        }
+        None => dumb_terminal(),
+    };
+
+    Ok(if plain {
+        Ui::Plain(plain::PlainUi)
+    } else {
+        Ui::Console(tui::ConsoleUi)
+    })
+}
+
+/// The label, or the label with the lowest number appended that no email has yet.
+fn unused_label(emails: &[Email], label: &str) -> String {
+    let taken = |candidate: &str| emails.iter().any(|e| e.label == candidate);
+    if !taken(label) {
+        return String::from(label);
+    }
+    (2..)
+        .map(|n| format!("{}-{}", label, n))
+        .find(|candidate| !taken(candidate))
+        .expect("there are fewer emails than numbers")
+}
+
+#[cfg(unix)]
+fn stdin_is_terminal() -> bool {
+    // SAFETY: isatty only inspects the file descriptor
+    unsafe { libc::isatty(libc::STDIN_FILENO) == 1 }
+}
+
+#[cfg(windows)]
+fn stdin_is_terminal() -> bool {
+    use winapi::um::{consoleapi::GetConsoleMode, processenv::GetStdHandle, winbase};
+
+    let mut mode = 0;
+    // SAFETY: GetConsoleMode only writes the mode, it fails for anything but a console
+    unsafe { GetConsoleMode(GetStdHandle(winbase::STD_INPUT_HANDLE), &mut mode) != 0 }
+}
+
+#[cfg(not(any(unix, windows)))]
+fn stdin_is_terminal() -> bool {
+    false
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Ui {
+    Console(tui::ConsoleUi),
+    Plain(plain::PlainUi),
+    NoInput(no_input::NoInputUi),
+    /// Interactive prompts whose style is not looked up yet.
+    Unresolved,
+}
+
+impl Ui {
+    /// Looks up the style on the first prompt,
+    /// so that commands that never prompt do not depend on `drive.prompt`.
+    fn resolved(&mut self) -> Result<&mut Self> {
+        if *self == Self::Unresolved {
+            *self = prompt_style()?;
+        }
+        Ok(self)
+    }
+}
+
+impl SelectOne for Ui {
+    fn select_one(&mut self, kind: Kind, items: &[Selectable<'_>]) -> Result<usize> {
+        match self.resolved()? {
+            Self::Console(ui) => ui.select_one(kind, items),
+            Self::Plain(ui) => ui.select_one(kind, items),
+            Self::NoInput(ui) => ui.select_one(kind, items),
+            Self::Unresolved => unreachable!("resolved above"),
+        }
+    }
+}
+
+impl SelectMany for Ui {
+    fn select_many(&mut self, kind: Kind, items: &[Selectable<'_>]) -> Result<Vec<usize>> {
+        match self.resolved()? {
+            Self::Console(ui) => ui.select_many(kind, items),
+            Self::Plain(ui) => ui.select_many(kind, items),
+            Self::NoInput(ui) => ui.select_many(kind, items),
+            Self::Unresolved => unreachable!("resolved above"),
+        }
+    }
+}
+
+impl PromptText for Ui {
+    fn prompt_for_text<V: Validator>(
+        &mut self,
+        field: Field,
+        id: &str,
+        initial: Option<String>,
+        validator: V,
+    ) -> Result<String> {
+        match self.resolved()? {
+            Self::Console(ui) => ui.prompt_for_text(field, id, initial, validator),
+            Self::Plain(ui) => ui.prompt_for_text(field, id, initial, validator),
+            Self::NoInput(ui) => ui.prompt_for_text(field, id, initial, validator),
+            Self::Unresolved => unreachable!("resolved above"),
+        }
+    }
+}
+
+impl PromptAlias for Ui {
+    fn prompt_for_alias<V: Validator>(&mut self, kind: Kind, validator: V) -> Result<String> {
+        match self.resolved()? {
+            Self::Console(ui) => ui.prompt_for_alias(kind, validator),
+            Self::Plain(ui) => ui.prompt_for_alias(kind, validator),
+            Self::NoInput(ui) => ui.prompt_for_alias(kind, validator),
+            Self::Unresolved => unreachable!("resolved above"),
+        }
+    }
 }
 
 impl<'a, T: SelectOne> SelectOne for &'a mut T {
@@ -182,13 +376,21 @@ fn selectable_items<'config>(
     config: &'config Config,
     pre_select: &[Id],
 ) -> Vec<Selectable<'config>> {
+    // the session keeps navigators with another email as `alias:label`
+    let checked = |alias: &Id| {
+        pre_select
+            .iter()
+            .any(|id| id == alias || id.rsplit_once(':').map_or(false, |(id, _)| id == &**alias))
+    };
     match kind {
         Kind::Navigator => config
             .navigators
             .iter()
             .map(|nav| Selectable {
                 item: &nav.alias,
-                checked: pre_select.contains(&nav.alias),
+                name: &nav.name,
+                email: &nav.email,
+                checked: checked(&nav.alias),
             })
             .collect(),
         Kind::Driver => config
@@ -196,7 +398,9 @@ fn selectable_items<'config>(
             .iter()
             .map(|drv| Selectable {
                 item: &drv.navigator.alias,
-                checked: pre_select.contains(&drv.navigator.alias),
+                name: &drv.navigator.name,
+                email: &drv.navigator.email,
+                checked: checked(&drv.navigator.alias),
             })
             .collect(),
     }
@@ -218,25 +422,46 @@ trait PromptTextExt: PromptText + Sized {
     ) -> Result<Navigator> {
         let PartialIdNav {
             id,
+            aliases,
             name,
             email,
+            emails,
             key: _,
+            key_format: _,
         } = partial;
         let validator = self.validator::<NavigatorSeat>(CheckMode::MustExist, config);
         let existing = self.verify_alias(validator, &id)?;
-        self.finish_nav(id, name, email, existing)
+        // a secondary alias edits the entry of its primary alias
+        let id = existing.map_or(id, |n| n.alias.clone());
+        let nav = self.finish_nav((id, aliases), name, (email, emails), existing)?;
+        check_aliases::<NavigatorSeat>(config, &nav)?;
+
+        Ok(nav)
     }
 
     fn complete_existing_drv(&mut self, partial: PartialIdNav, config: &Config) -> Result<Driver> {
         let PartialIdNav {
             id,
+            aliases,
             name,
             email,
+            emails,
             key,
+            key_format,
         } = partial;
         let validator = self.validator::<DriverSeat>(CheckMode::MustExist, config);
         let existing = self.verify_alias(validator, &id)?;
-        self.finish_drv(id, name, email, key, existing)
+        let id = existing.map_or(id, |d| d.navigator.alias.clone());
+        let drv = self.finish_drv(
+            (id, aliases),
+            name,
+            (email, emails),
+            (key, key_format),
+            existing,
+        )?;
+        check_aliases::<DriverSeat>(config, &drv.navigator)?;
+
+        Ok(drv)
     }
 
     fn validator<'config, T: Seat>(
@@ -264,9 +489,9 @@ trait PromptTextExt: PromptText + Sized {
 
     fn finish_nav(
         &mut self,
-        alias: Id,
+        (alias, aliases): (Id, Option<String>),
         name: Option<String>,
-        email: Option<String>,
+        (email, emails): (Option<String>, Option<String>),
         existing: Option<&Navigator>,
     ) -> Result<Navigator> {
         let name = self.prompt_for(
@@ -279,30 +504,94 @@ trait PromptTextExt: PromptText + Sized {
             &alias,
             email.or_else(|| existing.map(|n| n.email.clone())),
         )?;
+        let mut emails = match emails {
+            Some(emails) => parse_emails(&emails).map_err(|e| eyre!(e))?,
+            None => existing.map(|n| n.emails.clone()).unwrap_or_default(),
+        };
+
+        // entering the label of another email makes it the default,
+        // and the previous default becomes another email
+        let email = match emails.iter().position(|e| e.label == email) {
+            Some(idx) => {
+                let chosen = emails.remove(idx);
+                if let Some(previous) = existing.map(|n| &n.email) {
+                    if *previous != chosen.address && !emails.iter().any(|e| e.address == *previous)
+                    {
+                        emails.push(Email {
+                            label: unused_label(&emails, "previous"),
+                            address: previous.clone(),
+                        });
+                    }
+                }
+                chosen.address
+            }
+            None => email,
+        };
+
+        // new entries take their other emails only from the arguments
+        if existing.is_some() {
+            let edited =
+                self.prompt_for_text(Field::Emails, &alias, Some(join_emails(&emails)), EmailList)?;
+            emails = parse_emails(&edited).map_err(|e| eyre!(e))?;
+        }
 
-        Ok(Navigator { alias, name, email })
+        let mut aliases = aliases.map_or_else(
+            || existing.map(|n| n.aliases.clone()).unwrap_or_default(),
+            |aliases| parse_aliases(&aliases),
+        );
+        aliases.retain(|a| *a != alias);
+
+        Ok(Navigator {
+            alias,
+            aliases,
+            name,
+            email,
+            emails,
+            archived: existing.map_or(false, |n| n.archived),
+        })
     }
 
     fn finish_drv(
         &mut self,
-        alias: Id,
+        alias: (Id, Option<String>),
         name: Option<String>,
-        email: Option<String>,
-        key: Option<String>,
+        emails: (Option<String>, Option<String>),
+        (key, key_format): (Option<String>, Option<KeyFormat>),
         existing: Option<&Driver>,
     ) -> Result<Driver> {
-        let navigator = self.finish_nav(alias, name, email, existing.map(|d| &d.navigator))?;
-
-        let key = self.prompt_for_text(
-            Field::Key,
+        let navigator = self.finish_nav(alias, name, emails, existing.map(|d| &d.navigator))?;
+        let key_format = key_format
+            .or_else(|| existing.map(|d| d.key_format))
+            .unwrap_or_default();
+        let key = self.prompt_for_key(
             &navigator.alias,
             key.or_else(|| existing.and_then(|d| d.key.clone())),
-            CheckForEmpty::new(Field::Key).with_allow_empty(true),
+            key_format,
         )?;
 
-        let key = if key.is_empty() { None } else { Some(key) };
+        Ok(Driver {
+            navigator,
+            key,
+            key_format,
+        })
+    }
+
+    fn prompt_for_key(
+        &mut self,
+        id: &str,
+        key: Option<String>,
+        key_format: KeyFormat,
+    ) -> Result<Option<String>> {
+        let key = self.prompt_for_text(
+            Field::Key,
+            id,
+            key,
+            CheckForEmpty::new(Field::Key)
+                .with_allow_empty(true)
+                .and_then(SigningKey::new(key_format)),
+        )?;
 
-        Ok(Driver { navigator, key })
+        Ok(if key.is_empty() { None } else { Some(key) })
     }
 }
 
@@ -328,25 +617,43 @@ trait PromptAliasExt: PromptAlias + PromptText + Sized {
     fn complete_new_nav(&mut self, partial: PartialNav, config: &Config) -> Result<Navigator> {
         let PartialNav {
             id,
+            aliases,
             name,
             email,
+            emails,
             key: _,
+            key_format: _,
         } = partial;
 
         let (alias, existing) = self.prompt_alias::<NavigatorSeat>(config, id)?;
-        self.finish_nav(alias, name, email, existing)
+        let nav = self.finish_nav((alias, aliases), name, (email, emails), existing)?;
+        check_aliases::<NavigatorSeat>(config, &nav)?;
+
+        Ok(nav)
     }
 
     fn complete_new_drv(&mut self, partial: PartialNav, config: &Config) -> Result<Driver> {
         let PartialNav {
             id,
+            aliases,
             name,
             email,
+            emails,
             key,
+            key_format,
         } = partial;
 
         let (alias, existing) = self.prompt_alias::<DriverSeat>(config, id)?;
-        self.finish_drv(alias, name, email, key, existing)
+        let drv = self.finish_drv(
+            (alias, aliases),
+            name,
+            (email, emails),
+            (key, key_format),
+            existing,
+        )?;
+        check_aliases::<DriverSeat>(config, &drv.navigator)?;
+
+        Ok(drv)
     }
 }
 
@@ -358,8 +665,18 @@ enum CheckMode {
     MustExist,
 }
 
+/// Checks that none of the secondary aliases belongs to another entry.
+fn check_aliases<T: Seat>(config: &Config, nav: &Navigator) -> Result<()> {
+    for alias in &nav.aliases {
+        if T::find(config, alias).map_or(false, |other| *other.id() != nav.alias) {
+            bail!("Alias {} already exists.", style(&**alias).cyan());
+        }
+    }
+    Ok(())
+}
+
 trait Seat {
-    type Entity;
+    type Entity: IdRef;
 
     fn kind() -> Kind;
 
@@ -377,7 +694,7 @@ impl Seat for DriverSeat {
     }
 
     fn find<'a>(config: &'a Config, id: &str) -> Option<&'a Self::Entity> {
-        config.drivers.iter().find(|n| &*n.navigator.alias == id)
+        config.drivers.iter().find(|d| d.has_alias(id))
     }
 }
 
@@ -392,7 +709,7 @@ impl Seat for NavigatorSeat {
     }
 
     fn find<'a>(config: &'a Config, id: &str) -> Option<&'a Self::Entity> {
-        config.navigators.iter().find(|n| &*n.alias == id)
+        config.navigators.iter().find(|n| n.has_alias(id))
     }
 }
 
@@ -849,6 +1166,22 @@ mod tests {
         assert_eq!(selected, vec![nav1().id(), nav2().id()]);
     }
 
+    #[test]
+    fn test_select_ids_checks_navigators_with_a_label() {
+        let config = Config::from_iter([nav1(), nav2()]);
+
+        let ui = select_many(|_, items| {
+            assert_eq!(items[0].checked, false);
+            assert_eq!(items[1].checked, true);
+            Ok(vec![1])
+        });
+
+        let selected =
+            select_ids_from(ui, Kind::Navigator, &config, &[Id::from("nav2:oss")]).unwrap();
+
+        assert_eq!(selected, vec![nav2().id()]);
+    }
+
     #[test]
     fn test_select_ids_keeps_selection_order() {
         let config = Config::from_iter([nav1(), nav2()]);
@@ -961,6 +1294,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value(&navigator.email)
             .returns(Initial)
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .done();
 
         let config = Config::from_iter([navigator.clone()]);
@@ -982,6 +1318,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value(&navigator.email)
             .returns(Initial)
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .done();
 
         let config = Config::from_iter([navigator.clone()]);
@@ -1127,6 +1466,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value(&driver.navigator.email)
             .returns(Initial)
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .expect(Field::Key)
             .with_initial_value("a key")
             .returns(Initial)
@@ -1156,6 +1498,9 @@ mod tests {
             .expect(Field::Email)
             .with_initial_value(&driver.navigator.email)
             .returns(Initial)
+            .expect(Field::Emails)
+            .with_initial_value("")
+            .returns(Initial)
             .expect(Field::Key)
             .with_initial_value("a key")
             .returns(Initial)
@@ -1177,7 +1522,7 @@ mod tests {
     #[test]
     fn complete_existing_drv_empty_key_is_none() {
         let text = prompt_text(|field, _id, _initial| match field {
-            Field::Key => Ok(String::new()),
+            Field::Key | Field::Emails => Ok(String::new()),
             otherwise => Ok(otherwise.to_string()),
         });
 
@@ -1206,4 +1551,83 @@ mod tests {
 
         assert_eq!(err, "The alias must not be empty.");
     }
+
+    #[test]
+    fn no_input_completes_from_arguments() {
+        let partial = PartialNav::default()
+            .with_id(String::from("nav1"))
+            .with_name(String::from("bernd"))
+            .with_email(String::from("foo@bar.org"));
+
+        let nav = complete_new_nav(no_input::NoInputUi, partial, &Config::default()).unwrap();
+
+        assert_eq!(nav, nav1());
+    }
+
+    #[test]
+    fn no_input_names_the_missing_argument() {
+        let _guard = disable_colors();
+
+        let partial = PartialNav::default()
+            .with_id(String::from("nav1"))
+            .with_name(String::from("bernd"));
+        let err = complete_new_nav(no_input::NoInputUi, partial, &Config::default())
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "The email for nav1 is missing, pass it with --email.");
+
+        let err = complete_new_nav(
+            no_input::NoInputUi,
+            PartialNav::default(),
+            &Config::default(),
+        )
+        .unwrap_err()
+        .to_string();
+        assert_eq!(
+            err,
+            "The alias for the new navigator is missing, pass it with --as."
+        );
+    }
+
+    #[test]
+    fn label_as_email_makes_it_the_default_and_keeps_the_previous_one() {
+        let email = |label: &str, address: &str| Email {
+            label: String::from(label),
+            address: String::from(address),
+        };
+        let navigator = Navigator {
+            emails: vec![
+                email("oss", "bernd@oss.org"),
+                email("previous", "bernd@old.org"),
+            ],
+            ..nav1()
+        };
+        let config = Config::from_iter([navigator.clone()]);
+
+        let nav = complete_existing_nav(
+            no_input::NoInputUi,
+            PartialIdNav::new("nav1").with_email("oss"),
+            &config,
+        )
+        .unwrap();
+
+        assert_eq!(nav.email, "bernd@oss.org");
+        assert_eq!(
+            nav.emails,
+            [
+                email("previous", "bernd@old.org"),
+                email("previous-2", &navigator.email)
+            ]
+        );
+    }
+
+    #[test]
+    fn no_input_keeps_existing_values() {
+        let config = Config::from_iter([drv1(None)]);
+
+        let drv =
+            complete_existing_drv(no_input::NoInputUi, PartialIdNav::new("drv1"), &config).unwrap();
+
+        assert_eq!(drv, drv1(None));
+    }
 }
@@ -0,0 +1,75 @@
+use super::{PromptAlias, PromptText, SelectMany, SelectOne, Selectable};
+use crate::{
+    data::{Field, Kind},
+    ui::validation::Validator,
+};
+use console::style;
+use eyre::{bail, Result};
+
+/// Never prompts and fails with an error that names the missing argument instead.
+/// Values that were given as arguments, or that already exist, are used as they are.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct NoInputUi;
+
+impl SelectOne for NoInputUi {
+    fn select_one(&mut self, kind: Kind, _items: &[Selectable<'_>]) -> Result<usize> {
+        bail!(
+            "Selecting a {} needs input, pass its alias as an argument instead.",
+            kind
+        )
+    }
+}
+
+impl SelectMany for NoInputUi {
+    fn select_many(&mut self, kind: Kind, _items: &[Selectable<'_>]) -> Result<Vec<usize>> {
+        bail!(
+            "Selecting {}s needs input, pass their aliases as arguments instead.",
+            kind
+        )
+    }
+}
+
+impl PromptText for NoInputUi {
+    fn prompt_for_text<V: Validator>(
+        &mut self,
+        field: Field,
+        id: &str,
+        initial: Option<String>,
+        mut validator: V,
+    ) -> Result<String> {
+        match initial {
+            Some(initial) => {
+                validator.validate(&initial)?;
+                Ok(initial)
+            }
+            None if validator.validate("").is_ok() => Ok(String::new()),
+            None => bail!(
+                "The {} for {} is missing, pass it with {}.",
+                field,
+                style(id).cyan(),
+                style(argument(field)).yellow()
+            ),
+        }
+    }
+}
+
+impl PromptAlias for NoInputUi {
+    fn prompt_for_alias<V: Validator>(&mut self, kind: Kind, _validator: V) -> Result<String> {
+        bail!(
+            "The alias for the new {} is missing, pass it with {}.",
+            kind,
+            style(argument(Field::Alias)).yellow()
+        )
+    }
+}
+
+const fn argument(field: Field) -> &'static str {
+    match field {
+        Field::Alias => "--as",
+        Field::Aliases => "--aliases",
+        Field::Name => "--name",
+        Field::Email => "--email",
+        Field::Emails => "--emails",
+        Field::Key => "--key",
+    }
+}
@@ -0,0 +1,381 @@
+use super::{PromptAlias, PromptText, SelectMany, SelectOne, Selectable};
+use crate::{
+    data::{Field, Kind},
+    ui::validation::Validator,
+};
+use eyre::{bail, Result};
+use std::io::{self, BufRead, Write};
+
+/// Prints numbered lists and reads whole lines, without moving the cursor.
+/// For terminals that cannot handle the interactive prompts, e.g. `TERM=dumb`.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub struct PlainUi;
+
+impl SelectOne for PlainUi {
+    fn select_one(&mut self, kind: Kind, items: &[Selectable<'_>]) -> Result<usize> {
+        select_one(&mut io::stdin().lock(), &mut io::stderr(), kind, items)
+    }
+}
+
+impl SelectMany for PlainUi {
+    fn select_many(&mut self, kind: Kind, items: &[Selectable<'_>]) -> Result<Vec<usize>> {
+        select_many(&mut io::stdin().lock(), &mut io::stderr(), kind, items)
+    }
+}
+
+impl PromptText for PlainUi {
+    fn prompt_for_text<V: Validator>(
+        &mut self,
+        field: Field,
+        id: &str,
+        initial: Option<String>,
+        mut validator: V,
+    ) -> Result<String> {
+        let prompt = match initial.as_deref() {
+            Some(initial) if !initial.is_empty() && validator.validate("").is_ok() => format!(
+                "The {} for {} [{}, enter `-` to clear]: ",
+                field, id, initial
+            ),
+            Some(initial) => format!("The {} for {} [{}]: ", field, id, initial),
+            None => format!("The {} for {}: ", field, id),
+        };
+        prompt_for_line(
+            &mut io::stdin().lock(),
+            &mut io::stderr(),
+            &prompt,
+            initial,
+            validator,
+        )
+    }
+}
+
+impl PromptAlias for PlainUi {
+    fn prompt_for_alias<V: Validator>(&mut self, kind: Kind, validator: V) -> Result<String> {
+        let prompt = format!(
+            "The alias for the {}, used as identifier for all other commands: ",
+            kind
+        );
+        prompt_for_line(
+            &mut io::stdin().lock(),
+            &mut io::stderr(),
+            &prompt,
+            None,
+            validator,
+        )
+    }
+}
+
+fn select_one(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    kind: Kind,
+    items: &[Selectable<'_>],
+) -> Result<usize> {
+    print_items(output, items, false)?;
+    let prompt = format!("Select one {} by number or alias: ", kind);
+    loop {
+        let line = read_line(input, output, &prompt)?;
+        match parse_selection(items, &line) {
+            Ok(selected) if selected.len() == 1 => return Ok(selected[0]),
+            Ok(_) => writeln!(output, "Please select exactly one {}.", kind)?,
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+fn select_many(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    kind: Kind,
+    items: &[Selectable<'_>],
+) -> Result<Vec<usize>> {
+    print_items(output, items, true)?;
+    let prompt = format!(
+        concat!(
+            "Select any number of {}s by number or alias, separated by spaces.\n",
+            "  Press [return] to keep the checked ones, enter `-` to select none: "
+        ),
+        kind
+    );
+    loop {
+        let line = read_line(input, output, &prompt)?;
+        match line.trim() {
+            "" => {
+                return Ok(items
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, item)| item.checked)
+                    .map(|(index, _)| index)
+                    .collect());
+            }
+            "-" => return Ok(Vec::new()),
+            line => match parse_selection(items, line) {
+                Ok(selected) => return Ok(selected),
+                Err(e) => writeln!(output, "{}", e)?,
+            },
+        }
+    }
+}
+
+/// Reads a line, where an empty line keeps the initial value and `-` clears it.
+fn prompt_for_line(
+    input: &mut impl BufRead,
+    output: &mut impl Write,
+    prompt: &str,
+    initial: Option<String>,
+    mut validator: impl Validator,
+) -> Result<String> {
+    loop {
+        let line = read_line(input, output, prompt)?;
+        let line = match (line.trim(), &initial) {
+            ("", Some(initial)) => initial.clone(),
+            ("-", Some(initial)) if !initial.is_empty() => String::new(),
+            (line, _) => line.to_string(),
+        };
+        match validator.validate(&line) {
+            Ok(()) => return Ok(line),
+            Err(e) => writeln!(output, "{}", e)?,
+        }
+    }
+}
+
+fn print_items(
+    output: &mut impl Write,
+    items: &[Selectable<'_>],
+    show_checked: bool,
+) -> Result<()> {
+    let width = Selectable::alias_width(items);
+    for (number, item) in (1..).zip(items) {
+        let label = item.label(width);
+        match (show_checked, item.checked) {
+            (true, true) => writeln!(output, "  {:>2}) [x] {}", number, label)?,
+            (true, false) => writeln!(output, "  {:>2}) [ ] {}", number, label)?,
+            (false, _) => writeln!(output, "  {:>2}) {}", number, label)?,
+        }
+    }
+    Ok(())
+}
+
+fn read_line(input: &mut impl BufRead, output: &mut impl Write, prompt: &str) -> Result<String> {
+    write!(output, "{}", prompt)?;
+    output.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        bail!("Reached the end of the input while waiting for an answer.");
+    }
+    Ok(line)
+}
+
+/// Parses the numbers or aliases of the selected items, separated by spaces or commas.
+fn parse_selection(items: &[Selectable<'_>], line: &str) -> Result<Vec<usize>> {
+    let mut selected = Vec::new();
+    for token in line.split(|c: char| c == ',' || c.is_whitespace()) {
+        if token.is_empty() {
+            continue;
+        }
+        let index = match token.parse::<usize>() {
+            Ok(number) if (1..=items.len()).contains(&number) => number - 1,
+            Ok(number) => bail!("There is no item with the number {}.", number),
+            Err(_) => match items.iter().position(|item| item.item == token) {
+                Some(index) => index,
+                None => bail!("There is no item with the alias `{}`.", token),
+            },
+        };
+        if !selected.contains(&index) {
+            selected.push(index);
+        }
+    }
+    Ok(selected)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ui::validation::CheckForEmpty;
+
+    const ITEMS: [Selectable<'static>; 3] = [
+        Selectable {
+            item: "nav1",
+            name: "bernd",
+            email: "foo@bar.org",
+            checked: false,
+        },
+        Selectable {
+            item: "nav2",
+            name: "ronny",
+            email: "baz@bar.org",
+            checked: true,
+        },
+        Selectable {
+            item: "nav3",
+            name: "ralle",
+            email: "qux@bar.org",
+            checked: false,
+        },
+    ];
+
+    #[test]
+    fn select_one_by_number_or_alias() {
+        let mut output = Vec::new();
+        let selected =
+            select_one(&mut "2\n".as_bytes(), &mut output, Kind::Navigator, &ITEMS).unwrap();
+        assert_eq!(selected, 1);
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "   1) nav1 — bernd <foo@bar.org>\n",
+                "   2) nav2 — ronny <baz@bar.org> (current)\n",
+                "   3) nav3 — ralle <qux@bar.org>\n",
+                "Select one navigator by number or alias: "
+            )
+        );
+
+        let mut output = Vec::new();
+        let selected = select_one(
+            &mut "nav3\n".as_bytes(),
+            &mut output,
+            Kind::Navigator,
+            &ITEMS,
+        )
+        .unwrap();
+        assert_eq!(selected, 2);
+    }
+
+    #[test]
+    fn select_one_asks_again_on_invalid_input() {
+        let mut output = Vec::new();
+        let selected = select_one(
+            &mut "4\nnav1 nav2\nfoo\nnav1\n".as_bytes(),
+            &mut output,
+            Kind::Navigator,
+            &ITEMS,
+        )
+        .unwrap();
+        assert_eq!(selected, 0);
+
+        let output = String::from_utf8(output).unwrap();
+        assert!(output.contains("There is no item with the number 4."));
+        assert!(output.contains("Please select exactly one navigator."));
+        assert!(output.contains("There is no item with the alias `foo`."));
+    }
+
+    #[test]
+    fn print_items_aligns_the_identities() {
+        let items = [
+            Selectable {
+                item: "bob",
+                name: "Bob",
+                email: "bob@bar.org",
+                checked: true,
+            },
+            Selectable {
+                item: "robert",
+                name: "Robert",
+                email: "robert@bar.org",
+                checked: false,
+            },
+        ];
+        let mut output = Vec::new();
+        print_items(&mut output, &items, true).unwrap();
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            concat!(
+                "   1) [x] bob    — Bob <bob@bar.org> (current)\n",
+                "   2) [ ] robert — Robert <robert@bar.org>\n",
+            )
+        );
+    }
+
+    #[test]
+    fn select_many_from_a_line() {
+        let select = |line: &str| {
+            select_many(
+                &mut line.as_bytes(),
+                &mut Vec::new(),
+                Kind::Navigator,
+                &ITEMS,
+            )
+            .unwrap()
+        };
+
+        assert_eq!(select("3, nav1 3\n"), vec![2, 0]);
+        assert_eq!(select("\n"), vec![1]);
+        assert_eq!(select("-\n"), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn select_fails_at_the_end_of_input() {
+        let err = select_one(&mut "".as_bytes(), &mut Vec::new(), Kind::Driver, &ITEMS)
+            .unwrap_err()
+            .to_string();
+        assert_eq!(
+            err,
+            "Reached the end of the input while waiting for an answer."
+        );
+    }
+
+    #[test]
+    fn prompt_keeps_the_initial_value() {
+        let mut output = Vec::new();
+        let text = prompt_for_line(
+            &mut "\n".as_bytes(),
+            &mut output,
+            "The name for nav1 [bernd]: ",
+            Some(String::from("bernd")),
+            CheckForEmpty::new(Field::Name),
+        )
+        .unwrap();
+        assert_eq!(text, "bernd");
+    }
+
+    #[test]
+    fn prompt_clears_the_initial_value() {
+        let mut output = Vec::new();
+        let text = prompt_for_line(
+            &mut "-\n".as_bytes(),
+            &mut output,
+            "The signing key for drv1 [my-key.pub, enter `-` to clear]: ",
+            Some(String::from("my-key.pub")),
+            CheckForEmpty::new(Field::Key).with_allow_empty(true),
+        )
+        .unwrap();
+        assert_eq!(text, "");
+
+        let mut output = Vec::new();
+        let err = prompt_for_line(
+            &mut "-\n".as_bytes(),
+            &mut output,
+            "The name for nav1 [bernd]: ",
+            Some(String::from("bernd")),
+            CheckForEmpty::new(Field::Name),
+        )
+        .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "Reached the end of the input while waiting for an answer."
+        );
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "The name for nav1 [bernd]: The name must not be empty.\nThe name for nav1 [bernd]: "
+        );
+    }
+
+    #[test]
+    fn prompt_asks_again_until_valid() {
+        let mut output = Vec::new();
+        let text = prompt_for_line(
+            &mut "\n  ronny \n".as_bytes(),
+            &mut output,
+            "The name for nav2: ",
+            None,
+            CheckForEmpty::new(Field::Name),
+        )
+        .unwrap();
+        assert_eq!(text, "ronny");
+        assert_eq!(
+            String::from_utf8(output).unwrap(),
+            "The name for nav2: The name must not be empty.\nThe name for nav2: "
+        );
+    }
+}
@@ -6,9 +6,9 @@ use crate::{
     data::{Field, Kind},
     ui::validation::Validator,
 };
-use console::{style, Style, StyledObject};
-use dialoguer::{theme::ColorfulTheme, theme::Theme, Input, MultiSelect, Select};
-use eyre::Result;
+use console::{style, Key, Style, StyledObject, Term};
+use dialoguer::{theme::ColorfulTheme, theme::Theme, Input};
+use eyre::{bail, Result};
 use once_cell::sync::Lazy;
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
@@ -16,36 +16,200 @@ pub struct ConsoleUi;
 
 impl SelectOne for ConsoleUi {
     fn select_one(&mut self, kind: Kind, items: &[Selectable<'_>]) -> Result<usize> {
-        let mut select = Select::with_theme(&*THEME);
-        select.with_prompt(format!(
-            "Select one {}\n  Use arrows to select, [return] to confirm",
-            kind
-        ));
-
-        for item in items {
-            select.item(item.item);
+        // there would be nothing to confirm
+        if items.is_empty() {
+            bail!("There are no {}s to select from.", kind);
         }
+        let prompt = format!(
+            "Select one {}\n  Type to filter, use arrows to select, [return] to confirm",
+            kind
+        );
+        let chosen = Picker::new(items, false).interact(&prompt)?;
 
-        let chosen = select.interact()?;
-
-        Ok(chosen)
+        Ok(chosen[0])
     }
 }
 
 impl SelectMany for ConsoleUi {
     fn select_many(&mut self, kind: Kind, items: &[Selectable<'_>]) -> Result<Vec<usize>> {
-        let mut ms = MultiSelect::with_theme(&*THEME);
-
-        ms.with_prompt(format!(
-            "Select any number {}(s)\n  Use [space] to select, [return] to confirm",
+        let prompt = format!(
+            "Select any number {}(s)\n  Type to filter, use [space] to select, [return] to confirm",
             kind
+        );
+        let chosen = Picker::new(items, true).interact(&prompt)?;
+        Ok(chosen)
+    }
+}
+
+/// A list of items that is narrowed down by typing, which the dialoguer prompts cannot do.
+struct Picker<'a> {
+    aliases: Vec<&'a str>,
+    labels: Vec<String>,
+    /// What the query is matched against: the alias, name, and email in lower case.
+    texts: Vec<String>,
+    checked: Vec<bool>,
+    multi: bool,
+    query: String,
+    /// The position of the active item among the matches.
+    active: usize,
+}
+
+impl<'a> Picker<'a> {
+    fn new(items: &[Selectable<'a>], multi: bool) -> Self {
+        let width = Selectable::alias_width(items);
+        Self {
+            aliases: items.iter().map(|item| item.item).collect(),
+            labels: items.iter().map(|item| item.label(width)).collect(),
+            texts: items
+                .iter()
+                .map(|item| format!("{} {} {}", item.item, item.name, item.email).to_lowercase())
+                .collect(),
+            checked: items.iter().map(|item| item.checked).collect(),
+            multi,
+            query: String::new(),
+            active: 0,
+        }
+    }
+
+    /// The items that contain every word of the query, ignoring case.
+    fn matches(&self) -> Vec<usize> {
+        let query = self.query.to_lowercase();
+        self.texts
+            .iter()
+            .enumerate()
+            .filter(|(_, text)| query.split_whitespace().all(|word| text.contains(word)))
+            .map(|(index, _)| index)
+            .collect()
+    }
+
+    /// Handles a key press and returns whether the selection is confirmed.
+    /// Space checks items when selecting many, so the query can only be a single word there.
+    fn press(&mut self, key: Key) -> bool {
+        let matches = self.matches();
+        match key {
+            Key::Enter => return self.multi || !matches.is_empty(),
+            Key::ArrowUp => {
+                self.active = self
+                    .active
+                    .checked_sub(1)
+                    .unwrap_or_else(|| matches.len().saturating_sub(1));
+            }
+            Key::ArrowDown => {
+                self.active = if self.active + 1 < matches.len() {
+                    self.active + 1
+                } else {
+                    0
+                };
+            }
+            Key::Char(' ') if self.multi => {
+                if let Some(&index) = matches.get(self.active) {
+                    self.checked[index] = !self.checked[index];
+                }
+            }
+            Key::Char(c) if !c.is_control() => {
+                self.query.push(c);
+                self.active = 0;
+            }
+            Key::Backspace => {
+                self.query.pop();
+                self.active = 0;
+            }
+            Key::Escape => {
+                self.query.clear();
+                self.active = 0;
+            }
+            _ => {}
+        }
+        false
+    }
+
+    fn selection(&self) -> Vec<usize> {
+        if self.multi {
+            self.checked
+                .iter()
+                .enumerate()
+                .filter(|(_, &checked)| checked)
+                .map(|(index, _)| index)
+                .collect()
+        } else {
+            self.matches()
+                .get(self.active)
+                .copied()
+                .into_iter()
+                .collect()
+        }
+    }
+
+    /// The lines of the prompt, showing at most `rows` of the matches around the active one.
+    fn render(&self, prompt: &str, rows: usize) -> Result<Vec<String>> {
+        let mut header = String::new();
+        THEME.format_select_prompt(&mut header, prompt)?;
+        let mut lines = header.lines().map(String::from).collect::<Vec<_>>();
+        lines.push(format!(
+            "  {}",
+            THEME
+                .theme
+                .hint_style
+                .apply_to(format!("Filter: {}", self.query))
         ));
-        for item in items {
-            ms.item_checked(item.item, item.checked);
+
+        let matches = self.matches();
+        let skip = (self.active + 1).saturating_sub(rows);
+        for (position, &index) in matches.iter().enumerate().skip(skip).take(rows) {
+            let mut line = String::new();
+            let active = position == self.active;
+            if self.multi {
+                THEME.format_multi_select_prompt_item(
+                    &mut line,
+                    &self.labels[index],
+                    self.checked[index],
+                    active,
+                )?;
+            } else {
+                THEME.format_select_prompt_item(&mut line, &self.labels[index], active)?;
+            }
+            lines.push(line);
         }
+        Ok(lines)
+    }
 
-        let chosen = ms.interact()?;
-        Ok(chosen)
+    fn interact(mut self, prompt: &str) -> Result<Vec<usize>> {
+        let term = Term::stderr();
+        let rows = usize::from(term.size().0).saturating_sub(4).max(1);
+
+        term.hide_cursor()?;
+        let confirmed = self.read_keys(&term, prompt, rows);
+        term.show_cursor()?;
+        confirmed?;
+
+        let selection = self.selection();
+        let chosen = selection
+            .iter()
+            .map(|&index| self.aliases[index])
+            .collect::<Vec<_>>();
+        let mut summary = String::new();
+        if self.multi {
+            THEME.format_multi_select_prompt_selection(&mut summary, prompt, &chosen)?;
+        } else {
+            THEME.format_select_prompt_selection(&mut summary, prompt, chosen[0])?;
+        }
+        term.write_line(&summary)?;
+
+        Ok(selection)
+    }
+
+    fn read_keys(&mut self, term: &Term, prompt: &str, rows: usize) -> Result<()> {
+        loop {
+            let lines = self.render(prompt, rows)?;
+            for line in &lines {
+                term.write_line(line)?;
+            }
+            let confirmed = self.press(term.read_key()?);
+            term.clear_last_lines(lines.len())?;
+            if confirmed {
+                return Ok(());
+            }
+        }
     }
 }
 
@@ -252,3 +416,98 @@ impl Theme for PrettyTheme {
         Theme::format_sort_prompt_item(&self.theme, f, text, picked, active)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ITEMS: [Selectable<'static>; 3] = [
+        Selectable {
+            item: "bob",
+            name: "Bob",
+            email: "bob@bar.org",
+            checked: false,
+        },
+        Selectable {
+            item: "rob",
+            name: "Robert",
+            email: "robert@bar.org",
+            checked: true,
+        },
+        Selectable {
+            item: "alice",
+            name: "Alice",
+            email: "alice@baz.org",
+            checked: false,
+        },
+    ];
+
+    fn type_keys(picker: &mut Picker<'_>, keys: &str) {
+        for c in keys.chars() {
+            assert!(!picker.press(Key::Char(c)));
+        }
+    }
+
+    #[test]
+    fn filter_by_alias_name_or_email() {
+        let mut picker = Picker::new(&ITEMS, false);
+        assert_eq!(picker.matches(), [0, 1, 2]);
+
+        type_keys(&mut picker, "ROB");
+        assert_eq!(picker.matches(), [1]);
+
+        picker.press(Key::Escape);
+        type_keys(&mut picker, "bar b");
+        assert_eq!(picker.matches(), [0, 1]);
+
+        picker.press(Key::Backspace);
+        picker.press(Key::Backspace);
+        assert_eq!(picker.query, "bar");
+
+        picker.press(Key::Escape);
+        type_keys(&mut picker, "baz");
+        assert_eq!(picker.matches(), [2]);
+
+        picker.press(Key::Escape);
+        type_keys(&mut picker, "cur");
+        assert_eq!(picker.matches(), Vec::<usize>::new());
+    }
+
+    #[test]
+    fn select_one_of_no_items_fails() {
+        let err = ConsoleUi
+            .select_one(Kind::Navigator, &[])
+            .unwrap_err()
+            .to_string();
+        assert_eq!(err, "There are no navigators to select from.");
+    }
+
+    #[test]
+    fn select_one_among_the_matches() {
+        let mut picker = Picker::new(&ITEMS, false);
+        type_keys(&mut picker, "bar");
+        picker.press(Key::ArrowDown);
+        assert!(picker.press(Key::Enter));
+        assert_eq!(picker.selection(), [1]);
+
+        picker.press(Key::ArrowDown);
+        assert_eq!(picker.selection(), [0]);
+        picker.press(Key::ArrowUp);
+        assert_eq!(picker.selection(), [1]);
+
+        type_keys(&mut picker, "nobody");
+        assert!(!picker.press(Key::Enter));
+    }
+
+    #[test]
+    fn select_many_keeps_checks_outside_of_the_filter() {
+        let mut picker = Picker::new(&ITEMS, true);
+        type_keys(&mut picker, "alice");
+        picker.press(Key::Char(' '));
+        assert_eq!(picker.query, "alice");
+
+        type_keys(&mut picker, "nobody");
+        assert!(picker.press(Key::Enter));
+        assert_eq!(picker.selection(), [1, 2]);
+    }
+}
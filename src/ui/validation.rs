@@ -1,8 +1,12 @@
 use super::{CheckMode, Seat};
-use crate::{config::Config, data::Field};
+use crate::{
+    config::Config,
+    data::{parse_emails, Field, KeyFormat},
+};
 use console::style;
+use directories::BaseDirs;
 use eyre::{bail, eyre, Result};
-use std::{cell::Cell, marker::PhantomData};
+use std::{cell::Cell, fs::File, marker::PhantomData, path::PathBuf};
 
 pub trait Validator {
     fn validate(&mut self, input: &str) -> Result<()>;
@@ -107,6 +111,56 @@ impl<A, B> AndThen<A, B> {
     }
 }
 
+/// Checks that an SSH signing key, which is not given literally, points to a readable file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SigningKey {
+    format: KeyFormat,
+}
+
+impl SigningKey {
+    pub const fn new(format: KeyFormat) -> Self {
+        Self { format }
+    }
+}
+
+impl Validator for SigningKey {
+    fn validate(&mut self, input: &str) -> Result<()> {
+        let key = input.trim();
+        if self.format != KeyFormat::Ssh || key.is_empty() || is_literal_ssh_key(key) {
+            return Ok(());
+        }
+
+        let path = match key.strip_prefix("~/") {
+            Some(rest) => BaseDirs::new()
+                .map_or_else(|| PathBuf::from(key), |dirs| dirs.home_dir().join(rest)),
+            None => PathBuf::from(key),
+        };
+        match File::open(&path) {
+            Ok(_) => Ok(()),
+            Err(e) => bail!(
+                "The SSH key {} cannot be read: {}",
+                style(path.display()).cyan(),
+                e
+            ),
+        }
+    }
+}
+
+/// Git takes a public key as is if it is prefixed with `key::` or starts with `ssh-`.
+fn is_literal_ssh_key(key: &str) -> bool {
+    key.starts_with("key::") || key.starts_with("ssh-")
+}
+
+/// Checks a comma separated list of `label=email`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct EmailList;
+
+impl Validator for EmailList {
+    fn validate(&mut self, input: &str) -> Result<()> {
+        parse_emails(input).map(drop).map_err(|e| eyre!(e))
+    }
+}
+
 #[derive(Copy, Clone)]
 pub(super) struct Lookup<'config, T> {
     config: &'config Config,
@@ -144,3 +198,33 @@ impl<T: Seat> Validator for Lookup<'_, T> {
         Ok(())
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ssh_key_must_be_readable() {
+        let mut validator = SigningKey::new(KeyFormat::Ssh);
+        assert!(validator.validate("").is_ok());
+        assert!(validator.validate("key::ssh-ed25519 AAAAC3Nza").is_ok());
+        assert!(validator.validate("ssh-ed25519 AAAAC3Nza").is_ok());
+        assert!(validator.validate(file!()).is_ok());
+        assert!(validator.validate("does/not/exist.pub").is_err());
+    }
+
+    #[test]
+    fn email_list_must_have_labels() {
+        assert!(EmailList.validate("").is_ok());
+        assert!(EmailList
+            .validate("oss=foo@bar.org, home=foo@home.net")
+            .is_ok());
+        assert!(EmailList.validate("foo@bar.org").is_err());
+    }
+
+    #[test]
+    fn other_keys_are_not_checked() {
+        let mut validator = SigningKey::new(KeyFormat::OpenPgp);
+        assert!(validator.validate("does/not/exist.pub").is_ok());
+    }
+}
@@ -0,0 +1,152 @@
+use crate::{
+    config::Config,
+    data::{Id, Modification, Session, VerifyCommits},
+    drive::{self, HistoryEntry},
+    message,
+    repo::{Git, Repository},
+};
+use console::style;
+use eyre::{bail, eyre, Result};
+
+pub fn run(verify: VerifyCommits, config: &Config, session: &Session) -> Result<Modification> {
+    let repo = Repository::discover()?;
+    let trailers = drive::navigator_trailers(session, &repo)?;
+    let label = drive::email_label(Some(&repo))?;
+
+    let history = if verify.navigators.is_empty() {
+        drive::session_history(session.scope)?
+    } else {
+        vec![HistoryEntry {
+            started: 0,
+            navigators: verify.navigators,
+        }]
+    };
+    if history.is_empty() {
+        bail!(
+            "There is no session history to take the navigators from, use {} to name them.",
+            style("--with").yellow()
+        );
+    }
+
+    let log = Git::new(["log", "-z", "--format=%H%n%ct%n%B"])
+        .arg(&verify.range)
+        .arg("--")
+        .run()?;
+
+    let mut checked = 0_usize;
+    let mut failed = Vec::new();
+    for raw in log.split('\0').filter(|c| !c.trim().is_empty()) {
+        let commit = LoggedCommit::parse(raw)
+            .ok_or_else(|| eyre!("Could not read the commit from `git log`: {}", raw))?;
+        let ids = match navigators_at(&history, commit.time) {
+            Some(ids) => ids,
+            None => continue,
+        };
+        let navigators = ids
+            .iter()
+            .map(|id| drive::match_navigator(id, config, label.as_deref()))
+            .collect::<Result<Vec<_>>>()?;
+        let expected = drive::trailer_lines(&trailers, &navigators, None)?;
+
+        checked += 1;
+        let missing = message::missing_trailers(commit.message, &expected);
+        if !missing.is_empty() {
+            failed.push((commit, missing));
+        }
+    }
+
+    if failed.is_empty() {
+        println!(
+            "All {} commit(s) made while driving have the trailers.",
+            checked
+        );
+        return Ok(Modification::Unchanged);
+    }
+
+    for (commit, missing) in &failed {
+        eprintln!(
+            "{} {}",
+            style(&commit.sha[..commit.sha.len().min(7)]).cyan(),
+            commit.subject()
+        );
+        for line in missing {
+            eprintln!("    missing {}", style(line).yellow());
+        }
+    }
+
+    bail!(
+        "{} of {} commit(s) made while driving are missing trailers.",
+        failed.len(),
+        checked
+    )
+}
+
+/// The navigators of the last session that started before the commit, if any.
+fn navigators_at(history: &[HistoryEntry], time: u64) -> Option<&[Id]> {
+    history
+        .iter()
+        .rev()
+        .find(|entry| entry.started <= time)
+        .map(|entry| entry.navigators.as_slice())
+        .filter(|navigators| !navigators.is_empty())
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct LoggedCommit<'a> {
+    sha: &'a str,
+    /// The committer date, in seconds since the unix epoch.
+    time: u64,
+    message: &'a str,
+}
+
+impl<'a> LoggedCommit<'a> {
+    /// Parses one commit in the format `%H%n%ct%n%B`.
+    fn parse(raw: &'a str) -> Option<Self> {
+        let raw = raw.trim_start_matches('\n');
+        let (sha, rest) = raw.split_once('\n')?;
+        let (time, message) = rest.split_once('\n').unwrap_or((rest, ""));
+        Some(Self {
+            sha,
+            time: time.parse().ok()?,
+            message,
+        })
+    }
+
+    fn subject(&self) -> &'a str {
+        self.message.lines().next().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_logged_commit() {
+        let commit = LoggedCommit::parse(
+            "8d6b1b5bd6e0e1c3ba6a3c0ab5d4d9c8fb2fc9a3\n1700000000\nSubject\n\nBody\n",
+        )
+        .unwrap();
+        assert_eq!(commit.sha, "8d6b1b5bd6e0e1c3ba6a3c0ab5d4d9c8fb2fc9a3");
+        assert_eq!(commit.time, 1_700_000_000);
+        assert_eq!(commit.subject(), "Subject");
+    }
+
+    #[test]
+    fn navigators_of_the_session_at_the_commit() {
+        let history = [
+            HistoryEntry {
+                started: 100,
+                navigators: vec![Id::from("nav1")],
+            },
+            HistoryEntry {
+                started: 200,
+                navigators: Vec::new(),
+            },
+        ];
+
+        assert_eq!(navigators_at(&history, 50), None);
+        assert_eq!(navigators_at(&history, 150), Some(&[Id::from("nav1")][..]));
+        assert_eq!(navigators_at(&history, 250), None);
+    }
+}